@@ -0,0 +1,386 @@
+# Replies in English, which every other locale falls back to.
+#
+# Command names and descriptions stay in the source code for English. Other locales translate them
+# with a message named `cmd-` and the command's qualified name with dashes, such as
+# `cmd-prefix-set`. Its value is the name, its `description` attribute the description, and an
+# attribute named after a parameter describes that parameter.
+
+## Formatting
+
+# $category is the English ordinal plural category of $number: one, two, few or other.
+ordinal = { $number }{ $category ->
+    [one] st
+    [two] nd
+    [few] rd
+   *[other] th
+}
+month = { $month ->
+    [1] January
+    [2] February
+    [3] March
+    [4] April
+    [5] May
+    [6] June
+    [7] July
+    [8] August
+    [9] September
+    [10] October
+    [11] November
+   *[12] December
+}
+date-month-day = { $month } { $day }
+date-month-year = { $month }, { $year }
+date-full = { $month } { $day }, { $year }
+title-unavailable = Title unavailable.
+description-unavailable = No description available.
+anilist-footer = Data provided by Anilist.co
+vtuber-wiki-footer = Data provided by the Virtual YouTuber Wiki
+
+## Errors
+
+error-not-found = No results for `{ $query }`.
+error-upstream-timeout = { $service } took too long to answer, please try again later.
+error-rate-limited = Rate limited, try again in { $seconds }s.
+error-missing-permission-user = You need the { $permissions } permission for this.
+error-missing-permission-bot = I need the { $permissions } permission for this.
+error-invalid-input = Invalid input: { $reason }
+error-discord = Discord rejected the request, please try again later.
+error-internal = Something went wrong while processing the command.
+error-id = Error ID
+shutdown-refused = The bot is shutting down, please try again in a moment.
+
+## Pagination
+
+pagination-not-yours = Only { $user } can use these buttons.
+pagination-select = Select
+choose-not-yours = Only { $user } can pick from this menu.
+choose-picked = Picked **{ $option }**.
+choose-nothing-picked = Nothing was picked.
+
+## Prefix
+
+prefix-current = The prefix for this server is `{ $prefix }`.
+prefix-default = This server uses the default prefix `{ $prefix }`.
+prefix-additional = Also accepted: { $prefixes }
+prefix-mention = You can always mention me instead: { $mention }
+prefix-changed = Prefix set to `{ $prefix }`.
+prefix-reset-to-default = Prefix reset to the default.
+prefix-invalid-whitespace = The prefix must not be empty or contain whitespace.
+prefix-too-long = The prefix may be at most 16 characters long.
+
+## Settings
+
+settings-category-disabled = { $category } commands are disabled in this server.
+settings-title = Server Settings
+settings-prefix = Prefix
+settings-disabled-categories = Disabled Categories
+settings-log-channel = Log Channel
+settings-locale = Locale
+settings-none = None
+settings-default = Default
+settings-category-changed = { $enabled ->
+    [true] { $category } commands enabled.
+   *[false] { $category } commands disabled.
+}
+settings-log-channel-changed = Log channel set to { $channel }.
+settings-log-channel-removed = Log channel removed.
+settings-locale-unsupported = `{ $locale }` is not a locale supported by Discord.
+settings-locale-changed = Locale set to `{ $locale }`.
+settings-locale-reset = Locale reset to the default.
+settings-changed = Settings changed
+
+## Help
+
+help-title = Commands
+help-footer = Use /help followed by a command for details.
+help-category-general = General
+help-category-fun = Fun
+help-category-weeb = Anime & Manga
+help-category-owner = Owner
+help-usage = Usage
+help-subcommands = Subcommands
+help-parameters = Parameters
+help-optional = optional
+help-aliases = Aliases
+help-cooldown = Cooldown
+help-cooldown-global = { $seconds }s for everyone
+help-cooldown-user = { $seconds }s per user
+help-cooldown-guild = { $seconds }s per server
+help-cooldown-channel = { $seconds }s per channel
+help-cooldown-member = { $seconds }s per member
+help-examples = Examples
+help-context-menu = Right-click a user and pick **Apps › { $name }**.
+help-owner-only = Only bot owners can use this command.
+
+## General
+
+about-description = A battle maid for the Great Tomb of Nazarick
+about-authors = Authors
+about-source-code = Source Code
+userinfo-discriminator = Discriminator
+userinfo-user-id = User ID
+userinfo-nickname = Nickname
+userinfo-no-nickname = None
+userinfo-created = User Created
+userinfo-joined = Joined Server
+userinfo-joined-unavailable = Unavailable
+guildinfo-guild-id = Guild ID
+guildinfo-members = Members
+guildinfo-features = Features
+guildinfo-boost-level = Nitro Boost Level
+guildinfo-boosts = Nitro Boosts
+guildinfo-created = Guild created at { $date }
+ping-pending = Ping!
+ping-latency = Rest API: { $rest }ms
+    Shard Latency: { $shard }ms
+
+## Fun
+
+eightball-title = 🎱Eightball🎱
+eightball-answer = { $answer ->
+    [0] It is certain.
+    [1] It is decidedly so.
+    [2] Without a doubt.
+    [3] Yes- definitely.
+    [4] You may rely on it.
+    [5] As I see it, yes
+    [6] Most likely
+    [7] Outlook good.
+    [8] Yes.
+    [9] Signs point to yes.
+    [10] Reply hazy, try again.
+    [11] Ask again later.
+    [12] Better not tell you now
+    [13] Cannot predict now.
+    [14] Concentrate and ask again.
+    [15] Don't count on it.
+    [16] My reply is no.
+    [17] My sources say no.
+    [18] Outlook not so good.
+   *[19] Very doubtful.
+}
+ddate-today = Today is { $day }, the { $ordinal } day of { $season } in the YOLD { $year }
+ddate-tibs = Today is St. Tib's Day in the YOLD { $year }
+
+## Owner
+
+info-title = Running Information
+info-version = **Software version**: `{ $name } - v{ $version }`
+info-uptime = **Uptime**: `{ $uptime }`
+info-memory = **Memory Usage**: `{ $megabytes }MB`
+info-cpu = **CPU Usage**: `{ $percent }%`
+info-guilds = **Guilds**: `{ $count }`
+info-users = **Users**: `{ $count }`
+info-dm-channels = **DM Channels**: `{ $count }`
+info-response-cache = **Response Cache**: `{ $entries } entries, { $hits } hits, { $misses } misses`
+flushcache-done = Flushed { $count } cached responses.
+reload-unchanged = Reloaded config, nothing changed.
+reload-changed = Reloaded config!
+    { $changes }
+reload-failed = Keeping the current config, reload failed: { $error }
+rename-done = Name set.
+nickname-done = Nickname set.
+setavatar-done = Set avatar.
+presence-done = Set presence.
+shutdown-requested = Shutting down…
+restart-requested = Restarting…
+restart-done = Restarted in { $seconds }s.
+
+## VTubers
+
+vtuber-event-birthday = { $name }'s birthday
+# $years is the number of years, and $ordinal the same number as an ordinal.
+vtuber-event-anniversary = { $name }'s { $ordinal } debut anniversary
+vtuber-event-debut-anniversary = { $name }'s debut anniversary
+vtuber-announce-birthday = 🎂 Happy birthday, { $name }!
+vtuber-announce-anniversary = 🎉 Happy debut anniversary, { $name }!
+vtuber-announce-today = Today is { $event }.
+vtuber-announce-years = { $name } debuted { $years ->
+    [one] { $years } year
+   *[other] { $years } years
+} ago today.
+
+## Wikis
+
+wiki-unknown = There is no wiki called `{ $name }`, see `/wikis list` for the ones available.
+wiki-not-found = There is no wiki called `{ $name }`.
+wiki-too-many = This server already has { $max } wikis, the most allowed. Remove one first.
+wiki-unusable = Unable to use { $url }: { $error }
+wiki-added = `/wiki { $name }` now searches { $site } ({ $url }).
+wiki-removed = Removed the wiki `{ $name }`.
+wiki-list-title = Wikis
+wiki-name-taken = That name is taken by the built-in wiki.
+wiki-name-length = The name must be between 1 and 32 characters long.
+wiki-name-characters = The name may only contain letters, digits, - and _.
+wiki-url-invalid = That is not a valid URL.
+wiki-url-not-https = The wiki must be served over HTTPS.
+wiki-url-not-public = The wiki must be on a public domain.
+# $kind is what was searched for, articles or vtubers.
+wiki-no-matches = No { $kind ->
+    [vtubers] VTubers
+   *[articles] articles
+} found for `{ $query }`.
+wiki-several-matches = Several { $kind ->
+    [vtubers] VTubers
+   *[articles] articles
+} match `{ $query }`, which one do you mean?
+vtuber-too-many = This server already follows { $max } VTubers, the most allowed. Unfollow one first.
+vtuber-no-dates = The wiki doesn't list a birthday or debut date for { $name }.
+vtuber-dates-both = birthday on { $birthday } and debut on { $debut }
+vtuber-dates-birthday = birthday on { $birthday }
+vtuber-dates-debut = debut on { $debut }
+# $channel is the announcement channel, or none if there is none yet.
+vtuber-followed = Now following { $name }, { $dates }. { $channel ->
+    [none] Pick a channel for announcements with `/vtuber channel`.
+   *[other] Announcements are posted in { $channel }.
+}
+vtuber-updated = Updated { $name }, { $dates }. { $channel ->
+    [none] Pick a channel for announcements with `/vtuber channel`.
+   *[other] Announcements are posted in { $channel }.
+}
+vtuber-not-following = This server doesn't follow { $name }.
+vtuber-unfollowed = No longer following { $name }.
+vtuber-none-followed = This server doesn't follow any VTubers, add one with `/vtuber follow`.
+vtuber-upcoming-title = Upcoming Birthdays and Anniversaries
+vtuber-channel-set = VTuber announcements will be posted in { $channel }.
+vtuber-channel-removed = VTuber announcements stopped.
+
+## AniList
+
+anilist-no-user = There is no AniList user called { $name }.
+anilist-linked = Linked your AniList account { $name }.
+anilist-unlinked = Unlinked your AniList account.
+anilist-not-linked = You have not linked an AniList account.
+anilist-not-linked-self = You have not linked an AniList account, use `/anilist link` first.
+anilist-not-linked-other = { $user } has not linked an AniList account.
+anilist-anime = Anime
+anilist-episodes-watched = Episodes Watched
+anilist-time-watched = Time Watched
+anilist-anime-mean-score = Anime Mean Score
+anilist-manga = Manga
+anilist-chapters-read = Chapters Read
+anilist-volumes-read = Volumes Read
+anilist-manga-mean-score = Manga Mean Score
+anilist-favourite-genres = Favourite Genres
+anilist-hours = { $hours }h
+anilist-days-hours = { $days }d { $hours }h
+anilist-status = { $status ->
+    [CURRENT] Current
+    [PLANNING] Planning
+    [COMPLETED] Completed
+    [DROPPED] Dropped
+    [PAUSED] Paused
+    [REPEATING] Repeating
+   *[other] { $status }
+}
+# $status is the translated list status, or none for every entry.
+anilist-list-title = { $status ->
+    [none] { $user }'s { $kind ->
+        [manga] Manga
+       *[anime] Anime
+    } List
+   *[other] { $user }'s { $status } { $kind ->
+        [manga] Manga
+       *[anime] Anime
+    } List
+}
+anilist-list-empty = { $list } is empty.
+
+## Seasons
+
+season-name = { $season ->
+    [Winter] Winter
+    [Spring] Spring
+    [Summer] Summer
+   *[Fall] Fall
+}
+# $format is TV, ONA or Movie, and $format and $genre are none if not filtered by.
+season-title = { $season } { $year }{ $format ->
+    [none] {""}
+    [Movie] {" "}Movie
+   *[other] {" "}{ $format }
+}{ $genre ->
+    [none] {""}
+   *[other] {" "}{ $genre }
+} Anime
+season-unknown-genre = { $genre } is not an AniList genre.
+season-no-results = No { $title } found.
+season-episodes = { $episodes } eps
+season-members = { $members } members
+
+## Airing
+
+airing-nothing-soon = Nothing airs in the next day.
+airing-schedule-title = Airing in the next 24 hours
+airing-schedule-line = <t:{ $time }:t> (<t:{ $time }:R>) { $title }, episode { $episode }
+# $episodes is the number of episodes, or unknown.
+airing-next-episode = Episode { $episode }{ $episodes ->
+    [unknown] {""}
+   *[other] {" "}of { $episodes }
+} airs <t:{ $time }:R>, on <t:{ $time }:F>.
+airing-finished = This anime has finished airing.
+airing-cancelled = This anime was cancelled.
+airing-unscheduled = No upcoming episodes are scheduled yet.
+airing-later-episodes = Later Episodes
+airing-later-episode = Episode { $episode }: <t:{ $time }:f>
+airing-aired = Episode { $episode }{ $episodes ->
+    [unknown] {""}
+   *[other] {" "}of { $episodes }
+} just aired.
+airing-not-airing = { $title } is no longer airing.
+# $target is the channel announcements are posted in, or dm for the invoker's DMs.
+airing-too-many = { $target ->
+    [dm] You
+   *[other] { $target }
+} already follow { $max } anime, the most allowed. Unfollow one first.
+airing-followed = New episodes of { $title } will be announced { $target ->
+    [dm] to you by DM
+   *[other] in { $target }
+}.
+airing-already-following = { $target ->
+    [dm] You
+   *[other] { $target }
+} already follow { $title }.
+airing-not-following = { $target ->
+    [dm] You
+   *[other] { $target }
+} don't follow that anime.
+airing-unfollowed = New episodes of { $title } will no longer be announced { $target ->
+    [dm] to you by DM
+   *[other] in { $target }
+}.
+airing-none-followed = No anime are followed here.
+airing-list-title = Followed Anime
+airing-list-line = { $title } { $target ->
+    [dm] to you by DM
+   *[other] in { $target }
+}
+airing-manage-server = You need the Manage Server permission to change what is posted in this server's channels.
+
+## Anime, manga, characters, staff and studios
+
+name-unavailable = Name unavailable.
+weeb-status = Status
+weeb-episodes = Episodes
+weeb-chapters = Chapters
+weeb-genres = Genres
+weeb-average-score = Average Score
+weeb-season = Season
+weeb-start-date = Start Date
+weeb-end-date = End Date
+weeb-favourites = Favourites
+weeb-gender = Gender
+weeb-age = Age
+weeb-birthday = Birthday
+weeb-also-known-as = Also Known As
+weeb-appears-in = Appears In
+weeb-voice-actors = Voice Actors ({ $language })
+weeb-unknown-language = Unknown
+weeb-language = Language
+weeb-hometown = Hometown
+weeb-occupations = Occupations
+weeb-notable-works = Notable Works
+weeb-notable-characters = Notable Characters
+weeb-type = Type
+weeb-animation-studio = Animation Studio
+weeb-producer = Producer
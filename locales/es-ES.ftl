@@ -0,0 +1,507 @@
+## Formatting
+
+ordinal = { $number }.º
+month = { $month ->
+    [1] enero
+    [2] febrero
+    [3] marzo
+    [4] abril
+    [5] mayo
+    [6] junio
+    [7] julio
+    [8] agosto
+    [9] septiembre
+    [10] octubre
+    [11] noviembre
+   *[12] diciembre
+}
+date-month-day = { $day } de { $month }
+date-month-year = { $month } de { $year }
+date-full = { $day } de { $month } de { $year }
+title-unavailable = Título no disponible.
+description-unavailable = No hay descripción disponible.
+anilist-footer = Datos proporcionados por Anilist.co
+vtuber-wiki-footer = Datos proporcionados por la Virtual YouTuber Wiki
+
+## Errors
+
+error-not-found = No hay resultados para `{ $query }`.
+error-upstream-timeout = { $service } tardó demasiado en responder, inténtalo de nuevo más tarde.
+error-rate-limited = Demasiadas solicitudes, inténtalo de nuevo en { $seconds }s.
+error-missing-permission-user = Necesitas el permiso { $permissions } para esto.
+error-missing-permission-bot = Necesito el permiso { $permissions } para esto.
+error-invalid-input = Entrada no válida: { $reason }
+error-discord = Discord rechazó la solicitud, inténtalo de nuevo más tarde.
+error-internal = Algo salió mal al procesar el comando.
+error-id = ID de error
+shutdown-refused = El bot se está apagando, inténtalo de nuevo en un momento.
+
+## Pagination
+
+pagination-not-yours = Solo { $user } puede usar estos botones.
+pagination-select = Elegir
+choose-not-yours = Solo { $user } puede elegir en este menú.
+choose-picked = Elegido: **{ $option }**.
+choose-nothing-picked = No se eligió nada.
+
+## Prefix
+
+prefix-current = El prefijo de este servidor es `{ $prefix }`.
+prefix-default = Este servidor usa el prefijo predeterminado `{ $prefix }`.
+prefix-additional = También se aceptan: { $prefixes }
+prefix-mention = Siempre puedes mencionarme en su lugar: { $mention }
+prefix-changed = Prefijo cambiado a `{ $prefix }`.
+prefix-reset-to-default = Prefijo restablecido al predeterminado.
+prefix-invalid-whitespace = El prefijo no puede estar vacío ni contener espacios.
+prefix-too-long = El prefijo puede tener como máximo 16 caracteres.
+
+## Settings
+
+settings-category-disabled = Los comandos de { $category } están desactivados en este servidor.
+settings-title = Ajustes del servidor
+settings-prefix = Prefijo
+settings-disabled-categories = Categorías desactivadas
+settings-log-channel = Canal de registro
+settings-locale = Idioma
+settings-none = Ninguno
+settings-default = Predeterminado
+settings-category-changed = { $enabled ->
+    [true] Comandos de { $category } activados.
+   *[false] Comandos de { $category } desactivados.
+}
+settings-log-channel-changed = Canal de registro cambiado a { $channel }.
+settings-log-channel-removed = Canal de registro eliminado.
+settings-locale-unsupported = `{ $locale }` no es un idioma compatible con Discord.
+settings-locale-changed = Idioma cambiado a `{ $locale }`.
+settings-locale-reset = Idioma restablecido al predeterminado.
+settings-changed = Ajustes cambiados
+
+## Help
+
+help-title = Comandos
+help-footer = Usa /help seguido de un comando para ver los detalles.
+help-category-general = General
+help-category-fun = Diversión
+help-category-weeb = Anime y manga
+help-category-owner = Propietario
+help-usage = Uso
+help-subcommands = Subcomandos
+help-parameters = Parámetros
+help-optional = opcional
+help-aliases = Alias
+help-cooldown = Tiempo de espera
+help-cooldown-global = { $seconds }s para todos
+help-cooldown-user = { $seconds }s por usuario
+help-cooldown-guild = { $seconds }s por servidor
+help-cooldown-channel = { $seconds }s por canal
+help-cooldown-member = { $seconds }s por miembro
+help-examples = Ejemplos
+help-context-menu = Haz clic derecho en un usuario y elige **Aplicaciones › { $name }**.
+help-owner-only = Solo los propietarios del bot pueden usar este comando.
+
+## General
+
+about-description = Una doncella de batalla de la Gran Tumba de Nazarick
+about-authors = Autores
+about-source-code = Código fuente
+userinfo-discriminator = Discriminador
+userinfo-user-id = ID de usuario
+userinfo-nickname = Apodo
+userinfo-no-nickname = Ninguno
+userinfo-created = Usuario creado
+userinfo-joined = Se unió al servidor
+userinfo-joined-unavailable = No disponible
+guildinfo-guild-id = ID del servidor
+guildinfo-members = Miembros
+guildinfo-features = Funciones
+guildinfo-boost-level = Nivel de mejora Nitro
+guildinfo-boosts = Mejoras Nitro
+guildinfo-created = Servidor creado el { $date }
+ping-pending = ¡Ping!
+ping-latency = API REST: { $rest }ms
+    Latencia del shard: { $shard }ms
+
+## Fun
+
+eightball-title = 🎱Bola 8🎱
+eightball-answer = { $answer ->
+    [0] Es cierto.
+    [1] Es decididamente así.
+    [2] Sin duda.
+    [3] Sí, definitivamente.
+    [4] Puedes confiar en ello.
+    [5] Tal como lo veo, sí.
+    [6] Lo más probable.
+    [7] Buenas perspectivas.
+    [8] Sí.
+    [9] Las señales apuntan a que sí.
+    [10] Respuesta confusa, inténtalo de nuevo.
+    [11] Pregunta de nuevo más tarde.
+    [12] Mejor no te lo digo ahora.
+    [13] No puedo predecirlo ahora.
+    [14] Concéntrate y vuelve a preguntar.
+    [15] No cuentes con ello.
+    [16] Mi respuesta es no.
+    [17] Mis fuentes dicen que no.
+    [18] Las perspectivas no son buenas.
+   *[19] Muy dudoso.
+}
+ddate-today = Hoy es { $day }, el { $ordinal } día de { $season } del año { $year } YOLD
+ddate-tibs = Hoy es el Día de San Tib del año { $year } YOLD
+
+## Owner
+
+info-title = Información de ejecución
+info-version = **Versión del software**: `{ $name } - v{ $version }`
+info-uptime = **Tiempo activo**: `{ $uptime }`
+info-memory = **Uso de memoria**: `{ $megabytes }MB`
+info-cpu = **Uso de CPU**: `{ $percent }%`
+info-guilds = **Servidores**: `{ $count }`
+info-users = **Usuarios**: `{ $count }`
+info-dm-channels = **Canales de MD**: `{ $count }`
+info-response-cache = **Caché de respuestas**: `{ $entries } entradas, { $hits } aciertos, { $misses } fallos`
+flushcache-done = Se vaciaron { $count } respuestas en caché.
+reload-unchanged = Configuración recargada, no cambió nada.
+reload-changed = ¡Configuración recargada!
+    { $changes }
+reload-failed = Se mantiene la configuración actual, la recarga falló: { $error }
+rename-done = Nombre cambiado.
+nickname-done = Apodo cambiado.
+setavatar-done = Avatar cambiado.
+presence-done = Presencia cambiada.
+shutdown-requested = Apagando…
+restart-requested = Reiniciando…
+restart-done = Reiniciado en { $seconds } s.
+
+## VTubers
+
+vtuber-event-birthday = el cumpleaños de { $name }
+vtuber-event-anniversary = el { $ordinal } aniversario del debut de { $name }
+vtuber-event-debut-anniversary = el aniversario del debut de { $name }
+vtuber-announce-birthday = 🎂 ¡Feliz cumpleaños, { $name }!
+vtuber-announce-anniversary = 🎉 ¡Feliz aniversario de debut, { $name }!
+vtuber-announce-today = Hoy es { $event }.
+vtuber-announce-years = { $name } debutó hoy hace { $years ->
+    [one] { $years } año
+   *[other] { $years } años
+}.
+
+## Wikis
+
+wiki-unknown = No hay ninguna wiki llamada `{ $name }`, consulta `/wikis list` para ver las disponibles.
+wiki-not-found = No hay ninguna wiki llamada `{ $name }`.
+wiki-too-many = Este servidor ya tiene { $max } wikis, el máximo permitido. Elimina una primero.
+wiki-unusable = No se puede usar { $url }: { $error }
+wiki-added = `/wiki { $name }` ahora busca en { $site } ({ $url }).
+wiki-removed = Se eliminó la wiki `{ $name }`.
+wiki-list-title = Wikis
+wiki-name-taken = Ese nombre lo usa la wiki incorporada.
+wiki-name-length = El nombre debe tener entre 1 y 32 caracteres.
+wiki-name-characters = El nombre solo puede contener letras, dígitos, - y _.
+wiki-url-invalid = Esa no es una URL válida.
+wiki-url-not-https = La wiki debe servirse por HTTPS.
+wiki-url-not-public = La wiki debe estar en un dominio público.
+wiki-no-matches = No se encontraron { $kind ->
+    [vtubers] VTubers
+   *[articles] artículos
+} para `{ $query }`.
+wiki-several-matches = Varios { $kind ->
+    [vtubers] VTubers
+   *[articles] artículos
+} coinciden con `{ $query }`, ¿a cuál te refieres?
+vtuber-too-many = Este servidor ya sigue a { $max } VTubers, el máximo permitido. Deja de seguir a uno primero.
+vtuber-no-dates = La wiki no indica cumpleaños ni fecha de debut para { $name }.
+vtuber-dates-both = cumpleaños el { $birthday } y debut el { $debut }
+vtuber-dates-birthday = cumpleaños el { $birthday }
+vtuber-dates-debut = debut el { $debut }
+vtuber-followed = Ahora se sigue a { $name }, { $dates }. { $channel ->
+    [none] Elige un canal para los anuncios con `/vtuber channel`.
+   *[other] Los anuncios se publican en { $channel }.
+}
+vtuber-updated = Se actualizó { $name }, { $dates }. { $channel ->
+    [none] Elige un canal para los anuncios con `/vtuber channel`.
+   *[other] Los anuncios se publican en { $channel }.
+}
+vtuber-not-following = Este servidor no sigue a { $name }.
+vtuber-unfollowed = Ya no se sigue a { $name }.
+vtuber-none-followed = Este servidor no sigue a ningún VTuber, añade uno con `/vtuber follow`.
+vtuber-upcoming-title = Próximos cumpleaños y aniversarios
+vtuber-channel-set = Los anuncios de VTubers se publicarán en { $channel }.
+vtuber-channel-removed = Se detuvieron los anuncios de VTubers.
+
+## AniList
+
+anilist-no-user = No hay ningún usuario de AniList llamado { $name }.
+anilist-linked = Se vinculó tu cuenta de AniList { $name }.
+anilist-unlinked = Se desvinculó tu cuenta de AniList.
+anilist-not-linked = No has vinculado ninguna cuenta de AniList.
+anilist-not-linked-self = No has vinculado ninguna cuenta de AniList, usa `/anilist link` primero.
+anilist-not-linked-other = { $user } no ha vinculado ninguna cuenta de AniList.
+anilist-anime = Anime
+anilist-episodes-watched = Episodios vistos
+anilist-time-watched = Tiempo visto
+anilist-anime-mean-score = Puntuación media de anime
+anilist-manga = Manga
+anilist-chapters-read = Capítulos leídos
+anilist-volumes-read = Volúmenes leídos
+anilist-manga-mean-score = Puntuación media de manga
+anilist-favourite-genres = Géneros favoritos
+anilist-hours = { $hours } h
+anilist-days-hours = { $days } d { $hours } h
+anilist-status = { $status ->
+    [CURRENT] Actual
+    [PLANNING] Planeado
+    [COMPLETED] Completado
+    [DROPPED] Abandonado
+    [PAUSED] En pausa
+    [REPEATING] Repitiendo
+   *[other] { $status }
+}
+anilist-list-title = Lista de { $kind ->
+    [manga] manga
+   *[anime] anime
+} de { $user }{ $status ->
+    [none] {""}
+   *[other] : { $status }
+}
+anilist-list-empty = { $list } está vacía.
+
+## Seasons
+
+season-name = { $season ->
+    [Winter] Invierno
+    [Spring] Primavera
+    [Summer] Verano
+   *[Fall] Otoño
+}
+season-title = { $format ->
+    [none] Anime
+    [Movie] Películas de anime
+   *[other] Anime { $format }
+}{ $genre ->
+    [none] {""}
+   *[other] {" "}({ $genre })
+} de { $season } { $year }
+season-unknown-genre = { $genre } no es un género de AniList.
+season-no-results = No hay resultados para { $title }.
+season-episodes = { $episodes } eps
+season-members = { $members } miembros
+
+## Airing
+
+airing-nothing-soon = No se emite nada en el próximo día.
+airing-schedule-title = En emisión en las próximas 24 horas
+airing-schedule-line = <t:{ $time }:t> (<t:{ $time }:R>) { $title }, episodio { $episode }
+airing-next-episode = El episodio { $episode }{ $episodes ->
+    [unknown] {""}
+   *[other] {" "}de { $episodes }
+} se emite <t:{ $time }:R>, el <t:{ $time }:F>.
+airing-finished = Este anime ya terminó de emitirse.
+airing-cancelled = Este anime fue cancelado.
+airing-unscheduled = Todavía no hay episodios programados.
+airing-later-episodes = Episodios posteriores
+airing-later-episode = Episodio { $episode }: <t:{ $time }:f>
+airing-aired = El episodio { $episode }{ $episodes ->
+    [unknown] {""}
+   *[other] {" "}de { $episodes }
+} se acaba de emitir.
+airing-not-airing = { $title } ya no se emite.
+airing-too-many = { $target ->
+    [dm] Ya sigues
+   *[other] { $target } ya sigue
+} { $max } animes, el máximo permitido. Deja de seguir uno primero.
+airing-followed = Los nuevos episodios de { $title } se anunciarán { $target ->
+    [dm] por MD
+   *[other] en { $target }
+}.
+airing-already-following = { $target ->
+    [dm] Ya sigues
+   *[other] { $target } ya sigue
+} { $title }.
+airing-not-following = { $target ->
+    [dm] No sigues
+   *[other] { $target } no sigue
+} ese anime.
+airing-unfollowed = Los nuevos episodios de { $title } ya no se anunciarán { $target ->
+    [dm] por MD
+   *[other] en { $target }
+}.
+airing-none-followed = Aquí no se sigue ningún anime.
+airing-list-title = Animes seguidos
+airing-list-line = { $title } { $target ->
+    [dm] por MD
+   *[other] en { $target }
+}
+airing-manage-server = Necesitas el permiso Gestionar servidor para cambiar lo que se publica en los canales de este servidor.
+
+## Anime, manga, characters, staff and studios
+
+name-unavailable = Nombre no disponible.
+weeb-status = Estado
+weeb-episodes = Episodios
+weeb-chapters = Capítulos
+weeb-genres = Géneros
+weeb-average-score = Puntuación media
+weeb-season = Temporada
+weeb-start-date = Fecha de inicio
+weeb-end-date = Fecha de fin
+weeb-favourites = Favoritos
+weeb-gender = Género
+weeb-age = Edad
+weeb-birthday = Cumpleaños
+weeb-also-known-as = También conocido como
+weeb-appears-in = Aparece en
+weeb-voice-actors = Actores de voz ({ $language })
+weeb-unknown-language = Desconocido
+weeb-language = Idioma
+weeb-hometown = Ciudad natal
+weeb-occupations = Ocupaciones
+weeb-notable-works = Obras destacadas
+weeb-notable-characters = Personajes destacados
+weeb-type = Tipo
+weeb-animation-studio = Estudio de animación
+weeb-producer = Productor
+
+## Commands
+
+cmd-help = ayuda
+    .description = Lista los comandos, o explica uno de ellos
+    .command = Comando a explicar, déjalo vacío para listarlos todos
+
+cmd-ping = ping
+    .description = Responde con la latencia actual con Discord.
+cmd-about = acerca-de
+    .description = Muestra información sobre el bot.
+cmd-guildinfo = info-servidor
+    .description = Muestra información variada sobre el servidor.
+
+cmd-prefix = prefijo
+    .description = Muestra o cambia el prefijo de comandos de este servidor.
+cmd-prefix-set = establecer
+    .description = Cambia el prefijo de comandos de este servidor.
+    .prefix = Nuevo prefijo
+cmd-prefix-reset = restablecer
+    .description = Restablece el prefijo de comandos de este servidor al predeterminado.
+cmd-prefix-show = mostrar
+    .description = Muestra los prefijos de comandos de este servidor.
+
+cmd-settings = ajustes
+    .description = Muestra o cambia los ajustes del bot en este servidor.
+cmd-settings-show = mostrar
+    .description = Muestra los ajustes del bot en este servidor.
+cmd-settings-prefix = prefijo
+    .description = Cambia el prefijo de los comandos con prefijo, o lo restablece.
+    .prefix = Nuevo prefijo, déjalo vacío para usar el predeterminado
+cmd-settings-category = categoría
+    .description = Activa o desactiva una categoría de comandos.
+    .category = Categoría de comandos
+    .enabled = Si se pueden usar los comandos
+cmd-settings-logchannel = canal-registro
+    .description = Elige el canal donde se registran los cambios de ajustes.
+    .channel = Canal de registro, déjalo vacío para dejar de registrar
+cmd-settings-locale = idioma
+    .description = Elige el idioma de las respuestas en este servidor.
+    .locale = Código de idioma de Discord como es-ES o ja, déjalo vacío para el predeterminado
+
+cmd-anime = anime
+    .description = Muestra información sobre un anime de AniList
+    .anime_title = Título a buscar
+cmd-manga = manga
+    .description = Muestra información sobre un manga de AniList
+    .manga_title = Título a buscar
+cmd-character = personaje
+    .description = Muestra información sobre un personaje de AniList
+    .name = Nombre a buscar
+cmd-staff = staff
+    .description = Muestra información sobre un actor de voz o miembro del equipo de AniList
+    .name = Nombre a buscar
+cmd-studio = estudio
+    .description = Muestra información sobre un estudio de AniList
+    .name = Nombre a buscar
+
+cmd-airing = emisión
+    .description = Muestra cuándo se emiten los episodios y anuncia los nuevos
+cmd-airing-next = próximo
+    .description = Muestra cuándo se emiten los próximos episodios de un anime, o todo lo que se emite en el próximo día
+    .anime_title = Título a buscar, déjalo vacío para ver la programación del próximo día
+cmd-airing-follow = seguir
+    .description = Anuncia los nuevos episodios de un anime por MD, o en un canal de este servidor
+    .anime_title = Título a buscar
+    .channel = Canal donde publicar, déjalo vacío para recibir un MD
+cmd-airing-unfollow = dejar-de-seguir
+    .description = Deja de anunciar los nuevos episodios de un anime
+    .anime_title = Anime seguido
+    .channel = Canal donde se publican los episodios, déjalo vacío para tus MD
+cmd-airing-list = lista
+    .description = Lista los anime que sigues tú y los canales de este servidor
+
+cmd-anilist = anilist
+    .description = Vincula cuentas de AniList y muestra sus perfiles y listas
+cmd-anilist-link = vincular
+    .description = Vincula tu cuenta de AniList
+    .username = Tu nombre de usuario de AniList
+cmd-anilist-unlink = desvincular
+    .description = Desvincula tu cuenta de AniList
+cmd-anilist-profile = perfil
+    .description = Muestra las estadísticas de AniList de un usuario
+    .user = Usuario a mostrar, déjalo vacío para ti
+cmd-anilist-list = lista
+    .description = Muestra la lista de anime o manga de un usuario
+    .user = Usuario a mostrar, déjalo vacío para ti
+    .status = Mostrar solo las entradas con este estado
+    .kind = Anime o manga, anime si se deja vacío
+
+cmd-season = temporada
+    .description = Lista los anime de una temporada de AniList
+    .season = Temporada, la actual si se deja vacío
+    .year = Año, el actual si se deja vacío
+    .sort = Orden de las entradas, por popularidad si se deja vacío
+    .format = Mostrar solo las entradas de este formato
+    .genre = Mostrar solo las entradas de este género
+
+cmd-vtuber = vtuber
+    .description = Muestra Virtual Youtubers y anuncia sus cumpleaños
+cmd-vtuber-search = buscar
+    .description = Muestra información sobre un Virtual Youtuber
+    .name = Nombre a buscar
+cmd-vtuber-follow = seguir
+    .description = Anuncia el cumpleaños y el aniversario de debut de un Virtual Youtuber en este servidor
+    .name = Nombre a buscar
+cmd-vtuber-unfollow = dejar-de-seguir
+    .description = Deja de anunciar a un Virtual Youtuber en este servidor
+    .name = VTuber seguido
+cmd-vtuber-upcoming = próximos
+    .description = Lista los próximos cumpleaños y aniversarios de debut de los VTubers que sigue este servidor
+cmd-vtuber-channel = canal
+    .description = Elige el canal donde se anuncian los cumpleaños y aniversarios de debut
+    .channel = Canal de anuncios, déjalo vacío para dejar de anunciar
+
+cmd-wiki = wiki
+    .description = Muestra un artículo de una de las wikis de este servidor
+    .site = Wiki en la que buscar
+    .query = Artículo a buscar
+cmd-wikis = wikis
+    .description = Gestiona las wikis disponibles para /wiki en este servidor
+cmd-wikis-add = añadir
+    .description = Añade un sitio MediaWiki a /wiki, o lo cambia
+    .name = Nombre corto para elegir la wiki, como overlord
+    .url = La URL de api.php de la wiki, o su página principal
+    .start = HTML tras el cual empiezan las descripciones, por defecto tras el infobox
+    .end = HTML antes del cual terminan las descripciones, por defecto el primer encabezado
+cmd-wikis-remove = quitar
+    .description = Quita una wiki de /wiki
+    .name = Nombre de la wiki
+cmd-wikis-list = lista
+    .description = Lista las wikis disponibles para /wiki
+
+cmd-eightball = bola8
+    .description = Hazle tu pregunta a la bola 8 mágica y recibe tu fortuna.
+    .question = Tu pregunta
+cmd-darksouls = darksouls
+    .description = Muestra un mensaje de Dark Souls generado al azar.
+cmd-darksouls3 = darksouls3
+    .description = Muestra un mensaje de Dark Souls 3 generado al azar.
+cmd-bloodborne = bloodborne
+    .description = Muestra una nota de Bloodborne generada al azar.
+cmd-ddate = ddate
+    .description = Muestra la fecha actual del calendario discordiano/erisiano
@@ -0,0 +1,491 @@
+## Formatting
+
+ordinal = 第{ $number }
+month = { $month }月
+date-month-day = { $month }{ $day }日
+date-month-year = { $year }年{ $month }
+date-full = { $year }年{ $month }{ $day }日
+title-unavailable = タイトルはありません。
+description-unavailable = 説明はありません。
+anilist-footer = データ提供: Anilist.co
+vtuber-wiki-footer = データ提供: Virtual YouTuber Wiki
+
+## Errors
+
+error-not-found = `{ $query }` の検索結果はありません。
+error-upstream-timeout = { $service } からの応答がありません。しばらくしてからもう一度お試しください。
+error-rate-limited = リクエストが多すぎます。{ $seconds }秒後にもう一度お試しください。
+error-missing-permission-user = この操作には { $permissions } 権限が必要です。
+error-missing-permission-bot = この操作にはボットの { $permissions } 権限が必要です。
+error-invalid-input = 無効な入力です: { $reason }
+error-discord = Discord がリクエストを拒否しました。しばらくしてからもう一度お試しください。
+error-internal = コマンドの処理中に問題が発生しました。
+error-id = エラーID
+shutdown-refused = ボットを停止しています。しばらくしてからもう一度お試しください。
+
+## Pagination
+
+pagination-not-yours = このボタンは { $user } さんだけが使えます。
+pagination-select = 選択
+choose-not-yours = このメニューは { $user } さんだけが選べます。
+choose-picked = **{ $option }** を選びました。
+choose-nothing-picked = 何も選ばれませんでした。
+
+## Prefix
+
+prefix-current = このサーバーのプレフィックスは `{ $prefix }` です。
+prefix-default = このサーバーはデフォルトのプレフィックス `{ $prefix }` を使っています。
+prefix-additional = 他にも使えるもの: { $prefixes }
+prefix-mention = 代わりにいつでもメンションできます: { $mention }
+prefix-changed = プレフィックスを `{ $prefix }` に設定しました。
+prefix-reset-to-default = プレフィックスをデフォルトに戻しました。
+prefix-invalid-whitespace = プレフィックスは空にできず、空白も含められません。
+prefix-too-long = プレフィックスは16文字までです。
+
+## Settings
+
+settings-category-disabled = このサーバーでは { $category } コマンドは無効です。
+settings-title = サーバー設定
+settings-prefix = プレフィックス
+settings-disabled-categories = 無効なカテゴリー
+settings-log-channel = ログチャンネル
+settings-locale = 言語
+settings-none = なし
+settings-default = デフォルト
+settings-category-changed = { $enabled ->
+    [true] { $category } コマンドを有効にしました。
+   *[false] { $category } コマンドを無効にしました。
+}
+settings-log-channel-changed = ログチャンネルを { $channel } に設定しました。
+settings-log-channel-removed = ログチャンネルを解除しました。
+settings-locale-unsupported = `{ $locale }` は Discord が対応している言語ではありません。
+settings-locale-changed = 言語を `{ $locale }` に設定しました。
+settings-locale-reset = 言語をデフォルトに戻しました。
+settings-changed = 設定が変更されました
+
+## Help
+
+help-title = コマンド
+help-footer = 詳しくは /help の後にコマンド名を入力してください。
+help-category-general = 一般
+help-category-fun = お楽しみ
+help-category-weeb = アニメ・マンガ
+help-category-owner = オーナー
+help-usage = 使い方
+help-subcommands = サブコマンド
+help-parameters = パラメーター
+help-optional = 任意
+help-aliases = 別名
+help-cooldown = クールダウン
+help-cooldown-global = 全員で{ $seconds }秒
+help-cooldown-user = ユーザーごとに{ $seconds }秒
+help-cooldown-guild = サーバーごとに{ $seconds }秒
+help-cooldown-channel = チャンネルごとに{ $seconds }秒
+help-cooldown-member = メンバーごとに{ $seconds }秒
+help-examples = 例
+help-context-menu = ユーザーを右クリックして **アプリ › { $name }** を選んでください。
+help-owner-only = このコマンドはボットのオーナーだけが使えます。
+
+## General
+
+about-description = ナザリック地下大墳墓の戦闘メイド
+about-authors = 作者
+about-source-code = ソースコード
+userinfo-discriminator = タグ
+userinfo-user-id = ユーザーID
+userinfo-nickname = ニックネーム
+userinfo-no-nickname = なし
+userinfo-created = アカウント作成日
+userinfo-joined = サーバー参加日
+userinfo-joined-unavailable = 不明
+guildinfo-guild-id = サーバーID
+guildinfo-members = メンバー数
+guildinfo-features = 機能
+guildinfo-boost-level = ニトロブーストレベル
+guildinfo-boosts = ニトロブースト数
+guildinfo-created = サーバー作成日: { $date }
+ping-pending = Ping!
+ping-latency = REST API: { $rest }ms
+    シャードの遅延: { $shard }ms
+
+## Fun
+
+eightball-title = 🎱エイトボール🎱
+eightball-answer = { $answer ->
+    [0] 確実です。
+    [1] 間違いなくそうです。
+    [2] 疑いの余地はありません。
+    [3] はい、絶対に。
+    [4] 頼りにしていいでしょう。
+    [5] 私の見る限り、はい。
+    [6] おそらくそうでしょう。
+    [7] 見通しは良好です。
+    [8] はい。
+    [9] 兆しは「はい」を示しています。
+    [10] はっきりしません。もう一度どうぞ。
+    [11] 後でもう一度聞いてください。
+    [12] 今は言わないほうがいいでしょう。
+    [13] 今は予測できません。
+    [14] 集中してもう一度聞いてください。
+    [15] 期待しないでください。
+    [16] 私の答えは「いいえ」です。
+    [17] 私の情報源は「いいえ」と言っています。
+    [18] 見通しはあまり良くありません。
+   *[19] とても疑わしいです。
+}
+ddate-today = 今日は YOLD { $year } 年、{ $season }の{ $ordinal }日、{ $day }です
+ddate-tibs = 今日は YOLD { $year } 年の聖ティブの日です
+
+## Owner
+
+info-title = 実行情報
+info-version = **ソフトウェアバージョン**: `{ $name } - v{ $version }`
+info-uptime = **稼働時間**: `{ $uptime }`
+info-memory = **メモリ使用量**: `{ $megabytes }MB`
+info-cpu = **CPU使用率**: `{ $percent }%`
+info-guilds = **サーバー数**: `{ $count }`
+info-users = **ユーザー数**: `{ $count }`
+info-dm-channels = **DMチャンネル数**: `{ $count }`
+info-response-cache = **レスポンスキャッシュ**: `{ $entries }件、ヒット{ $hits }回、ミス{ $misses }回`
+flushcache-done = キャッシュされた応答を{ $count }件削除しました。
+reload-unchanged = 設定を再読み込みしました。変更はありません。
+reload-changed = 設定を再読み込みしました！
+    { $changes }
+reload-failed = 再読み込みに失敗したため、現在の設定を維持します: { $error }
+rename-done = 名前を設定しました。
+nickname-done = ニックネームを設定しました。
+setavatar-done = アバターを設定しました。
+presence-done = ステータスを設定しました。
+shutdown-requested = 停止しています…
+restart-requested = 再起動しています…
+restart-done = { $seconds }秒で再起動しました。
+
+## VTubers
+
+vtuber-event-birthday = { $name }さんの誕生日
+vtuber-event-anniversary = { $name }さんのデビュー{ $years }周年
+vtuber-event-debut-anniversary = { $name }さんのデビュー記念日
+vtuber-announce-birthday = 🎂 { $name }さん、お誕生日おめでとうございます！
+vtuber-announce-anniversary = 🎉 { $name }さん、デビュー記念日おめでとうございます！
+vtuber-announce-today = 今日は{ $event }です。
+vtuber-announce-years = { $name }さんは{ $years }年前の今日デビューしました。
+
+## Wikis
+
+wiki-unknown = `{ $name }` という wiki はありません。使える wiki は `/wikis list` で確認できます。
+wiki-not-found = `{ $name }` という wiki はありません。
+wiki-too-many = このサーバーにはすでに上限の{ $max }個の wiki があります。先にどれかを削除してください。
+wiki-unusable = { $url } を使えません: { $error }
+wiki-added = `/wiki { $name }` で { $site } ({ $url }) を検索できるようになりました。
+wiki-removed = wiki `{ $name }` を削除しました。
+wiki-list-title = Wiki 一覧
+wiki-name-taken = その名前は組み込みの wiki が使っています。
+wiki-name-length = 名前は1〜32文字にしてください。
+wiki-name-characters = 名前には英数字、- と _ だけが使えます。
+wiki-url-invalid = 有効な URL ではありません。
+wiki-url-not-https = wiki は HTTPS で提供されている必要があります。
+wiki-url-not-public = wiki は公開ドメインにある必要があります。
+wiki-no-matches = `{ $query }` に一致する{ $kind ->
+    [vtubers] VTuber
+   *[articles] 記事
+}は見つかりませんでした。
+wiki-several-matches = `{ $query }` に一致する{ $kind ->
+    [vtubers] VTuber
+   *[articles] 記事
+}が複数あります。どれのことですか？
+vtuber-too-many = このサーバーはすでに上限の{ $max }人の VTuber をフォローしています。先に誰かのフォローを解除してください。
+vtuber-no-dates = wiki には { $name }さんの誕生日もデビュー日も載っていません。
+vtuber-dates-both = 誕生日は{ $birthday }、デビューは{ $debut }
+vtuber-dates-birthday = 誕生日は{ $birthday }
+vtuber-dates-debut = デビューは{ $debut }
+vtuber-followed = { $name }さんをフォローしました（{ $dates }）。{ $channel ->
+    [none] `/vtuber channel` でお知らせ用のチャンネルを選んでください。
+   *[other] お知らせは { $channel } に投稿されます。
+}
+vtuber-updated = { $name }さんを更新しました（{ $dates }）。{ $channel ->
+    [none] `/vtuber channel` でお知らせ用のチャンネルを選んでください。
+   *[other] お知らせは { $channel } に投稿されます。
+}
+vtuber-not-following = このサーバーは { $name }さんをフォローしていません。
+vtuber-unfollowed = { $name }さんのフォローを解除しました。
+vtuber-none-followed = このサーバーは VTuber をフォローしていません。`/vtuber follow` で追加してください。
+vtuber-upcoming-title = 今後の誕生日と記念日
+vtuber-channel-set = VTuber のお知らせは { $channel } に投稿されます。
+vtuber-channel-removed = VTuber のお知らせを停止しました。
+
+## AniList
+
+anilist-no-user = { $name } という AniList ユーザーはいません。
+anilist-linked = AniList アカウント { $name } を連携しました。
+anilist-unlinked = AniList アカウントの連携を解除しました。
+anilist-not-linked = AniList アカウントが連携されていません。
+anilist-not-linked-self = AniList アカウントが連携されていません。先に `/anilist link` を使ってください。
+anilist-not-linked-other = { $user } さんは AniList アカウントを連携していません。
+anilist-anime = アニメ
+anilist-episodes-watched = 視聴したエピソード
+anilist-time-watched = 視聴時間
+anilist-anime-mean-score = アニメの平均スコア
+anilist-manga = マンガ
+anilist-chapters-read = 読んだ話数
+anilist-volumes-read = 読んだ巻数
+anilist-manga-mean-score = マンガの平均スコア
+anilist-favourite-genres = 好きなジャンル
+anilist-hours = { $hours }時間
+anilist-days-hours = { $days }日{ $hours }時間
+anilist-status = { $status ->
+    [CURRENT] 視聴中
+    [PLANNING] 予定
+    [COMPLETED] 完了
+    [DROPPED] 中断
+    [PAUSED] 一時停止
+    [REPEATING] リピート中
+   *[other] { $status }
+}
+anilist-list-title = { $user } さんの{ $kind ->
+    [manga] マンガ
+   *[anime] アニメ
+}リスト{ $status ->
+    [none] {""}
+   *[other] （{ $status }）
+}
+anilist-list-empty = { $list }は空です。
+
+## Seasons
+
+season-name = { $season ->
+    [Winter] 冬
+    [Spring] 春
+    [Summer] 夏
+   *[Fall] 秋
+}
+season-title = { $year }年{ $season }{ $genre ->
+    [none] {""}
+   *[other] {" "}{ $genre }
+}{ $format ->
+    [none] アニメ
+    [Movie] {" "}劇場アニメ
+   *[other] {" "}{ $format }アニメ
+}
+season-unknown-genre = { $genre } は AniList のジャンルではありません。
+season-no-results = { $title }は見つかりませんでした。
+season-episodes = { $episodes }話
+season-members = { $members }人
+
+## Airing
+
+airing-nothing-soon = 今後1日以内に放送されるものはありません。
+airing-schedule-title = 今後24時間の放送予定
+airing-schedule-line = <t:{ $time }:t> (<t:{ $time }:R>) { $title } 第{ $episode }話
+airing-next-episode = 第{ $episode }話{ $episodes ->
+    [unknown] {""}
+   *[other] （全{ $episodes }話）
+}は<t:{ $time }:F>（<t:{ $time }:R>）に放送されます。
+airing-finished = このアニメは放送を終了しました。
+airing-cancelled = このアニメは中止されました。
+airing-unscheduled = 今後のエピソードはまだ予定されていません。
+airing-later-episodes = 以降のエピソード
+airing-later-episode = 第{ $episode }話: <t:{ $time }:f>
+airing-aired = 第{ $episode }話{ $episodes ->
+    [unknown] {""}
+   *[other] （全{ $episodes }話）
+}が放送されました。
+airing-not-airing = { $title }はもう放送されていません。
+airing-too-many = { $target ->
+    [dm] あなた
+   *[other] { $target }
+}はすでに上限の{ $max }作品をフォローしています。先にどれかのフォローを解除してください。
+airing-followed = { $title }の新しいエピソードを{ $target ->
+    [dm] DM で
+   *[other] { $target } で
+}お知らせします。
+airing-already-following = { $target ->
+    [dm] あなた
+   *[other] { $target }
+}はすでに{ $title }をフォローしています。
+airing-not-following = { $target ->
+    [dm] あなた
+   *[other] { $target }
+}はそのアニメをフォローしていません。
+airing-unfollowed = { $title }の新しいエピソードを{ $target ->
+    [dm] DM で
+   *[other] { $target } で
+}お知らせするのをやめました。
+airing-none-followed = ここでフォローしているアニメはありません。
+airing-list-title = フォロー中のアニメ
+airing-list-line = { $title }（{ $target ->
+    [dm] DM
+   *[other] { $target }
+}）
+airing-manage-server = このサーバーのチャンネルへの投稿を変更するにはサーバー管理権限が必要です。
+
+## Anime, manga, characters, staff and studios
+
+name-unavailable = 名前はありません。
+weeb-status = ステータス
+weeb-episodes = エピソード
+weeb-chapters = 話数
+weeb-genres = ジャンル
+weeb-average-score = 平均スコア
+weeb-season = シーズン
+weeb-start-date = 開始日
+weeb-end-date = 終了日
+weeb-favourites = お気に入り
+weeb-gender = 性別
+weeb-age = 年齢
+weeb-birthday = 誕生日
+weeb-also-known-as = 別名
+weeb-appears-in = 登場作品
+weeb-voice-actors = 声優（{ $language }）
+weeb-unknown-language = 不明
+weeb-language = 言語
+weeb-hometown = 出身地
+weeb-occupations = 職業
+weeb-notable-works = 代表作
+weeb-notable-characters = 代表的なキャラクター
+weeb-type = 種類
+weeb-animation-studio = アニメーションスタジオ
+weeb-producer = プロデューサー
+
+## Commands
+
+cmd-help = ヘルプ
+    .description = コマンドを一覧表示、または説明します
+    .command = 説明するコマンド、空欄ですべて表示
+
+cmd-ping = ping
+    .description = Discord との現在の遅延を表示します。
+cmd-about = ボットについて
+    .description = ボットの情報を表示します。
+cmd-guildinfo = サーバー情報
+    .description = サーバーのさまざまな情報を表示します。
+
+cmd-prefix = プレフィックス
+    .description = このサーバーのコマンドのプレフィックスを表示・変更します。
+cmd-prefix-set = 設定
+    .description = このサーバーのコマンドのプレフィックスを設定します。
+    .prefix = 新しいプレフィックス
+cmd-prefix-reset = リセット
+    .description = このサーバーのコマンドのプレフィックスをデフォルトに戻します。
+cmd-prefix-show = 表示
+    .description = このサーバーのコマンドのプレフィックスを表示します。
+
+cmd-settings = 設定
+    .description = このサーバーでのボットの設定を表示・変更します。
+cmd-settings-show = 表示
+    .description = このサーバーでのボットの設定を表示します。
+cmd-settings-prefix = プレフィックス
+    .description = プレフィックスコマンドのプレフィックスを設定、またはリセットします。
+    .prefix = 新しいプレフィックス、空欄でデフォルト
+cmd-settings-category = カテゴリー
+    .description = コマンドのカテゴリーを有効・無効にします。
+    .category = コマンドのカテゴリー
+    .enabled = コマンドを使えるかどうか
+cmd-settings-logchannel = ログチャンネル
+    .description = 設定の変更を記録するチャンネルを設定します。
+    .channel = ログチャンネル、空欄で記録を停止
+cmd-settings-locale = 言語
+    .description = このサーバーでの返信の言語を設定します。
+    .locale = ja や en-US などの Discord の言語コード、空欄でデフォルト
+
+cmd-anime = アニメ
+    .description = AniList のアニメの情報を表示します
+    .anime_title = 検索するタイトル
+cmd-manga = マンガ
+    .description = AniList のマンガの情報を表示します
+    .manga_title = 検索するタイトル
+cmd-character = キャラクター
+    .description = AniList のキャラクターの情報を表示します
+    .name = 検索する名前
+cmd-staff = スタッフ
+    .description = AniList の声優・スタッフの情報を表示します
+    .name = 検索する名前
+cmd-studio = スタジオ
+    .description = AniList のスタジオの情報を表示します
+    .name = 検索する名前
+
+cmd-airing = 放送
+    .description = エピソードの放送時間を表示し、新しいエピソードをお知らせします
+cmd-airing-next = 次回
+    .description = アニメの次のエピソードの放送時間、または今後1日の放送予定を表示します
+    .anime_title = 検索するタイトル、空欄で今後1日の放送予定
+cmd-airing-follow = フォロー
+    .description = アニメの新しいエピソードを DM、またはこのサーバーのチャンネルでお知らせします
+    .anime_title = 検索するタイトル
+    .channel = 投稿するチャンネル、空欄で DM
+cmd-airing-unfollow = フォロー解除
+    .description = アニメの新しいエピソードのお知らせを停止します
+    .anime_title = フォロー中のアニメ
+    .channel = エピソードが投稿されるチャンネル、空欄で DM
+cmd-airing-list = 一覧
+    .description = あなたとこのサーバーのチャンネルがフォローしているアニメを一覧表示します
+
+cmd-anilist = anilist
+    .description = AniList アカウントを連携し、プロフィールとリストを表示します
+cmd-anilist-link = 連携
+    .description = あなたの AniList アカウントを連携します
+    .username = AniList のユーザー名
+cmd-anilist-unlink = 連携解除
+    .description = あなたの AniList アカウントの連携を解除します
+cmd-anilist-profile = プロフィール
+    .description = ユーザーの AniList の統計を表示します
+    .user = 表示するユーザー、空欄で自分
+cmd-anilist-list = リスト
+    .description = ユーザーのアニメ・マンガのリストを表示します
+    .user = 表示するユーザー、空欄で自分
+    .status = このステータスのエントリーだけを表示
+    .kind = アニメかマンガ、空欄でアニメ
+
+cmd-season = シーズン
+    .description = AniList のシーズンのアニメを一覧表示します
+    .season = シーズン、空欄で現在のシーズン
+    .year = 年、空欄で今年
+    .sort = 並び順、空欄で人気順
+    .format = このフォーマットのエントリーだけを表示
+    .genre = このジャンルのエントリーだけを表示
+
+cmd-vtuber = vtuber
+    .description = バーチャルYouTuberを表示し、誕生日をお知らせします
+cmd-vtuber-search = 検索
+    .description = バーチャルYouTuberの情報を表示します
+    .name = 検索する名前
+cmd-vtuber-follow = フォロー
+    .description = バーチャルYouTuberの誕生日とデビュー記念日をこのサーバーでお知らせします
+    .name = 検索する名前
+cmd-vtuber-unfollow = フォロー解除
+    .description = このサーバーでのバーチャルYouTuberのお知らせを停止します
+    .name = フォロー中の VTuber
+cmd-vtuber-upcoming = 今後の予定
+    .description = このサーバーがフォローしている VTuber の次の誕生日とデビュー記念日を一覧表示します
+cmd-vtuber-channel = チャンネル
+    .description = 誕生日とデビュー記念日をお知らせするチャンネルを設定します
+    .channel = お知らせするチャンネル、空欄でお知らせを停止
+
+cmd-wiki = wiki
+    .description = このサーバーのウィキの記事を表示します
+    .site = 検索するウィキ
+    .query = 検索する記事
+cmd-wikis = wikis
+    .description = このサーバーで /wiki に使えるウィキを管理します
+cmd-wikis-add = 追加
+    .description = MediaWiki のサイトを /wiki に追加、または変更します
+    .name = ウィキを選ぶための短い名前、例: overlord
+    .url = ウィキの api.php の URL、またはメインページ
+    .start = 説明が始まる位置の HTML、デフォルトはインフォボックスの後
+    .end = 説明が終わる位置の HTML、デフォルトは最初の見出し
+cmd-wikis-remove = 削除
+    .description = /wiki からウィキを削除します
+    .name = ウィキの名前
+cmd-wikis-list = 一覧
+    .description = /wiki で使えるウィキを一覧表示します
+
+cmd-eightball = 8ボール
+    .description = マジック8ボールに質問して、運勢を占ってもらいます。
+    .question = 質問
+cmd-darksouls = darksouls
+    .description = ランダムに生成された Dark Souls のメッセージを表示します。
+cmd-darksouls3 = darksouls3
+    .description = ランダムに生成された Dark Souls 3 のメッセージを表示します。
+cmd-bloodborne = bloodborne
+    .description = ランダムに生成された Bloodborne のメモを表示します。
+cmd-ddate = ddate
+    .description = ディスコルディア暦の今日の日付を表示します
@@ -0,0 +1,303 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use graphql_client::GraphQLQuery;
+use poise::serenity_prelude::{Colour, GuildId, Http};
+use reqwest::Url;
+use tracing::{debug, warn};
+
+use crate::commands::weeb::ANILIST_ICON;
+use crate::db::airing::{self, Subscription, Target};
+use crate::db::guild_settings::{self, GuildSettings};
+use crate::db::Database;
+use crate::http::HttpClient;
+use crate::i18n::{tr, FALLBACK_LOCALE};
+use crate::reply::Embed;
+use crate::util::ConfigHandle;
+use crate::Error;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/AiringScheduleQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+pub struct AiringScheduleQuery;
+
+pub type Schedule = airing_schedule_query::AiringScheduleQueryPageAiringSchedules;
+
+/// How often AniList is asked for newly aired episodes.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// Episodes that aired longer ago than this while the bot was offline are not announced.
+const MAX_CATCH_UP: i64 = 6 * 60 * 60;
+/// Stop paging through schedules after this many pages.
+const MAX_PAGES: i64 = 10;
+
+/// Announces newly aired episodes of followed anime until the process exits.
+///
+/// The time of the last check is stored in the database, so episodes that air while the bot is
+/// restarting are announced once it is back.
+pub fn spawn_airing_notifier(
+    http: Arc<Http>,
+    client: HttpClient,
+    db: Database,
+    config: ConfigHandle,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = poll(&http, &client, &db, &config).await {
+                warn!("Failed to check for aired episodes: {}", e);
+            }
+        }
+    });
+}
+
+async fn poll(
+    http: &Http,
+    client: &HttpClient,
+    db: &Database,
+    config: &ConfigHandle,
+) -> Result<(), Error> {
+    let now = Utc::now().timestamp();
+    let window = poll_window(db.run(airing::last_checked).await?, now);
+    let settings = db.run(guild_settings::load_all).await?;
+    let subscriptions = db.run(airing::all).await?;
+    let targets = targets_by_media(&subscriptions, &settings);
+
+    if let Some((from, to)) = window.filter(|_| !targets.is_empty()) {
+        let (endpoint, colour) = {
+            let config = config.read().await;
+            (config.api.anilist.clone(), config.colours.anilist)
+        };
+        let media_ids = targets.keys().copied().collect();
+        let schedules = airing_schedules(client, &endpoint, from, to, Some(media_ids)).await?;
+        for schedule in schedules {
+            for (target, locale) in targets.get(&schedule.media_id).into_iter().flatten() {
+                debug!(
+                    "Announcing episode {} of {} to {:?}",
+                    schedule.episode, schedule.media_id, target
+                );
+                let embed = aired_embed(&schedule, colour, locale);
+                if let Err(e) = announce(http, *target, embed).await {
+                    warn!("Failed to announce an aired episode to {:?}: {}", target, e);
+                }
+            }
+        }
+    }
+    db.run(move |connection| airing::set_last_checked(connection, now))
+        .await?;
+    Ok(())
+}
+
+/// The Unix times to ask AniList for aired episodes between, given when the last poll was. The
+/// first poll only starts the clock, and after a long downtime only the last [`MAX_CATCH_UP`]
+/// seconds are announced.
+fn poll_window(last_checked: Option<i64>, now: i64) -> Option<(i64, i64)> {
+    let from = last_checked.unwrap_or(now).max(now - MAX_CATCH_UP);
+    // `airingAt_lesser` is exclusive, the episodes airing right now are included.
+    (from < now).then(|| (from, now + 1))
+}
+
+/// Where to announce each followed anime, and in which locale. Channels get their guild's
+/// locale, DMs the fallback.
+fn targets_by_media<'a>(
+    subscriptions: &[Subscription],
+    settings: &'a HashMap<GuildId, GuildSettings>,
+) -> HashMap<i64, Vec<(Target, &'a str)>> {
+    let mut targets: HashMap<i64, Vec<(Target, &str)>> = HashMap::new();
+    for subscription in subscriptions {
+        let locale = subscription
+            .guild_id
+            .and_then(|guild_id| settings.get(&guild_id))
+            .and_then(|settings| settings.locale.as_deref())
+            .unwrap_or(FALLBACK_LOCALE);
+        targets
+            .entry(subscription.media_id)
+            .or_default()
+            .push((subscription.target, locale));
+    }
+    targets
+}
+
+async fn announce(http: &Http, target: Target, embed: Embed) -> Result<(), Error> {
+    let channel = match target {
+        Target::Channel(channel) => channel,
+        Target::User(user) => user.create_dm_channel(http).await?.id,
+    };
+    channel
+        .send_message(http, |m| m.set_embed(embed.truncated().into()))
+        .await?;
+    Ok(())
+}
+
+fn aired_embed(schedule: &Schedule, colour: Colour, locale: &str) -> Embed {
+    let media = schedule.media.as_ref();
+    let title = media.and_then(|m| m.title.as_ref());
+
+    let mut e = Embed::new()
+        .colour(colour)
+        .title(
+            title
+                .and_then(|t| t.romaji.clone().or_else(|| t.native.clone()))
+                .unwrap_or_else(|| tr(locale, "title-unavailable", &[])),
+        )
+        .description(tr(
+            locale,
+            "airing-aired",
+            &[
+                ("episode", schedule.episode.into()),
+                (
+                    "episodes",
+                    media
+                        .and_then(|m| m.episodes)
+                        .map_or_else(|| "unknown".into(), Into::into),
+                ),
+            ],
+        ));
+    if let Some(url) = media.and_then(|m| m.site_url.as_ref()) {
+        e = e.url(url);
+    }
+    if let Some(image) = media
+        .and_then(|m| m.cover_image.as_ref())
+        .and_then(|img| img.large.as_ref())
+    {
+        e = e.thumbnail(image);
+    }
+    if let Some(aired) = Utc.timestamp_opt(schedule.airing_at, 0).single() {
+        e = e.timestamp(aired);
+    }
+    e.footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON)
+}
+
+/// Episodes airing after `from` and before `to`, both Unix times, optionally limited to some
+/// anime.
+pub async fn airing_schedules(
+    client: &HttpClient,
+    endpoint: &Url,
+    from: i64,
+    to: i64,
+    media_ids: Option<Vec<i64>>,
+) -> Result<Vec<Schedule>, Error> {
+    let mut schedules = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let variables = airing_schedule_query::Variables {
+            page: Some(page),
+            from: Some(from),
+            to: Some(to),
+            media_ids: media_ids
+                .as_ref()
+                .map(|ids| ids.iter().copied().map(Some).collect()),
+        };
+        let response = client
+            .graphql_uncached::<AiringScheduleQuery>(endpoint, variables)
+            .await?;
+        let page = response
+            .data
+            .and_then(|data| data.page)
+            .ok_or("Unable to get airing schedules from response.")?;
+        schedules.extend(
+            page.airing_schedules
+                .unwrap_or_default()
+                .into_iter()
+                .flatten(),
+        );
+        let has_next_page = page
+            .page_info
+            .and_then(|info| info.has_next_page)
+            .unwrap_or(false);
+        if !has_next_page {
+            break;
+        }
+    }
+    Ok(schedules)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{ChannelId, UserId};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn first_poll_only_starts_the_clock() {
+        assert_eq!(poll_window(None, NOW), None);
+        assert_eq!(poll_window(Some(NOW), NOW), None);
+    }
+
+    #[test]
+    fn polls_cover_everything_since_the_last() {
+        assert_eq!(poll_window(Some(NOW - 60), NOW), Some((NOW - 60, NOW + 1)));
+    }
+
+    #[test]
+    fn restarts_catch_up_on_at_most_six_hours() {
+        assert_eq!(
+            poll_window(Some(NOW - 60 * 60), NOW),
+            Some((NOW - 60 * 60, NOW + 1))
+        );
+        assert_eq!(
+            poll_window(Some(NOW - 2 * 24 * 60 * 60), NOW),
+            Some((NOW - MAX_CATCH_UP, NOW + 1))
+        );
+    }
+
+    #[test]
+    fn channels_are_announced_in_their_guilds_locale() {
+        let guild = GuildId(1);
+        let channel = Target::Channel(ChannelId(2));
+        let user = Target::User(UserId(3));
+        let subscription = |media_id, target, guild_id| Subscription {
+            media_id,
+            title: "Overlord".to_owned(),
+            target,
+            guild_id,
+        };
+        let mut settings = HashMap::new();
+        settings.insert(
+            guild,
+            GuildSettings {
+                locale: Some("ja".to_owned()),
+                ..Default::default()
+            },
+        );
+
+        let targets = targets_by_media(
+            &[
+                subscription(10, channel, Some(guild)),
+                subscription(10, user, None),
+                subscription(20, Target::Channel(ChannelId(4)), Some(GuildId(5))),
+            ],
+            &settings,
+        );
+
+        assert_eq!(targets[&10], vec![(channel, "ja"), (user, FALLBACK_LOCALE)]);
+        assert_eq!(
+            targets[&20],
+            vec![(Target::Channel(ChannelId(4)), FALLBACK_LOCALE)]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use poise::serenity_prelude::{Colour, Http};
+use tracing::warn;
+
+use crate::db::alerts::{self, AlertTarget};
+use crate::db::Database;
+use crate::error::BotError;
+use crate::reply::Embed;
+use crate::{Context, Error};
+
+/// How often queued alerts are sent.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Most alerts sent at once; the remaining ones are only mentioned by number.
+const MAX_ALERTS_PER_FLUSH: usize = 5;
+/// Most distinct errors kept until the next flush; any more are only counted.
+const MAX_PENDING: usize = 50;
+/// Most error IDs listed in a grouped alert.
+const MAX_ERROR_IDS: usize = 5;
+
+/// A command error worth telling the owners about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alert {
+    pub command: String,
+    pub invoker: String,
+    pub guild: Option<String>,
+    pub arguments: String,
+    /// The error followed by each of its sources.
+    pub chain: Vec<String>,
+    pub error_id: String,
+}
+
+impl Alert {
+    /// Describes `error`, reported with `error_id`, in the command run in `context`.
+    pub fn new(context: Context<'_>, error: &BotError, error_id: &str) -> Alert {
+        let author = context.author();
+        let guild = context
+            .guild_id()
+            .map(|guild_id| match guild_id.name(context.discord()) {
+                Some(name) => format!("{} ({})", name, guild_id),
+                None => guild_id.to_string(),
+            });
+        Alert {
+            command: context.command().qualified_name.clone(),
+            invoker: format!("{} ({})", author.tag(), author.id),
+            guild,
+            arguments: arguments(context),
+            chain: chain(error),
+            error_id: error_id.to_owned(),
+        }
+    }
+
+    /// Alerts for the same failure of the same command are grouped.
+    fn same_failure(&self, other: &Alert) -> bool {
+        self.command == other.command && self.chain == other.chain
+    }
+}
+
+/// The arguments a command was invoked with, as typed or as `name:value` pairs.
+fn arguments(context: Context<'_>) -> String {
+    match context {
+        poise::Context::Prefix(prefix) => prefix.args.to_owned(),
+        poise::Context::Application(application) => application
+            .args
+            .iter()
+            .map(|option| match &option.value {
+                Some(value) => format!("{}:{}", option.name, value),
+                None => option.name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn chain(error: &BotError) -> Vec<String> {
+    let mut chain = vec![error.to_string()];
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        chain.push(error.to_string());
+        source = error.source();
+    }
+    chain
+}
+
+/// An alert with the number of times it happened since the last flush.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub alert: Alert,
+    pub count: usize,
+    /// IDs of the most recent occurrences.
+    pub error_ids: Vec<String>,
+}
+
+/// Collects alerts until they are sent by [`spawn_alert_sender`].
+#[derive(Default)]
+pub struct AlertSink {
+    pending: Mutex<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    groups: Vec<Group>,
+    /// Alerts left out because too many distinct ones were pending.
+    dropped: usize,
+}
+
+impl AlertSink {
+    /// Queues `alert`, adding it to the group of the same failure if there is one.
+    pub fn push(&self, alert: Alert) {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(group) = pending
+            .groups
+            .iter_mut()
+            .find(|group| group.alert.same_failure(&alert))
+        {
+            group.count += 1;
+            if group.error_ids.len() == MAX_ERROR_IDS {
+                group.error_ids.remove(0);
+            }
+            group.error_ids.push(alert.error_id);
+        } else if pending.groups.len() < MAX_PENDING {
+            pending.groups.push(Group {
+                error_ids: vec![alert.error_id.clone()],
+                alert,
+                count: 1,
+            });
+        } else {
+            pending.dropped += 1;
+        }
+    }
+
+    /// Takes every queued group along with the number of alerts that didn't fit.
+    pub fn take(&self) -> (Vec<Group>, usize) {
+        let mut pending = self.pending.lock().unwrap();
+        let dropped = std::mem::take(&mut pending.dropped);
+        (std::mem::take(&mut pending.groups), dropped)
+    }
+}
+
+/// The embed an alert group is sent as.
+pub fn alert_embed(group: &Group) -> Embed {
+    let alert = &group.alert;
+    let title = if group.count == 1 {
+        format!("Error in /{}", alert.command)
+    } else {
+        format!("Error in /{} ({} times)", alert.command, group.count)
+    };
+    let arguments = if alert.arguments.is_empty() {
+        "None".to_owned()
+    } else {
+        format!("```\n{}\n```", alert.arguments.replace("```", "'''"))
+    };
+    let chain = alert
+        .chain
+        .iter()
+        .enumerate()
+        .map(|(depth, error)| format!("{}{}", "  ".repeat(depth), error))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Embed::new()
+        .colour(Colour::RED)
+        .title(title)
+        .description(format!("```\n{}\n```", chain.replace("```", "'''")))
+        .field("Invoker", &alert.invoker, true)
+        .field(
+            "Guild",
+            alert.guild.as_deref().unwrap_or("Direct message"),
+            true,
+        )
+        .field("Arguments", arguments, false)
+        .field(
+            "Error IDs",
+            group
+                .error_ids
+                .iter()
+                .map(|id| format!("`{}`", id))
+                .collect::<Vec<_>>()
+                .join(", "),
+            false,
+        )
+        .truncated()
+}
+
+/// Sends queued alerts to the configured target once a minute, while alerts are enabled.
+///
+/// Alerts queued while they are disabled are thrown away.
+pub fn spawn_alert_sender(http: Arc<Http>, db: Database, sink: Arc<AlertSink>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = flush(&http, &db, &sink).await {
+                warn!("Failed to send owner alerts: {}", e);
+            }
+        }
+    });
+}
+
+/// Sends the queued alerts now, see [`spawn_alert_sender`].
+pub async fn flush(http: &Http, db: &Database, sink: &AlertSink) -> Result<(), Error> {
+    let (groups, dropped) = sink.take();
+    if groups.is_empty() && dropped == 0 {
+        return Ok(());
+    }
+    let target = match db.run(alerts::load).await? {
+        alerts::AlertSettings {
+            enabled: true,
+            target: Some(target),
+        } => target,
+        _ => return Ok(()),
+    };
+    let channel = match target {
+        AlertTarget::Channel(channel) => channel,
+        AlertTarget::Dm(user) => user.create_dm_channel(http).await?.id,
+    };
+    for group in groups.iter().take(MAX_ALERTS_PER_FLUSH) {
+        let embed = alert_embed(group).into();
+        channel.send_message(http, |m| m.set_embed(embed)).await?;
+    }
+    let skipped = groups
+        .iter()
+        .skip(MAX_ALERTS_PER_FLUSH)
+        .map(|group| group.count)
+        .sum::<usize>()
+        + dropped;
+    if skipped > 0 {
+        channel
+            .say(
+                http,
+                format!("{} more errors happened, see the log for them.", skipped),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(command: &str, error: &str, id: &str) -> Alert {
+        Alert {
+            command: command.to_owned(),
+            invoker: "Nelly#0042 (80351110224678912)".to_owned(),
+            guild: None,
+            arguments: "anime_title:\"Bebop\"".to_owned(),
+            chain: vec![error.to_owned(), "connection reset".to_owned()],
+            error_id: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn groups_identical_errors() {
+        let sink = AlertSink::default();
+        sink.push(alert("anime", "request failed", "a"));
+        sink.push(alert("anime", "request failed", "b"));
+        sink.push(alert("manga", "request failed", "c"));
+        sink.push(alert("anime", "decode failed", "d"));
+
+        let (groups, dropped) = sink.take();
+        assert_eq!(dropped, 0);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].count, 2);
+        assert_eq!(groups[0].error_ids, ["a", "b"]);
+        assert!(sink.take().0.is_empty());
+    }
+
+    #[test]
+    fn counts_alerts_beyond_the_pending_limit() {
+        let sink = AlertSink::default();
+        for i in 0..MAX_PENDING + 3 {
+            sink.push(alert("anime", &format!("error {}", i), "id"));
+        }
+        let (groups, dropped) = sink.take();
+        assert_eq!(groups.len(), MAX_PENDING);
+        assert_eq!(dropped, 3);
+    }
+
+    #[test]
+    fn embed_shows_the_failure() {
+        let group = Group {
+            alert: alert("anilist profile", "request failed", "0badf00d"),
+            count: 3,
+            error_ids: vec!["0badf00d".to_owned()],
+        };
+        let embed = alert_embed(&group);
+        assert_eq!(
+            embed.title.as_deref(),
+            Some("Error in /anilist profile (3 times)")
+        );
+        assert_eq!(
+            embed.description.as_deref(),
+            Some("```\nrequest failed\n  connection reset\n```")
+        );
+        assert_eq!(embed.field_value("Guild"), Some("Direct message"));
+        assert_eq!(embed.field_value("Error IDs"), Some("`0badf00d`"));
+    }
+}
@@ -0,0 +1,313 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::util::{get_project_dirs, ConfigHandle};
+
+/// How often the cache is written to disk when persistence is enabled.
+const SAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Response bodies of AniList and wiki requests, keyed by the request.
+///
+/// Entries expire after the TTL they were inserted with. Expiry times are wall clock times, so
+/// entries loaded from disk stay valid across restarts.
+#[derive(Default)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    /// Whether there are changes that haven't been saved to disk.
+    dirty: AtomicBool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    /// Unix time the entry expires at.
+    expires: u64,
+    body: String,
+}
+
+/// Cache usage since the bot started.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl ResponseCache {
+    /// Loads the entries saved at `path` that haven't expired yet.
+    pub fn load(path: &Path) -> Result<ResponseCache> {
+        let cache = ResponseCache::default();
+        if path.exists() {
+            let entries: HashMap<String, CachedResponse> =
+                serde_json::from_str(&fs::read_to_string(path)?)?;
+            let now = unix_now();
+            cache.entries.lock().unwrap().extend(
+                entries
+                    .into_iter()
+                    .filter(|(_, response)| response.expires > now),
+            );
+        }
+        Ok(cache)
+    }
+
+    /// Writes all entries to `path`, replacing the file at once so a crash can't corrupt it.
+    ///
+    /// The cache stays dirty if writing fails, so the next periodic save tries again.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = {
+            let entries = self.entries.lock().unwrap();
+            // Cleared while the entries are locked, so changes made during the write mark the
+            // cache dirty again.
+            self.dirty.store(false, Ordering::Relaxed);
+            serde_json::to_string(&*entries)
+        };
+        let result = json.map_err(Into::into).and_then(|json| write(path, &json));
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(key).filter(|r| r.expires > unix_now()) {
+            Some(response) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(response.body.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Whether there is an unexpired entry for `key`, without counting a hit or miss.
+    pub fn contains(&self, key: &str) -> bool {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key).is_some_and(|r| r.expires > unix_now())
+    }
+
+    /// Stores a response, making room by dropping expired and then the oldest entries once
+    /// there are `capacity` of them.
+    pub fn insert(&self, key: String, body: String, ttl: Duration, capacity: usize) {
+        if ttl.is_zero() || capacity == 0 {
+            return;
+        }
+        let now = unix_now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= capacity {
+            entries.retain(|_, response| response.expires > now);
+        }
+        while entries.len() >= capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, response)| response.expires)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                expires: now + ttl.as_secs(),
+                body,
+            },
+        );
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Drops every entry, returning how many there were.
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let count = entries.len();
+        entries.clear();
+        self.dirty.store(true, Ordering::Relaxed);
+        count
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.lock().unwrap().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Relaxed)
+    }
+}
+
+fn write(path: &Path, json: &str) -> Result<()> {
+    let directory = path
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent of path!"))?;
+    fs::create_dir_all(directory)?;
+    let mut file = tempfile::NamedTempFile::new_in(directory)?;
+    std::io::Write::write_all(&mut file, json.as_bytes())?;
+    file.persist(path)?;
+    Ok(())
+}
+
+/// Where the cache is saved, in the project cache directory.
+pub fn cache_path() -> Result<PathBuf> {
+    let project_dirs =
+        get_project_dirs().ok_or_else(|| anyhow!("Failed to get project directories!"))?;
+    Ok(project_dirs.cache_dir().join("responses.json"))
+}
+
+/// Periodically saves the cache to disk while `cache.persist` is enabled.
+pub fn spawn_cache_saver(cache: Arc<ResponseCache>, config: ConfigHandle) {
+    tokio::spawn(async move {
+        let path = match cache_path() {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Not saving the response cache: {}", e);
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(SAVE_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if !config.read().await.cache.persist || !cache.is_dirty() {
+                continue;
+            }
+            let cache = cache.clone();
+            let path = path.clone();
+            match tokio::task::spawn_blocking(move || cache.save(&path)).await {
+                Ok(Ok(())) => debug!("Saved the response cache"),
+                Ok(Err(e)) => warn!("Failed to save the response cache: {}", e),
+                Err(e) => warn!("Failed to save the response cache: {}", e),
+            }
+        }
+    });
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn failed_saves_keep_the_cache_dirty() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::default();
+        cache.insert("key".to_owned(), "body".to_owned(), TTL, 10);
+        // A file where the cache directory should be makes the write fail.
+        let blocker = directory.path().join("blocker");
+        fs::write(&blocker, "").unwrap();
+        assert!(cache.save(&blocker.join("responses.json")).is_err());
+        assert!(cache.is_dirty());
+        cache
+            .save(&directory.path().join("responses.json"))
+            .unwrap();
+        assert!(!cache.is_dirty());
+    }
+
+    #[test]
+    fn expired_entries_are_misses() {
+        let cache = ResponseCache::default();
+        cache.insert("fresh".to_owned(), "body".to_owned(), TTL, 10);
+        cache.entries.lock().unwrap().insert(
+            "stale".to_owned(),
+            CachedResponse {
+                expires: unix_now() - 1,
+                body: "old".to_owned(),
+            },
+        );
+        assert!(cache.contains("fresh") && !cache.contains("stale"));
+        assert_eq!(cache.get("fresh").as_deref(), Some("body"));
+        assert_eq!(cache.get("stale"), None);
+        assert_eq!(cache.get("missing"), None);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[test]
+    fn zero_ttl_or_capacity_stores_nothing() {
+        let cache = ResponseCache::default();
+        cache.insert("key".to_owned(), "body".to_owned(), Duration::ZERO, 10);
+        cache.insert("key".to_owned(), "body".to_owned(), TTL, 0);
+        assert_eq!(cache.stats().entries, 0);
+        assert!(!cache.is_dirty());
+    }
+
+    #[test]
+    fn full_caches_drop_expired_then_the_oldest_entries() {
+        let cache = ResponseCache::default();
+        cache.entries.lock().unwrap().insert(
+            "stale".to_owned(),
+            CachedResponse {
+                expires: unix_now() - 1,
+                body: "old".to_owned(),
+            },
+        );
+        cache.insert("short".to_owned(), "1".to_owned(), TTL, 2);
+        assert_eq!(cache.stats().entries, 2);
+        cache.insert("long".to_owned(), "2".to_owned(), TTL * 2, 2);
+        assert!(!cache.entries.lock().unwrap().contains_key("stale"));
+        cache.insert("longer".to_owned(), "3".to_owned(), TTL * 3, 2);
+        assert_eq!(cache.get("short"), None);
+        assert_eq!(cache.get("long").as_deref(), Some("2"));
+        assert_eq!(cache.get("longer").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn saved_entries_load_until_they_expire() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("cache/responses.json");
+        assert_eq!(ResponseCache::load(&path).unwrap().stats().entries, 0);
+
+        let cache = ResponseCache::default();
+        cache.insert("key".to_owned(), "body".to_owned(), TTL, 10);
+        cache.entries.lock().unwrap().insert(
+            "stale".to_owned(),
+            CachedResponse {
+                expires: unix_now() - 1,
+                body: "old".to_owned(),
+            },
+        );
+        cache.save(&path).unwrap();
+
+        let loaded = ResponseCache::load(&path).unwrap();
+        assert_eq!(loaded.stats().entries, 1);
+        assert_eq!(loaded.get("key").as_deref(), Some("body"));
+        assert!(!loaded.is_dirty());
+    }
+}
@@ -0,0 +1,514 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use chrono::Utc;
+use graphql_client::GraphQLQuery;
+use poise::serenity_prelude::{Colour, GuildChannel, Mentionable, Permissions};
+use poise::AutocompleteChoice;
+use reqwest::Url;
+
+use crate::airing::airing_schedules;
+use crate::commands::settings::reply_locale;
+use crate::commands::weeb::{
+    anilist_config, autocomplete_anime, id_or_title, link, ANILIST_ICON, ANILIST_ID_PREFIX,
+};
+use crate::db::airing::{self, Subscription, Target};
+use crate::error::BotError;
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::pagination::{browse, browse_ephemeral};
+use crate::reply::{Embed, Reply};
+use crate::{Context, Error};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/AiringQuery.graphql",
+    response_derives = "Debug,Clone,PartialEq"
+)]
+struct AiringQuery;
+
+/// How many anime a single channel or user may follow.
+const MAX_SUBSCRIPTIONS: usize = 25;
+/// Entries per page of the schedule for the next day.
+const SCHEDULE_PAGE_SIZE: usize = 15;
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    subcommands("next", "follow", "unfollow", "list"),
+    description_localized("en-US", "Shows when episodes air and announces new ones")
+)]
+pub async fn airing(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized(
+        "en-US",
+        "Shows when the next episodes of an anime air, or everything airing in the next day"
+    )
+)]
+pub async fn next(
+    context: Context<'_>,
+    #[description = "Title to search for, leave empty for the next day's schedule"]
+    #[autocomplete = "autocomplete_anime"]
+    anime_title: Option<String>,
+) -> Result<(), Error> {
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let pages = match anime_title {
+        Some(anime_title) => {
+            let (id, title) = id_or_title(anime_title.clone());
+            let media = airing_query(
+                &context.data().http,
+                &endpoint,
+                airing_query::Variables { title, id },
+            )
+            .await?;
+            if media.is_empty() {
+                return Err(BotError::NotFound(anime_title).into());
+            }
+            media
+                .into_iter()
+                .map(|media| countdown_embed(&locale, media, colour).truncated().into())
+                .collect()
+        }
+        None => {
+            let now = Utc::now().timestamp();
+            let schedules = airing_schedules(
+                &context.data().http,
+                &endpoint,
+                now,
+                now + 24 * 60 * 60,
+                None,
+            )
+            .await?;
+            if schedules.is_empty() {
+                Reply::text(tr(&locale, "airing-nothing-soon", &[]))
+                    .send(context)
+                    .await?;
+                return Ok(());
+            }
+            let lines = schedules
+                .into_iter()
+                .map(|schedule| {
+                    let media = schedule.media.as_ref();
+                    let title = media
+                        .and_then(|m| m.title.as_ref())
+                        .and_then(|t| t.romaji.as_deref().or(t.native.as_deref()))
+                        .map_or_else(|| tr(&locale, "title-unavailable", &[]), str::to_owned);
+                    let title = link(&title, media.and_then(|m| m.site_url.as_deref()));
+                    tr(
+                        &locale,
+                        "airing-schedule-line",
+                        &[
+                            ("time", schedule.airing_at.into()),
+                            ("title", title.into()),
+                            ("episode", schedule.episode.into()),
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines
+                .chunks(SCHEDULE_PAGE_SIZE)
+                .map(|chunk| {
+                    Embed::new()
+                        .colour(colour)
+                        .title(tr(&locale, "airing-schedule-title", &[]))
+                        .description(chunk.join("\n"))
+                        .footer(tr(&locale, "anilist-footer", &[]))
+                        .footer_icon(ANILIST_ICON)
+                        .truncated()
+                        .into()
+                })
+                .collect()
+        }
+    };
+    browse(context, pages).await?;
+    Ok(())
+}
+
+fn countdown_embed(
+    locale: &str,
+    media: airing_query::AiringQueryPageMedia,
+    colour: Colour,
+) -> Embed {
+    let mut e = Embed::new()
+        .colour(colour)
+        .title(media_title(locale, &media));
+    if let Some(url) = &media.site_url {
+        e = e.url(url);
+    }
+    if let Some(image) = media.cover_image.and_then(|img| img.large) {
+        e = e.thumbnail(image);
+    }
+    e = match media.next_airing_episode {
+        Some(next) => e.description(tr(
+            locale,
+            "airing-next-episode",
+            &[
+                ("episode", next.episode.into()),
+                (
+                    "episodes",
+                    media.episodes.map_or_else(|| "unknown".into(), Into::into),
+                ),
+                ("time", next.airing_at.into()),
+            ],
+        )),
+        None => e.description(tr(
+            locale,
+            match media.status {
+                Some(airing_query::MediaStatus::FINISHED) => "airing-finished",
+                Some(airing_query::MediaStatus::CANCELLED) => "airing-cancelled",
+                _ => "airing-unscheduled",
+            },
+            &[],
+        )),
+    };
+    let upcoming = media
+        .airing_schedule
+        .and_then(|schedule| schedule.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .skip(1)
+        .map(|episode| {
+            tr(
+                locale,
+                "airing-later-episode",
+                &[
+                    ("episode", episode.episode.into()),
+                    ("time", episode.airing_at.into()),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    if !upcoming.is_empty() {
+        e = e.field(
+            tr(locale, "airing-later-episodes", &[]),
+            upcoming.join("\n"),
+            false,
+        );
+    }
+    e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON)
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized(
+        "en-US",
+        "Announces new episodes of an anime to you by DM, or in a channel of this server"
+    )
+)]
+pub async fn follow(
+    context: Context<'_>,
+    #[description = "Title to search for"]
+    #[autocomplete = "autocomplete_anime"]
+    anime_title: String,
+    #[description = "Channel to post in, leave empty to get a DM instead"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let target = match resolve_target(context, channel).await? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+    let (endpoint, _, _) = anilist_config(&context).await;
+    context.defer_ephemeral().await?;
+    let (id, title) = id_or_title(anime_title.clone());
+    let media = airing_query(
+        &context.data().http,
+        &endpoint,
+        airing_query::Variables { title, id },
+    )
+    .await?
+    .into_iter()
+    .next()
+    .ok_or(BotError::NotFound(anime_title))?;
+    let locale = reply_locale(context).await;
+    let title = media_title(&locale, &media);
+    if matches!(
+        media.status,
+        Some(airing_query::MediaStatus::FINISHED) | Some(airing_query::MediaStatus::CANCELLED)
+    ) {
+        let message = tr(&locale, "airing-not-airing", &[("title", title.into())]);
+        reply(context, message).await?;
+        return Ok(());
+    }
+
+    let existing = context
+        .data()
+        .db
+        .run(move |connection| airing::for_target(connection, target))
+        .await?;
+    if existing.len() >= MAX_SUBSCRIPTIONS {
+        let message = tr(
+            &locale,
+            "airing-too-many",
+            &[
+                ("target", target_name(target).into()),
+                ("max", MAX_SUBSCRIPTIONS.into()),
+            ],
+        );
+        reply(context, message).await?;
+        return Ok(());
+    }
+    let subscription = Subscription {
+        media_id: media.id,
+        title: title.clone(),
+        target,
+        guild_id: match target {
+            Target::Channel(_) => context.guild_id(),
+            Target::User(_) => None,
+        },
+    };
+    let added = context
+        .data()
+        .db
+        .run(move |connection| airing::subscribe(connection, &subscription))
+        .await?;
+    let message = tr(
+        &locale,
+        if added {
+            "airing-followed"
+        } else {
+            "airing-already-following"
+        },
+        &[
+            ("target", target_name(target).into()),
+            ("title", title.into()),
+        ],
+    );
+    reply(context, message).await
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Stops announcing new episodes of an anime")
+)]
+pub async fn unfollow(
+    context: Context<'_>,
+    #[description = "Followed anime"]
+    #[autocomplete = "autocomplete_subscriptions"]
+    anime_title: String,
+    #[description = "Channel the episodes are posted in, leave empty for your DMs"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let target = match resolve_target(context, channel).await? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+    let subscriptions = context
+        .data()
+        .db
+        .run(move |connection| airing::for_target(connection, target))
+        .await?;
+    let subscription = match id_or_title(anime_title) {
+        (Some(id), _) => subscriptions.into_iter().find(|s| s.media_id == id),
+        (None, Some(title)) => subscriptions
+            .into_iter()
+            .find(|s| s.title.eq_ignore_ascii_case(title.trim())),
+        (None, None) => None,
+    };
+    let locale = reply_locale(context).await;
+    let subscription = match subscription {
+        Some(subscription) => subscription,
+        None => {
+            let message = tr(
+                &locale,
+                "airing-not-following",
+                &[("target", target_name(target).into())],
+            );
+            reply(context, message).await?;
+            return Ok(());
+        }
+    };
+    let media_id = subscription.media_id;
+    context
+        .data()
+        .db
+        .run(move |connection| airing::unsubscribe(connection, media_id, target))
+        .await?;
+    let message = tr(
+        &locale,
+        "airing-unfollowed",
+        &[
+            ("target", target_name(target).into()),
+            ("title", subscription.title.into()),
+        ],
+    );
+    reply(context, message).await
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Lists the anime followed by you and this server's channels")
+)]
+pub async fn list(context: Context<'_>) -> Result<(), Error> {
+    let user = Target::User(context.author().id);
+    let guild_id = context.guild_id();
+    let (mut subscriptions, guild_subscriptions) = context
+        .data()
+        .db
+        .run(move |connection| {
+            let guild_subscriptions = match guild_id {
+                Some(guild_id) => airing::for_guild(connection, guild_id)?,
+                None => Vec::new(),
+            };
+            Ok((airing::for_target(connection, user)?, guild_subscriptions))
+        })
+        .await?;
+    subscriptions.extend(guild_subscriptions);
+    let locale = reply_locale(context).await;
+    if subscriptions.is_empty() {
+        reply(context, tr(&locale, "airing-none-followed", &[])).await?;
+        return Ok(());
+    }
+    let (_, site, colour) = anilist_config(&context).await;
+    let lines = subscriptions
+        .into_iter()
+        .map(|s| {
+            let url = site
+                .join(&format!("anime/{}", s.media_id))
+                .map_or_else(|_| site.to_string(), String::from);
+            tr(
+                &locale,
+                "airing-list-line",
+                &[
+                    ("title", link(&s.title, Some(&url)).into()),
+                    ("target", target_name(s.target).into()),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    let pages = Embed::new()
+        .colour(colour)
+        .title(tr(&locale, "airing-list-title", &[]))
+        .description(lines.join("\n"))
+        .pages();
+    browse_ephemeral(context, pages.into_iter().map(Into::into).collect()).await
+}
+
+async fn autocomplete_subscriptions(
+    context: Context<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice<String>> {
+    let user = Target::User(context.author().id);
+    let guild_id = context.guild_id();
+    let subscriptions = context
+        .data()
+        .db
+        .run(move |connection| {
+            let mut subscriptions = airing::for_target(connection, user)?;
+            if let Some(guild_id) = guild_id {
+                subscriptions.extend(airing::for_guild(connection, guild_id)?);
+            }
+            Ok(subscriptions)
+        })
+        .await
+        .unwrap_or_default();
+    let partial = partial.to_lowercase();
+    let mut choices: Vec<AutocompleteChoice<String>> = Vec::new();
+    for subscription in subscriptions {
+        let value = format!("{}{}", ANILIST_ID_PREFIX, subscription.media_id);
+        if subscription.title.to_lowercase().contains(&partial)
+            && !choices.iter().any(|c| c.value == value)
+        {
+            choices.push(AutocompleteChoice {
+                name: subscription.title.chars().take(100).collect(),
+                value,
+            });
+        }
+    }
+    choices.truncate(25);
+    choices
+}
+
+/// Picks the channel, which needs the Manage Server permission, or the author's DMs.
+///
+/// Replies and returns `None` if the author may not manage the channel's subscriptions.
+async fn resolve_target(
+    context: Context<'_>,
+    channel: Option<GuildChannel>,
+) -> Result<Option<Target>, Error> {
+    let channel = match channel {
+        Some(channel) => channel,
+        None => return Ok(Some(Target::User(context.author().id))),
+    };
+    let member = context
+        .author_member()
+        .await
+        .ok_or("Failed to get member of the author.")?;
+    let permissions = match member.permissions {
+        Some(permissions) => permissions,
+        None => member.permissions(context.discord())?,
+    };
+    if Some(channel.guild_id) != context.guild_id()
+        || !permissions.contains(Permissions::MANAGE_GUILD)
+    {
+        let locale = reply_locale(context).await;
+        reply(context, tr(&locale, "airing-manage-server", &[])).await?;
+        return Ok(None);
+    }
+    Ok(Some(Target::Channel(channel.id)))
+}
+
+/// Where announcements go for the `target` argument of messages, a channel mention or `dm`.
+fn target_name(target: Target) -> String {
+    match target {
+        Target::Channel(channel) => channel.mention().to_string(),
+        Target::User(_) => "dm".to_owned(),
+    }
+}
+
+fn media_title(locale: &str, media: &airing_query::AiringQueryPageMedia) -> String {
+    media
+        .title
+        .as_ref()
+        .and_then(|t| t.romaji.clone().or_else(|| t.native.clone()))
+        .unwrap_or_else(|| tr(locale, "title-unavailable", &[]))
+}
+
+async fn reply(context: Context<'_>, message: String) -> Result<(), Error> {
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+async fn airing_query(
+    http: &HttpClient,
+    endpoint: &Url,
+    variables: airing_query::Variables,
+) -> Result<Vec<airing_query::AiringQueryPageMedia>, Box<dyn std::error::Error + Send + Sync>> {
+    let response = http.graphql::<AiringQuery>(endpoint, variables).await?;
+    Ok(response
+        .data
+        .and_then(|data| data.page)
+        .and_then(|page| page.media)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect())
+}
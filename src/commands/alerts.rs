@@ -0,0 +1,122 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::{Colour, GuildChannel};
+
+use crate::commands::owner::is_owner;
+use crate::db::alerts::{self, AlertSettings, AlertTarget};
+use crate::{Context, Error};
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    subcommands("enable", "disable", "status"),
+    description_localized("en-US", "Turns alerts about command errors on or off")
+)]
+pub async fn alerts(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized("en-US", "Sends command errors to a channel, or to you by DM")
+)]
+pub async fn enable(
+    context: Context<'_>,
+    #[description = "Channel to send alerts to, leave empty to get them by DM"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let target = match channel {
+        Some(channel) => AlertTarget::Channel(channel.id),
+        None => AlertTarget::Dm(context.author().id),
+    };
+    let settings = AlertSettings {
+        enabled: true,
+        target: Some(target),
+    };
+    context
+        .data()
+        .db
+        .run(move |connection| alerts::save(connection, &settings))
+        .await?;
+    let message = format!("Command errors will be sent to {}.", describe(target));
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized("en-US", "Stops sending command errors to the owners")
+)]
+pub async fn disable(context: Context<'_>) -> Result<(), Error> {
+    context
+        .data()
+        .db
+        .run(|connection| {
+            let mut settings = alerts::load(connection)?;
+            settings.enabled = false;
+            alerts::save(connection, &settings)
+        })
+        .await?;
+    context
+        .send(|m| {
+            m.content("Command errors will only be logged.")
+                .ephemeral(true)
+        })
+        .await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized("en-US", "Shows where command errors are sent")
+)]
+pub async fn status(context: Context<'_>) -> Result<(), Error> {
+    let settings = context.data().db.run(alerts::load).await?;
+    let state = match settings {
+        AlertSettings {
+            enabled: true,
+            target: Some(target),
+        } => format!("Enabled, sent to {}", describe(target)),
+        _ => "Disabled".to_owned(),
+    };
+    context
+        .send(|m| {
+            m.embed(|e| {
+                e.colour(Colour::BLURPLE)
+                    .title("Error Alerts")
+                    .description(state)
+            })
+            .ephemeral(true)
+        })
+        .await?;
+    Ok(())
+}
+
+fn describe(target: AlertTarget) -> String {
+    match target {
+        AlertTarget::Channel(channel) => format!("<#{}>", channel),
+        AlertTarget::Dm(user) => format!("<@{}> by DM", user),
+    }
+}
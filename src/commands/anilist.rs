@@ -0,0 +1,617 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use graphql_client::GraphQLQuery;
+use poise::serenity_prelude::{Colour, User};
+use reqwest::Url;
+
+use crate::commands::settings::reply_locale;
+use crate::commands::weeb::{self, anilist_config, MediaKind, ANILIST_ICON};
+use crate::db::anilist_links::{self, AnilistLink};
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::pagination::browse;
+use crate::reply::{Embed, Reply};
+use crate::{Context, Error};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/UserQuery.graphql",
+    response_derives = "Debug,Clone",
+    deprecated = "allow"
+)]
+struct UserQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/MediaListQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+struct MediaListQuery;
+
+/// Entries per page of a list.
+const LIST_PAGE_SIZE: usize = 15;
+/// Stop fetching a list after this many pages of 50 entries.
+const MAX_LIST_PAGES: i64 = 6;
+
+/// Statuses of an entry on an AniList list.
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ListStatus {
+    Current,
+    Planning,
+    Completed,
+    Dropped,
+    Paused,
+    Repeating,
+}
+
+impl From<ListStatus> for media_list_query::MediaListStatus {
+    fn from(status: ListStatus) -> Self {
+        match status {
+            ListStatus::Current => media_list_query::MediaListStatus::CURRENT,
+            ListStatus::Planning => media_list_query::MediaListStatus::PLANNING,
+            ListStatus::Completed => media_list_query::MediaListStatus::COMPLETED,
+            ListStatus::Dropped => media_list_query::MediaListStatus::DROPPED,
+            ListStatus::Paused => media_list_query::MediaListStatus::PAUSED,
+            ListStatus::Repeating => media_list_query::MediaListStatus::REPEATING,
+        }
+    }
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    subcommands("link", "unlink", "profile", "list"),
+    description_localized("en-US", "Links AniList accounts and shows their profiles and lists")
+)]
+pub async fn anilist(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Links your AniList account")
+)]
+pub async fn link(
+    context: Context<'_>,
+    #[description = "Your AniList username"] username: String,
+) -> Result<(), Error> {
+    let (endpoint, _, _) = anilist_config(&context).await;
+    context.defer_ephemeral().await?;
+    let user = match user_query(
+        &context.data().http,
+        &endpoint,
+        user_query::Variables {
+            id: None,
+            name: Some(username.trim().to_owned()),
+        },
+    )
+    .await?
+    {
+        Some(user) => user,
+        None => {
+            let locale = reply_locale(context).await;
+            let message = tr(&locale, "anilist-no-user", &[("name", username.into())]);
+            reply(context, message).await?;
+            return Ok(());
+        }
+    };
+    let link = AnilistLink {
+        anilist_id: user.id,
+        username: user.name.clone(),
+    };
+    let user_id = context.author().id;
+    context
+        .data()
+        .db
+        .run(move |connection| anilist_links::set(connection, user_id, &link))
+        .await?;
+    let name = match user.site_url {
+        Some(url) => format!("[{}](<{}>)", user.name, url),
+        None => user.name,
+    };
+    let locale = reply_locale(context).await;
+    reply(
+        context,
+        tr(&locale, "anilist-linked", &[("name", name.into())]),
+    )
+    .await
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Unlinks your AniList account")
+)]
+pub async fn unlink(context: Context<'_>) -> Result<(), Error> {
+    let user_id = context.author().id;
+    let removed = context
+        .data()
+        .db
+        .run(move |connection| anilist_links::remove(connection, user_id))
+        .await?;
+    let locale = reply_locale(context).await;
+    let message = if removed {
+        tr(&locale, "anilist-unlinked", &[])
+    } else {
+        tr(&locale, "anilist-not-linked", &[])
+    };
+    reply(context, message).await
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Shows the AniList statistics of a user")
+)]
+pub async fn profile(
+    context: Context<'_>,
+    #[description = "User to show, leave empty for yourself"] user: Option<User>,
+) -> Result<(), Error> {
+    let link = match linked_account(context, user).await? {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    context.defer().await?;
+    let user = user_query(
+        &context.data().http,
+        &endpoint,
+        user_query::Variables {
+            id: Some(link.anilist_id),
+            name: None,
+        },
+    )
+    .await?
+    .ok_or("Unable to get user from response.")?;
+    let locale = reply_locale(context).await;
+    Reply::embed(profile_embed(&locale, user, colour))
+        .send(context)
+        .await?;
+    Ok(())
+}
+
+fn profile_embed(locale: &str, user: user_query::UserQueryUser, colour: Colour) -> Embed {
+    let label = |id| tr(locale, id, &[]);
+    let mut e = Embed::new().colour(colour).title(&user.name);
+    if let Some(url) = &user.site_url {
+        e = e.url(url);
+    }
+    if let Some(avatar) = user.avatar.and_then(|avatar| avatar.large) {
+        e = e.thumbnail(avatar);
+    }
+    if let Some(banner) = user.banner_image {
+        e = e.image(banner);
+    }
+    let statistics = user.statistics;
+    let mut genres: Vec<(String, i64)> = Vec::new();
+    if let Some(anime) = statistics.as_ref().and_then(|s| s.anime.as_ref()) {
+        e = e
+            .field(label("anilist-anime"), anime.count, true)
+            .field(
+                label("anilist-episodes-watched"),
+                anime.episodes_watched,
+                true,
+            )
+            .field(
+                label("anilist-time-watched"),
+                format_minutes(locale, anime.minutes_watched),
+                true,
+            );
+        if anime.mean_score > 0.0 {
+            e = e.field(
+                label("anilist-anime-mean-score"),
+                format!("{:.1}", anime.mean_score),
+                true,
+            );
+        }
+        add_genres(
+            &mut genres,
+            anime
+                .genres
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|g| (&g.genre, g.count)),
+        );
+    }
+    if let Some(manga) = statistics.as_ref().and_then(|s| s.manga.as_ref()) {
+        e = e
+            .field(label("anilist-manga"), manga.count, true)
+            .field(label("anilist-chapters-read"), manga.chapters_read, true)
+            .field(label("anilist-volumes-read"), manga.volumes_read, true);
+        if manga.mean_score > 0.0 {
+            e = e.field(
+                label("anilist-manga-mean-score"),
+                format!("{:.1}", manga.mean_score),
+                true,
+            );
+        }
+        add_genres(
+            &mut genres,
+            manga
+                .genres
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|g| (&g.genre, g.count)),
+        );
+    }
+    genres.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    if !genres.is_empty() {
+        e = e.field(
+            label("anilist-favourite-genres"),
+            genres
+                .into_iter()
+                .take(5)
+                .map(|(genre, _)| genre)
+                .collect::<Vec<_>>()
+                .join(", "),
+            false,
+        );
+    }
+    e.footer(label("anilist-footer")).footer_icon(ANILIST_ICON)
+}
+
+/// Adds the entry counts of anime or manga genres to the combined counts.
+fn add_genres<'a>(
+    genres: &mut Vec<(String, i64)>,
+    statistics: impl Iterator<Item = (&'a Option<String>, i64)>,
+) {
+    for (genre, count) in statistics {
+        let genre = match genre {
+            Some(genre) => genre,
+            None => continue,
+        };
+        match genres.iter_mut().find(|(g, _)| g == genre) {
+            Some((_, total)) => *total += count,
+            None => genres.push((genre.clone(), count)),
+        }
+    }
+}
+
+/// Formats minutes as days and hours in `locale`, e.g. "12d 4h".
+fn format_minutes(locale: &str, minutes: i64) -> String {
+    let hours = minutes / 60;
+    match (hours / 24, hours % 24) {
+        (0, hours) => tr(locale, "anilist-hours", &[("hours", hours.into())]),
+        (days, hours) => tr(
+            locale,
+            "anilist-days-hours",
+            &[("days", days.into()), ("hours", hours.into())],
+        ),
+    }
+}
+
+/// The name of a list status in `locale`.
+fn status_name(locale: &str, status: &media_list_query::MediaListStatus) -> String {
+    tr(
+        locale,
+        "anilist-status",
+        &[("status", format!("{:?}", status).into())],
+    )
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Shows the anime or manga list of a user")
+)]
+pub async fn list(
+    context: Context<'_>,
+    #[description = "User to show, leave empty for yourself"] user: Option<User>,
+    #[description = "Only show entries with this status"] status: Option<ListStatus>,
+    #[description = "Anime or manga, anime if left empty"] kind: Option<MediaKind>,
+) -> Result<(), Error> {
+    let link = match linked_account(context, user).await? {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+    let (endpoint, site, colour) = anilist_config(&context).await;
+    let kind = kind.unwrap_or(MediaKind::Anime);
+    context.defer().await?;
+    let entries = media_list(
+        &context.data().http,
+        &endpoint,
+        link.anilist_id,
+        match kind {
+            MediaKind::Anime => media_list_query::MediaType::ANIME,
+            MediaKind::Manga => media_list_query::MediaType::MANGA,
+        },
+        status.map(Into::into),
+    )
+    .await?;
+    let locale = reply_locale(context).await;
+    let title = tr(
+        &locale,
+        "anilist-list-title",
+        &[
+            ("user", link.username.as_str().into()),
+            (
+                "status",
+                status
+                    .map_or_else(|| "none".to_owned(), |s| status_name(&locale, &s.into()))
+                    .into(),
+            ),
+            (
+                "kind",
+                match kind {
+                    MediaKind::Anime => "anime",
+                    MediaKind::Manga => "manga",
+                }
+                .into(),
+            ),
+        ],
+    );
+    let url = site
+        .join(&format!(
+            "user/{}/{}list",
+            link.username,
+            match kind {
+                MediaKind::Anime => "anime",
+                MediaKind::Manga => "manga",
+            }
+        ))
+        .ok();
+    if entries.is_empty() {
+        let message = tr(&locale, "anilist-list-empty", &[("list", title.into())]);
+        reply(context, message).await?;
+        return Ok(());
+    }
+    let lines = entries
+        .into_iter()
+        .map(|entry| list_line(&locale, entry))
+        .collect::<Vec<_>>();
+    let pages = lines
+        .chunks(LIST_PAGE_SIZE)
+        .map(|chunk| {
+            let mut e = Embed::new()
+                .colour(colour)
+                .title(&title)
+                .description(chunk.join("\n"))
+                .footer(tr(&locale, "anilist-footer", &[]))
+                .footer_icon(ANILIST_ICON);
+            if let Some(url) = &url {
+                e = e.url(url.as_str());
+            }
+            e.truncated().into()
+        })
+        .collect();
+    browse(context, pages).await?;
+    Ok(())
+}
+
+fn list_line(locale: &str, entry: media_list_query::MediaListQueryPageMediaList) -> String {
+    let media = entry.media;
+    let title = media
+        .as_ref()
+        .and_then(|m| m.title.as_ref())
+        .and_then(|t| t.romaji.clone())
+        .unwrap_or_else(|| tr(locale, "title-unavailable", &[]));
+    let mut line = weeb::link(&title, media.as_ref().and_then(|m| m.site_url.as_deref()));
+    if let Some(status) = entry.status {
+        line.push_str(&format!(" · {}", status_name(locale, &status)));
+    }
+    let total = media.and_then(|m| m.episodes.or(m.chapters));
+    match (entry.progress, total) {
+        (Some(progress), Some(total)) => line.push_str(&format!(" · {}/{}", progress, total)),
+        (Some(progress), None) if progress > 0 => line.push_str(&format!(" · {}", progress)),
+        _ => {}
+    }
+    if let Some(score) = entry.score.filter(|score| *score > 0.0) {
+        line.push_str(&format!(" · {}/10", score));
+    }
+    line
+}
+
+/// The AniList account linked by `user`, or the author if `None`.
+///
+/// Replies and returns `None` if no account was linked.
+async fn linked_account(
+    context: Context<'_>,
+    user: Option<User>,
+) -> Result<Option<AnilistLink>, Error> {
+    let user = user.unwrap_or_else(|| context.author().clone());
+    let user_id = user.id;
+    let link = context
+        .data()
+        .db
+        .run(move |connection| anilist_links::get(connection, user_id))
+        .await?;
+    if link.is_none() {
+        let locale = reply_locale(context).await;
+        let message = if user.id == context.author().id {
+            tr(&locale, "anilist-not-linked-self", &[])
+        } else {
+            tr(
+                &locale,
+                "anilist-not-linked-other",
+                &[("user", user.name.into())],
+            )
+        };
+        reply(context, message).await?;
+    }
+    Ok(link)
+}
+
+async fn reply(context: Context<'_>, message: String) -> Result<(), Error> {
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+async fn user_query(
+    http: &HttpClient,
+    endpoint: &Url,
+    variables: user_query::Variables,
+) -> Result<Option<user_query::UserQueryUser>, Box<dyn std::error::Error + Send + Sync>> {
+    let response = http.graphql::<UserQuery>(endpoint, variables).await?;
+    // AniList answers a missing user with a "Not Found." error rather than a null user.
+    Ok(response.data.and_then(|data| data.user))
+}
+
+async fn media_list(
+    http: &HttpClient,
+    endpoint: &Url,
+    user_id: i64,
+    media_type: media_list_query::MediaType,
+    status: Option<media_list_query::MediaListStatus>,
+) -> Result<
+    Vec<media_list_query::MediaListQueryPageMediaList>,
+    Box<dyn std::error::Error + Send + Sync>,
+> {
+    let mut entries = Vec::new();
+    for page in 1..=MAX_LIST_PAGES {
+        let variables = media_list_query::Variables {
+            page: Some(page),
+            user_id: Some(user_id),
+            type_: Some(media_type.clone()),
+            status: status.clone(),
+        };
+        let response = http.graphql::<MediaListQuery>(endpoint, variables).await?;
+        let page = response
+            .data
+            .and_then(|data| data.page)
+            .ok_or("Unable to get list from response.")?;
+        entries.extend(page.media_list.unwrap_or_default().into_iter().flatten());
+        let has_next_page = page
+            .page_info
+            .and_then(|info| info.has_next_page)
+            .unwrap_or(false);
+        if !has_next_page {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn user(statistics: serde_json::Value) -> user_query::UserQueryUser {
+        serde_json::from_value(json!({
+            "id": 1,
+            "name": "Lupusregina",
+            "siteUrl": "https://anilist.co/user/Lupusregina",
+            "avatar": { "large": "https://s4.anilist.co/avatar.png" },
+            "bannerImage": null,
+            "statistics": statistics,
+        }))
+        .unwrap()
+    }
+
+    fn entry(entry: serde_json::Value) -> media_list_query::MediaListQueryPageMediaList {
+        serde_json::from_value(entry).unwrap()
+    }
+
+    #[test]
+    fn minutes_as_days_and_hours() {
+        assert_eq!(format_minutes("en-US", 59), "0h");
+        assert_eq!(format_minutes("en-US", 5 * 60 + 30), "5h");
+        assert_eq!(format_minutes("en-US", 24 * 60), "1d 0h");
+        assert_eq!(format_minutes("en-US", (12 * 24 + 4) * 60), "12d 4h");
+    }
+
+    #[test]
+    fn profile_combines_anime_and_manga_genres() {
+        let genre = |genre: &str, count: i64| json!({ "genre": genre, "count": count });
+        let embed = profile_embed(
+            "en-US",
+            user(json!({
+                "anime": {
+                    "count": 120,
+                    "meanScore": 78.25,
+                    "minutesWatched": 60 * 50,
+                    "episodesWatched": 1400,
+                    "genres": [genre("Action", 40), genre("Comedy", 30)],
+                },
+                "manga": {
+                    "count": 12,
+                    "meanScore": 0.0,
+                    "chaptersRead": 800,
+                    "volumesRead": 60,
+                    "genres": [genre("Comedy", 15), genre("Drama", 5)],
+                },
+            })),
+            Colour::BLUE,
+        );
+
+        assert_eq!(embed.title.as_deref(), Some("Lupusregina"));
+        assert_eq!(
+            embed.url.as_deref(),
+            Some("https://anilist.co/user/Lupusregina")
+        );
+        assert_eq!(embed.field_value("Anime"), Some("120"));
+        assert_eq!(embed.field_value("Time Watched"), Some("2d 2h"));
+        assert_eq!(embed.field_value("Anime Mean Score"), Some("78.2"));
+        assert_eq!(embed.field_value("Chapters Read"), Some("800"));
+        assert_eq!(embed.field_value("Manga Mean Score"), None);
+        assert_eq!(
+            embed.field_value("Favourite Genres"),
+            Some("Comedy, Action, Drama")
+        );
+    }
+
+    #[test]
+    fn profile_without_statistics() {
+        let embed = profile_embed("en-US", user(json!(null)), Colour::BLUE);
+
+        assert!(embed.fields.is_empty());
+        assert_eq!(
+            embed.thumbnail.as_deref(),
+            Some("https://s4.anilist.co/avatar.png")
+        );
+        assert_eq!(embed.footer.as_deref(), Some("Data provided by Anilist.co"));
+    }
+
+    #[test]
+    fn list_lines_show_status_progress_and_score() {
+        let line = list_line(
+            "en-US",
+            entry(json!({
+                "status": "CURRENT",
+                "progress": 5,
+                "score": 8.5,
+                "media": {
+                    "siteUrl": "https://anilist.co/anime/1",
+                    "episodes": 12,
+                    "chapters": null,
+                    "title": { "romaji": "Overlord" },
+                },
+            })),
+        );
+        assert_eq!(
+            line,
+            "[Overlord](https://anilist.co/anime/1) · Current · 5/12 · 8.5/10"
+        );
+
+        let line = list_line(
+            "en-US",
+            entry(json!({
+                "status": "PLANNING",
+                "progress": 0,
+                "score": 0.0,
+                "media": null,
+            })),
+        );
+        assert_eq!(line, "Title unavailable. · Planning");
+    }
+}
@@ -14,14 +14,15 @@
  *    limitations under the License.
  */
 
-use core::fmt;
-
 use chrono::{Datelike, Local, NaiveDate};
 use rand::prelude::*;
 
+use crate::commands::settings::reply_locale;
+use crate::i18n::{ordinal, tr};
+use crate::reply::{Embed, Reply};
 use crate::{Context, Error};
 use lazy_static::lazy_static;
-use poise::serenity_prelude::Colour;
+use poise::serenity_prelude::{Colour, Member, User};
 use std::cmp::Ordering;
 
 lazy_static! {
@@ -75,8 +76,10 @@ impl From<NaiveDate> for Dday {
         let year = date.year() + 1166;
         let mut day_of_year = date.ordinal0();
         let mut tibs_day = false;
-        if year % 4 == 0 && year % 100 != 0 || year % 100 == 0 && year % 400 == 0 {
-            match day_of_year.cmp(&60) {
+        let gregorian_year = date.year();
+        if gregorian_year % 4 == 0 && gregorian_year % 100 != 0 || gregorian_year % 400 == 0 {
+            // February 29th, which isn't part of any season.
+            match day_of_year.cmp(&59) {
                 Ordering::Equal => tibs_day = true,
                 Ordering::Greater => day_of_year -= 1,
                 Ordering::Less => (),
@@ -92,30 +95,33 @@ impl From<NaiveDate> for Dday {
     }
 }
 
-impl fmt::Display for Dday {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Dday {
+    /// States the date in `locale`.
+    fn describe(&self, locale: &str) -> String {
         if self.tibs_day {
-            write!(f, "Today is St. Tib's Day in the YOLD {}", self.year)
-        } else {
-            write!(
-                f,
-                "Today is {}, the {}{} day of {} in the YOLD {}",
-                match self.season_day {
-                    5 => DAPOSTLES[(self.day / 73) as usize],
-                    50 => DHOLIDAYS[(self.day / 73) as usize],
-                    _ => DDAYS[(self.day % 5) as usize],
-                },
-                self.season_day,
-                parse_int_ordinal_suffix(self.season_day),
-                DSEASONS[(self.day / 73) as usize],
-                self.year
-            )
+            return tr(locale, "ddate-tibs", &[("year", self.year.into())]);
         }
+        let day = match self.season_day {
+            5 => DAPOSTLES[(self.day / 73) as usize],
+            50 => DHOLIDAYS[(self.day / 73) as usize],
+            _ => DDAYS[(self.day % 5) as usize],
+        };
+        tr(
+            locale,
+            "ddate-today",
+            &[
+                ("day", day.into()),
+                ("ordinal", ordinal(locale, self.season_day.into()).into()),
+                ("season", DSEASONS[(self.day / 73) as usize].into()),
+                ("year", self.year.into()),
+            ],
+        )
     }
 }
 
 #[poise::command(
     slash_command,
+    category = "fun",
     description_localized(
         "en-US",
         "Ask the magic eight ball your question and receive your fortune."
@@ -123,64 +129,61 @@ impl fmt::Display for Dday {
     aliases("8ball")
 )]
 pub async fn eightball(context: Context<'_>, question: String) -> Result<(), Error> {
-    let answers = vec![
-        "It is certain.",
-        "It is decidedly so.",
-        "Without a doubt.",
-        "Yes- definitely.",
-        "You may rely on it.",
-        "As I see it, yes",
-        "Most likely",
-        "Outlook good.",
-        "Yes.",
-        "Signs point to yes.",
-        "Reply hazy, try again.",
-        "Ask again later.",
-        "Better not tell you now",
-        "Cannot predict now.",
-        "Concentrate and ask again.",
-        "Don't count on it.",
-        "My reply is no.",
-        "My sources say no.",
-        "Outlook not so good.",
-        "Very doubtful.",
-    ];
     let mut rng = rand::rngs::StdRng::from_entropy();
-    let num = rng.gen_range(0..=19);
-    let choice = answers[num];
-    let nick = context.author_member().await.and_then(|m| m.nick.clone());
-
-    context
-        .send(|m| {
-            m.embed(|e| {
-                e.colour({
-                    if num <= 9 {
-                        Colour::new(0x28_A7_45)
-                    } else if num <= 14 {
-                        Colour::new(0xFF_C1_07)
-                    } else {
-                        Colour::new(0xDC_35_45)
-                    }
-                })
-                .description(question)
-                .author(|mut a| {
-                    if let Some(nick) = nick {
-                        a.name(nick);
-                    } else {
-                        a.name(context.author().name.clone());
-                    }
-                    a = a.icon_url(context.author().face());
-                    a
-                })
-                .field("🎱Eightball🎱", choice, false)
-            })
-        })
-        .await?;
+    let answer = rng.gen_range(0..EIGHTBALL_ANSWERS);
+    let member = context.author_member().await;
+    let locale = reply_locale(context).await;
+    eightball_reply(
+        &locale,
+        context.author(),
+        member.as_deref(),
+        question,
+        answer,
+    )
+    .send(context)
+    .await?;
     Ok(())
 }
 
+/// How many answers the eight ball has, positive first, then noncommittal, then negative. Each is
+/// a variant of the `eightball-answer` message.
+pub const EIGHTBALL_ANSWERS: usize = 20;
+
+/// Answers `question` in `locale` with the eight ball answer at index `answer`, attributed to the
+/// asker's nickname if they have one.
+pub fn eightball_reply(
+    locale: &str,
+    author: &User,
+    member: Option<&Member>,
+    question: String,
+    answer: usize,
+) -> Reply {
+    let colour = if answer <= 9 {
+        Colour::new(0x28_A7_45)
+    } else if answer <= 14 {
+        Colour::new(0xFF_C1_07)
+    } else {
+        Colour::new(0xDC_35_45)
+    };
+    let name = member
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| author.name.clone());
+    Reply::embed(
+        Embed::new()
+            .colour(colour)
+            .description(question)
+            .author(name, Some(author.face()))
+            .field(
+                tr(locale, "eightball-title", &[]),
+                tr(locale, "eightball-answer", &[("answer", answer.into())]),
+                false,
+            ),
+    )
+}
+
 #[poise::command(
     slash_command,
+    category = "fun",
     description_localized("en-US", "Display a randomly generated Dark Souls message."),
     aliases("ds")
 )]
@@ -195,6 +198,7 @@ pub async fn darksouls(context: Context<'_>) -> Result<(), Error> {
 
 #[poise::command(
     slash_command,
+    category = "fun",
     description_localized("en-US", "Display a randomly generated Dark Souls 3 message."),
     aliases("ds3")
 )]
@@ -238,6 +242,7 @@ pub async fn darksouls3(context: Context<'_>) -> Result<(), Error> {
 
 #[poise::command(
     slash_command,
+    category = "fun",
     description_localized("en-US", "Display a randomly generated Bloodborne note."),
     aliases("bb")
 )]
@@ -281,26 +286,19 @@ pub async fn bloodborne(context: Context<'_>) -> Result<(), Error> {
 
 #[poise::command(
     slash_command,
+    category = "fun",
     description_localized("en-US", "Display the current date of the Discordian/Erisian Calendar"),
     aliases("dd")
 )]
 pub async fn ddate(context: Context<'_>) -> Result<(), Error> {
-    let today = Local::now();
-    let message = Dday::from(today.date_naive());
-    context.say(format!("{}", message)).await?;
+    let locale = reply_locale(context).await;
+    ddate_reply(&locale, Local::now().date_naive())
+        .send(context)
+        .await?;
     Ok(())
 }
 
-fn parse_int_ordinal_suffix(num: u32) -> &'static str {
-    if num / 10 == 1 {
-        "th"
-    } else if num % 10 == 1 {
-        "st"
-    } else if num % 10 == 2 {
-        "nd"
-    } else if num % 10 == 3 {
-        "rd"
-    } else {
-        "th"
-    }
+/// States the Discordian date of `date` in `locale`.
+pub fn ddate_reply(locale: &str, date: NaiveDate) -> Reply {
+    Reply::text(Dday::from(date).describe(locale))
 }
@@ -14,12 +14,16 @@
  *    limitations under the License.
  */
 
+use crate::commands::settings::reply_locale;
+use crate::i18n::tr;
+use crate::reply::{Embed, Reply};
 use crate::{Context, Error};
 use chrono::Utc;
-use poise::serenity_prelude::{Colour, Permissions, ShardId, User};
+use poise::serenity_prelude::{Guild, Member, Permissions, ShardId, User};
 
 #[poise::command(
     slash_command,
+    category = "general",
     description_localized("en-US", "Shows information about the bot.")
 )]
 pub async fn about(context: Context<'_>) -> Result<(), Error> {
@@ -27,7 +31,7 @@ pub async fn about(context: Context<'_>) -> Result<(), Error> {
     let face = current_user.face();
     let invite_url = current_user
         .invite_url(
-            &context.discord(),
+            context.discord(),
             Permissions::SEND_MESSAGES
                 | Permissions::EMBED_LINKS
                 | Permissions::ADD_REACTIONS
@@ -35,21 +39,27 @@ pub async fn about(context: Context<'_>) -> Result<(), Error> {
                 | Permissions::CHANGE_NICKNAME,
         )
         .await?;
+    let colour = context.data().config.read().await.colours.about;
+    let locale = reply_locale(context).await;
     context
         .send(|m| {
             m.embed(|e| {
                 e.url(&invite_url)
-                    .colour(Colour::new(0x00D2_5148))
-                    .description("A battle maid for the Great Tomb of Nazarick")
-                    .title(&crate::BOT_NAME)
+                    .colour(colour)
+                    .description(tr(&locale, "about-description", &[]))
+                    .title(crate::BOT_NAME)
                     .author(|mut a| {
-                        a = a.name(&crate::BOT_NAME);
+                        a = a.name(crate::BOT_NAME);
                         // Bot avatar URL
                         a = a.icon_url(&face);
                         a
                     })
-                    .field("Authors", &crate::AUTHORS, false)
-                    .field("Source Code", "https://github.com/flat/lupusregina-", false)
+                    .field(tr(&locale, "about-authors", &[]), crate::AUTHORS, false)
+                    .field(
+                        tr(&locale, "about-source-code", &[]),
+                        "https://github.com/flat/lupusregina-",
+                        false,
+                    )
             })
             .ephemeral(true)
         })
@@ -57,7 +67,11 @@ pub async fn about(context: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(context_menu_command = "User information", guild_only)]
+#[poise::command(
+    context_menu_command = "User information",
+    guild_only,
+    category = "general"
+)]
 pub async fn userinfo(
     context: Context<'_>,
     #[description = "The user to show information about."] user: User,
@@ -66,30 +80,42 @@ pub async fn userinfo(
         .guild_id()
         .ok_or("Failed to get GuildID from Message.")?;
     let member = guild_id.member(context.discord(), user.id).await?;
-
-    let nickname = member.nick.map_or("None".to_owned(), |nick| nick);
-    let member_joined = member
-        .joined_at
-        .map_or("Unavailable".to_owned(), |d| format!("{}", d));
-
-    context
-        .send(move |m| {
-            m.embed(move |e| {
-                e.author(|a| a.name(&user.name).icon_url(&user.face()))
-                    .field("Discriminator", format!("#{:04}", user.discriminator), true)
-                    .field("User ID", user.id, true)
-                    .field("Nickname", nickname, true)
-                    .field("User Created", user.created_at(), true)
-                    .field("Joined Server", member_joined, true)
-            })
-            .ephemeral(true)
-        })
+    let locale = reply_locale(context).await;
+    userinfo_reply(&locale, &user, &member)
+        .send(context)
         .await?;
     Ok(())
 }
 
+/// Describes `user` and their membership of the current guild.
+pub fn userinfo_reply(locale: &str, user: &User, member: &Member) -> Reply {
+    let nickname = member
+        .nick
+        .clone()
+        .unwrap_or_else(|| tr(locale, "userinfo-no-nickname", &[]));
+    let member_joined = member.joined_at.map_or_else(
+        || tr(locale, "userinfo-joined-unavailable", &[]),
+        |d| format!("{}", d),
+    );
+    Reply::embed(
+        Embed::new()
+            .author(&user.name, Some(user.face()))
+            .field(
+                tr(locale, "userinfo-discriminator", &[]),
+                format!("#{:04}", user.discriminator),
+                true,
+            )
+            .field(tr(locale, "userinfo-user-id", &[]), user.id, true)
+            .field(tr(locale, "userinfo-nickname", &[]), nickname, true)
+            .field(tr(locale, "userinfo-created", &[]), user.created_at(), true)
+            .field(tr(locale, "userinfo-joined", &[]), member_joined, true),
+    )
+    .ephemeral()
+}
+
 #[poise::command(
     slash_command,
+    category = "general",
     guild_only,
     description_localized("en-US", "Shows various information about the guild.")
 )]
@@ -98,46 +124,55 @@ pub async fn guildinfo(context: Context<'_>) -> Result<(), Error> {
         .guild_id()
         .ok_or("Failed to get GuildID from Message.")?;
     let guild = guild_id
-        .to_guild_cached(&context.discord())
+        .to_guild_cached(context.discord())
         .ok_or("Failed to get Guild from GuildID")?;
-
-    context
-        .send(move |m| {
-            m.embed(move |e| {
-                e.author(|a| {
-                    a.name(&guild.name);
-                    if let Some(guild_icon) = &guild.icon_url() {
-                        a.icon_url(guild_icon);
-                    }
-                    a
-                })
-                .field("Guild ID", format!("{}", guild_id), true)
-                .field("Members", guild.member_count, true)
-                .field("Features", format!("{:?}", guild.features), true)
-                .field(
-                    "Nitro Boost Level",
-                    format!("{:?}", guild.premium_tier),
-                    true,
-                )
-                .field("Nitro Boosts", guild.premium_subscription_count, true);
-                if let Some(splash) = guild.splash_url() {
-                    e.image(splash);
-                }
-                e.footer(|f| f.text(format!("Guild created at {}", guild_id.created_at())))
-            })
-            .ephemeral(true)
-        })
-        .await?;
+    let locale = reply_locale(context).await;
+    guildinfo_reply(&locale, &guild).send(context).await?;
     Ok(())
 }
 
+/// Describes `guild`.
+pub fn guildinfo_reply(locale: &str, guild: &Guild) -> Reply {
+    let created = guild.id.created_at().to_string();
+    let mut embed = Embed::new()
+        .author(&guild.name, guild.icon_url())
+        .field(tr(locale, "guildinfo-guild-id", &[]), guild.id, true)
+        .field(
+            tr(locale, "guildinfo-members", &[]),
+            guild.member_count,
+            true,
+        )
+        .field(
+            tr(locale, "guildinfo-features", &[]),
+            format!("{:?}", guild.features),
+            true,
+        )
+        .field(
+            tr(locale, "guildinfo-boost-level", &[]),
+            format!("{:?}", guild.premium_tier),
+            true,
+        )
+        .field(
+            tr(locale, "guildinfo-boosts", &[]),
+            guild.premium_subscription_count,
+            true,
+        )
+        .footer(tr(locale, "guildinfo-created", &[("date", created.into())]));
+    if let Some(splash) = guild.splash_url() {
+        embed = embed.image(splash);
+    }
+    Reply::embed(embed).ephemeral()
+}
+
 #[poise::command(
     slash_command,
+    category = "general",
     description_localized("en-US", "Responds with the current latency to Discord.")
 )]
 pub async fn ping(context: Context<'_>) -> Result<(), Error> {
+    let locale = reply_locale(context).await;
     let now = Utc::now();
-    let msg = context.say("Ping!").await?;
+    let msg = context.say(tr(&locale, "ping-pending", &[])).await?;
     let finish = Utc::now();
     let lping = ((finish.timestamp() - now.timestamp()) * 1000)
         + (i64::from(finish.timestamp_subsec_millis()) - i64::from(now.timestamp_subsec_millis()));
@@ -153,12 +188,14 @@ pub async fn ping(context: Context<'_>) -> Result<(), Error> {
         .latency
         .ok_or("Failed to get latency from shard.")?
         .as_millis();
-    msg.edit(context, |m| {
-        m.content(&format!(
-            "Rest API: {}ms\nShard Latency: {}ms",
-            lping, shard_latency
-        ))
-    })
-    .await?;
+    let latency = tr(
+        &locale,
+        "ping-latency",
+        &[
+            ("rest", lping.into()),
+            ("shard", (shard_latency as u64).into()),
+        ],
+    );
+    msg.edit(context, |m| m.content(latency)).await?;
     Ok(())
 }
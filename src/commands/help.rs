@@ -0,0 +1,483 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::Colour;
+use poise::{Command, CooldownConfig};
+
+use crate::commands::owner::invoked_by_owner;
+use crate::commands::settings::reply_locale;
+use crate::error::BotError;
+use crate::i18n::tr;
+use crate::pagination::browse;
+use crate::reply::{Embed, Reply};
+use crate::{Context, Data, Error};
+
+/// Categories in the order they are listed; commands without one are general.
+const CATEGORIES: &[&str] = &["general", "fun", "weeb", "owner"];
+
+/// Example invocations shown for a command, by qualified name.
+const EXAMPLES: &[(&str, &[&str])] = &[
+    ("help", &["/help anime", "/help prefix set"]),
+    ("prefix set", &["/prefix set prefix:?"]),
+    ("prefix show", &["@Lupusreginaβ prefix show"]),
+    (
+        "settings category",
+        &["/settings category category:Fun enabled:False"],
+    ),
+    ("settings locale", &["/settings locale locale:ja"]),
+    ("anime", &["/anime anime_title:Cowboy Bebop"]),
+    ("manga", &["/manga manga_title:Berserk"]),
+    ("character", &["/character name:Spike Spiegel"]),
+    ("staff", &["/staff name:Kana Hanazawa"]),
+    ("studio", &["/studio name:Madhouse"]),
+    (
+        "airing next",
+        &["/airing next", "/airing next anime_title:Frieren"],
+    ),
+    (
+        "airing follow",
+        &[
+            "/airing follow anime_title:Frieren",
+            "/airing follow anime_title:Frieren channel:#anime",
+        ],
+    ),
+    ("anilist link", &["/anilist link username:Josh"]),
+    ("anilist list", &["/anilist list status:Current kind:Manga"]),
+    ("season", &["/season", "/season season:Fall year:2006"]),
+    ("vtuber search", &["/vtuber search name:Gawr Gura"]),
+    ("vtuber follow", &["/vtuber follow name:Gawr Gura"]),
+    ("wiki", &["/wiki site:vtuber query:Hololive"]),
+    (
+        "wikis add",
+        &["/wikis add name:overlord url:https://overlordmaruyama.fandom.com"],
+    ),
+    ("eightball", &["/eightball question:Will it rain tomorrow?"]),
+    (
+        "alerts enable",
+        &["/alerts enable", "/alerts enable channel:#errors"],
+    ),
+];
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    category = "general",
+    description_localized("en-US", "Lists the commands, or explains one of them")
+)]
+pub async fn help(
+    context: Context<'_>,
+    #[description = "Command to explain, leave empty to list them all"]
+    #[autocomplete = "autocomplete_command"]
+    #[rest]
+    command: Option<String>,
+) -> Result<(), Error> {
+    let owner = invoked_by_owner(context).await;
+    let locale = reply_locale(context).await;
+    match command {
+        Some(query) => {
+            let commands = &context.framework().options().commands;
+            let path = find(commands, &query)
+                .filter(|path| visible(path[0], owner))
+                .ok_or(BotError::NotFound(query))?;
+            let embed = command_embed(context, &locale, &path).await;
+            Reply::embed(embed).ephemeral().send(context).await?;
+        }
+        None => {
+            let listings = listings(context, &locale, owner).await;
+            if let Context::Prefix(_) = context {
+                let pages = listings
+                    .iter()
+                    .flat_map(|listing| listing.page(&locale).pages())
+                    .map(Into::into)
+                    .collect();
+                browse(context, pages).await?;
+            } else {
+                Reply::embed(overview(&locale, &listings))
+                    .ephemeral()
+                    .send(context)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn autocomplete_command(context: Context<'_>, partial: &str) -> Vec<String> {
+    let owner = invoked_by_owner(context).await;
+    let partial = partial.to_lowercase();
+    let mut names = vec![];
+    for command in &context.framework().options().commands {
+        if !visible(command, owner) {
+            continue;
+        }
+        names.push(command.name.clone());
+        for subcommand in &command.subcommands {
+            names.push(format!("{} {}", command.name, subcommand.name));
+        }
+    }
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(&partial))
+        .take(25)
+        .collect()
+}
+
+/// Whether a top-level command is listed for the invoker.
+pub fn visible(command: &Command<Data, Error>, owner: bool) -> bool {
+    owner || !(command.hide_in_help || command.owners_only || category(command) == "owner")
+}
+
+fn category(command: &Command<Data, Error>) -> &'static str {
+    command.category.unwrap_or("general")
+}
+
+/// Looks up `query`, a command name or alias followed by subcommand names, returning the command
+/// and the ones it is nested in, outermost first.
+pub fn find<'a>(
+    commands: &'a [Command<Data, Error>],
+    query: &str,
+) -> Option<Vec<&'a Command<Data, Error>>> {
+    let mut path: Vec<&Command<Data, Error>> = vec![];
+    let mut candidates = commands;
+    for word in query.split_whitespace() {
+        let word = word.trim_start_matches('/');
+        let command = candidates.iter().find(|command| {
+            command.name.eq_ignore_ascii_case(word)
+                || command
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(word))
+        })?;
+        path.push(command);
+        candidates = &command.subcommands;
+    }
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// The description of a command in `locale`, or the default one.
+fn describe(command: &Command<Data, Error>, locale: &str) -> String {
+    command
+        .description_localizations
+        .get(locale)
+        .or(command.description.as_ref())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// How a command is invoked, such as `/airing follow <anime_title> [channel]`.
+pub fn usage(prefix: &str, path: &[&Command<Data, Error>]) -> String {
+    let mut usage = prefix.to_owned();
+    usage.push_str(
+        &path
+            .iter()
+            .map(|command| command.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    if let Some(command) = path.last() {
+        for parameter in &command.parameters {
+            if parameter.required {
+                usage.push_str(&format!(" <{}>", parameter.name));
+            } else {
+                usage.push_str(&format!(" [{}]", parameter.name));
+            }
+        }
+    }
+    usage
+}
+
+/// What a prefix-only command is invoked with here: the server's prefix or the default one.
+async fn text_prefix(context: Context<'_>) -> String {
+    if let Context::Prefix(prefix) = context {
+        return prefix.prefix.to_owned();
+    }
+    if let Some(guild_id) = context.guild_id() {
+        if let Some(prefix) = context.data().guild_settings(guild_id).await.prefix {
+            return prefix;
+        }
+    }
+    context.data().config.read().await.general.prefix.clone()
+}
+
+fn prefix_for<'a>(command: &Command<Data, Error>, text_prefix: &'a str) -> &'a str {
+    if command.slash_action.is_some() {
+        "/"
+    } else {
+        text_prefix
+    }
+}
+
+/// The commands of one category the invoker may see, as `(invocation, description)` pairs.
+struct Listing {
+    title: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Listing {
+    /// A page for the category with a line per command.
+    fn page(&self, locale: &str) -> Embed {
+        let lines = self
+            .entries
+            .iter()
+            .map(|(invocation, description)| {
+                if description.is_empty() {
+                    format!("`{}`", invocation)
+                } else {
+                    format!("`{}` — {}", invocation, description)
+                }
+            })
+            .collect::<Vec<_>>();
+        Embed::new()
+            .colour(Colour::BLURPLE)
+            .title(&self.title)
+            .description(lines.join("\n"))
+            .footer(tr(locale, "help-footer", &[]))
+    }
+}
+
+async fn listings(context: Context<'_>, locale: &str, owner: bool) -> Vec<Listing> {
+    let text_prefix = text_prefix(context).await;
+    let commands = &context.framework().options().commands;
+    let mut listings = vec![];
+    for name in CATEGORIES {
+        let entries = commands
+            .iter()
+            .filter(|command| category(command) == *name && visible(command, owner))
+            .map(|command| {
+                let invocation = match command.context_menu_name {
+                    Some(menu) if command.slash_action.is_none() => menu.to_owned(),
+                    _ => format!("{}{}", prefix_for(command, &text_prefix), command.name),
+                };
+                (invocation, describe(command, locale))
+            })
+            .collect::<Vec<_>>();
+        if !entries.is_empty() {
+            listings.push(Listing {
+                title: tr(locale, &format!("help-category-{}", name), &[]),
+                entries,
+            });
+        }
+    }
+    listings
+}
+
+/// Every category on one embed, with only the command names, for slash commands where the
+/// buttons aren't needed.
+fn overview(locale: &str, listings: &[Listing]) -> Embed {
+    let mut embed = Embed::new()
+        .colour(Colour::BLURPLE)
+        .title(tr(locale, "help-title", &[]))
+        .footer(tr(locale, "help-footer", &[]));
+    for listing in listings {
+        let names = listing
+            .entries
+            .iter()
+            .map(|(invocation, _)| format!("`{}`", invocation))
+            .collect::<Vec<_>>();
+        embed = embed.field(&listing.title, names.join(" "), false);
+    }
+    embed.truncated()
+}
+
+async fn command_embed(
+    context: Context<'_>,
+    locale: &str,
+    path: &[&Command<Data, Error>],
+) -> Embed {
+    let text_prefix = text_prefix(context).await;
+    let command = path[path.len() - 1];
+    let top_level = path[0];
+    let prefix = prefix_for(top_level, &text_prefix);
+    let qualified_name = path
+        .iter()
+        .map(|command| command.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut embed = Embed::new()
+        .colour(Colour::BLURPLE)
+        .title(format!("{}{}", prefix, qualified_name))
+        .description(describe(command, locale));
+
+    if let Some(menu) = command
+        .context_menu_name
+        .filter(|_| command.slash_action.is_none())
+    {
+        embed = embed.field(
+            tr(locale, "help-usage", &[]),
+            tr(locale, "help-context-menu", &[("name", menu.into())]),
+            false,
+        );
+    } else if command.subcommands.is_empty() {
+        embed = embed.field(
+            tr(locale, "help-usage", &[]),
+            format!("`{}`", usage(prefix, path)),
+            false,
+        );
+    } else {
+        let subcommands = command
+            .subcommands
+            .iter()
+            .map(|subcommand| {
+                let mut path = path.to_vec();
+                path.push(subcommand);
+                format!(
+                    "`{}` — {}",
+                    usage(prefix, &path),
+                    describe(subcommand, locale)
+                )
+            })
+            .collect::<Vec<_>>();
+        embed = embed.field(
+            tr(locale, "help-subcommands", &[]),
+            subcommands.join("\n"),
+            false,
+        );
+    }
+
+    if !command.parameters.is_empty() {
+        let parameters = command
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let description = parameter
+                    .description_localizations
+                    .get(locale)
+                    .or(parameter.description.as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+                let optional = if parameter.required {
+                    String::new()
+                } else {
+                    format!(" ({})", tr(locale, "help-optional", &[]))
+                };
+                format!("`{}` — {}{}", parameter.name, description, optional)
+            })
+            .collect::<Vec<_>>();
+        embed = embed.field(
+            tr(locale, "help-parameters", &[]),
+            parameters.join("\n"),
+            false,
+        );
+    }
+
+    if !command.aliases.is_empty() {
+        let aliases = command
+            .aliases
+            .iter()
+            .map(|alias| format!("`{}`", alias))
+            .collect::<Vec<_>>();
+        embed = embed.field(tr(locale, "help-aliases", &[]), aliases.join(" "), true);
+    }
+
+    // Subcommands share the cooldown of their top-level command.
+    let cooldown = context.data().config.read().await.cooldown(&top_level.name);
+    let cooldowns = cooldown_lines(locale, &cooldown);
+    if !cooldowns.is_empty() {
+        embed = embed.field(tr(locale, "help-cooldown", &[]), cooldowns.join("\n"), true);
+    }
+
+    if let Some((_, examples)) = EXAMPLES.iter().find(|(name, _)| *name == qualified_name) {
+        let examples = examples
+            .iter()
+            .map(|example| format!("`{}`", example))
+            .collect::<Vec<_>>();
+        embed = embed.field(tr(locale, "help-examples", &[]), examples.join("\n"), false);
+    }
+
+    if category(top_level) == "owner" || top_level.owners_only || top_level.hide_in_help {
+        embed = embed.footer(tr(locale, "help-owner-only", &[]));
+    }
+    embed.truncated()
+}
+
+fn cooldown_lines(locale: &str, cooldown: &CooldownConfig) -> Vec<String> {
+    [
+        ("help-cooldown-global", cooldown.global),
+        ("help-cooldown-user", cooldown.user),
+        ("help-cooldown-guild", cooldown.guild),
+        ("help-cooldown-channel", cooldown.channel),
+        ("help-cooldown-member", cooldown.member),
+    ]
+    .iter()
+    .filter_map(|(id, duration)| {
+        let seconds = (*duration)?.as_secs_f64();
+        Some(tr(locale, id, &[("seconds", seconds.into())]))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::commands::{fun, owner, prefix};
+
+    fn commands() -> Vec<Command<Data, Error>> {
+        vec![
+            prefix::prefix(),
+            fun::eightball(),
+            owner::flushcache(),
+            help(),
+        ]
+    }
+
+    #[test]
+    fn finds_commands_by_alias_and_subcommand() {
+        let commands = commands();
+        let path = find(&commands, "8ball").unwrap();
+        assert_eq!(path[0].name, "eightball");
+        let path = find(&commands, "/Prefix set").unwrap();
+        assert_eq!(usage("/", &path), "/prefix set <prefix>");
+        assert_eq!(
+            usage("/", &find(&commands, "help").unwrap()),
+            "/help [command]"
+        );
+        assert!(find(&commands, "prefix nope").is_none());
+        assert!(find(&commands, " ").is_none());
+    }
+
+    #[test]
+    fn hides_owner_commands_from_everyone_else() {
+        let commands = commands();
+        let listed = |owner| {
+            commands
+                .iter()
+                .filter(|command| visible(command, owner))
+                .map(|command| command.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(listed(false), ["prefix", "eightball", "help"]);
+        assert_eq!(listed(true).len(), 4);
+    }
+
+    #[test]
+    fn describes_cooldowns() {
+        let cooldown = CooldownConfig {
+            user: Some(Duration::from_secs(5)),
+            guild: Some(Duration::from_millis(2500)),
+            ..Default::default()
+        };
+        assert_eq!(
+            cooldown_lines("en-US", &cooldown),
+            ["5s per user", "2.5s per server"]
+        );
+    }
+}
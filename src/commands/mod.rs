@@ -14,7 +14,16 @@
  *    limitations under the License.
  */
 
+pub mod airing;
+pub mod alerts;
+pub mod anilist;
 pub mod fun;
 pub mod general;
+pub mod help;
 pub mod owner;
+pub mod prefix;
+pub mod season;
+pub mod settings;
+pub mod vtuber;
 pub mod weeb;
+pub mod wiki;
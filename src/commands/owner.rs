@@ -17,15 +17,49 @@
 use chrono::Utc;
 use poise::send_application_reply;
 use poise::serenity_prelude::json::hashmap_to_json_map;
-use poise::serenity_prelude::{Activity, Colour, OnlineStatus};
+use poise::serenity_prelude::{Activity, OnlineStatus};
 #[cfg(target_os = "linux")]
 use procfs::process::Process;
 
-use crate::{serenity, util, Context, Error};
+use crate::cache::cache_path;
+use crate::commands::settings::reply_locale;
+use crate::db::restart::{self, RestartNotice};
+use crate::i18n::tr;
+use crate::reply::{Embed, Reply};
+use crate::shutdown::Mode;
+use crate::{serenity, Context, Error};
+
+/// Whether the invoker is one of the application owners or the owners listed in the configuration.
+pub async fn invoked_by_owner(context: Context<'_>) -> bool {
+    let author = context.author().id;
+    context.framework().options().owners.contains(&author)
+        || context
+            .data()
+            .config
+            .read()
+            .await
+            .general
+            .owners
+            .contains(&author)
+}
+
+/// Command check allowing only the owners, see [`invoked_by_owner`].
+pub async fn is_owner(context: Context<'_>) -> Result<bool, Error> {
+    if invoked_by_owner(context).await {
+        return Ok(true);
+    }
+    let locale = reply_locale(context).await;
+    Reply::text(tr(&locale, "help-owner-only", &[]))
+        .ephemeral()
+        .send(context)
+        .await?;
+    Ok(false)
+}
 
 #[poise::command(
     slash_command,
-    owners_only,
+    category = "owner",
+    check = "is_owner",
     description_localized("en-US", "Shows information about the bot")
 )]
 pub async fn info(context: Context<'_>) -> Result<(), Error> {
@@ -50,26 +84,35 @@ pub async fn info(context: Context<'_>) -> Result<(), Error> {
     let guilds = cache.guilds().len().to_string();
     let channels = cache.private_channels().len().to_string();
     let users = cache.users().len();
+    let colour = context.data().config.read().await.colours.info;
+    let locale = reply_locale(context).await;
 
-    let mut desc = format!(
-        "**Software version**: `{} - v{}`",
-        &crate::BOT_NAME,
-        &crate::VERSION
-    );
-    use std::fmt::Write as _;
-    let _ = write!(desc, "\n**Uptime**: `{}`", &uptime);
+    let mut lines = vec![
+        tr(
+            &locale,
+            "info-version",
+            &[
+                ("name", crate::BOT_NAME.into()),
+                ("version", crate::VERSION.into()),
+            ],
+        ),
+        tr(&locale, "info-uptime", &[("uptime", uptime.into())]),
+    ];
 
     #[cfg(target_os = "linux")]
     if let Ok(process) = Process::myself() {
         if let Ok(page_size) = procfs::page_size() {
             if let Ok(statm) = process.statm() {
-                let _ = write!(
-                    desc,
-                    "\n**Memory Usage**: `{:.2}MB`",
-                    ((statm.resident * page_size as u64) - (statm.shared * page_size as u64))
-                        as f64
+                let megabytes = format!(
+                    "{:.2}",
+                    ((statm.resident * page_size) - (statm.shared * page_size)) as f64
                         / 1048576_f64
                 );
+                lines.push(tr(
+                    &locale,
+                    "info-memory",
+                    &[("megabytes", megabytes.into())],
+                ));
             }
         }
         if let Ok(ticks) = procfs::ticks_per_second() {
@@ -79,49 +122,83 @@ pub async fn info(context: Context<'_>) -> Result<(), Error> {
                         + process.stat()?.stime
                         + process.stat()?.cutime as u64
                         + process.stat()?.cstime as u64)
-                        / ticks as u64)
-                        / (kstats.btime - (process.stat()?.starttime / ticks as u64)));
-                let _ = write!(desc, "\n**CPU Usage**: `{}%`", cpu_usage);
+                        / ticks)
+                        / (kstats.btime - (process.stat()?.starttime / ticks)));
+                lines.push(tr(&locale, "info-cpu", &[("percent", cpu_usage.into())]));
             }
         }
     };
 
-    let _ = write!(desc, "\n**Guilds**: `{}`", guilds);
-    let _ = write!(desc, "\n**Users**: `{}`", users);
-    let _ = write!(desc, "\n**DM Channels**: `{}`", channels);
+    lines.push(tr(&locale, "info-guilds", &[("count", guilds.into())]));
+    lines.push(tr(&locale, "info-users", &[("count", users.into())]));
+    lines.push(tr(
+        &locale,
+        "info-dm-channels",
+        &[("count", channels.into())],
+    ));
+    let stats = context.data().http.cache().stats();
+    lines.push(tr(
+        &locale,
+        "info-response-cache",
+        &[
+            ("entries", stats.entries.into()),
+            ("hits", stats.hits.into()),
+            ("misses", stats.misses.into()),
+        ],
+    ));
 
-    context
-        .send(|m| {
-            m.embed(|e| {
-                e.colour(Colour::FABLED_PINK)
-                    .author(|mut a| {
-                        a = a.name(&name);
-                        a = a.icon_url(&face);
-                        a
-                    })
-                    .title("Running Information")
-                    .description(desc)
-            })
-            .ephemeral(true)
-        })
-        .await?;
+    Reply::embed(
+        Embed::new()
+            .colour(colour)
+            .author(name, Some(face))
+            .title(tr(&locale, "info-title", &[]))
+            .description(lines.join("\n")),
+    )
+    .ephemeral()
+    .send(context)
+    .await?;
     Ok(())
 }
 
-#[poise::command(slash_command, owners_only)]
-pub async fn reload(context: Context<'_>) -> Result<(), Error> {
-    let conf = util::get_configuration()?;
-    {
-        let mut ini = context.data().config.lock().await;
-        *ini = conf;
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized("en-US", "Empties the cache of AniList and wiki responses")
+)]
+pub async fn flushcache(context: Context<'_>) -> Result<(), Error> {
+    let count = context.data().http.cache().clear();
+    let path = cache_path()?;
+    if path.exists() {
+        tokio::fs::remove_file(path).await?;
     }
-    context.say("Reloaded config!").await?;
+    let locale = reply_locale(context).await;
+    let message = tr(&locale, "flushcache-done", &[("count", count.into())]);
+    Reply::text(message).ephemeral().send(context).await?;
+    Ok(())
+}
+
+#[poise::command(slash_command, check = "is_owner", category = "owner")]
+pub async fn reload(context: Context<'_>) -> Result<(), Error> {
+    let result = context.data().config.reload().await;
+    let locale = reply_locale(context).await;
+    let message = match result {
+        Ok(changes) if changes.is_empty() => tr(&locale, "reload-unchanged", &[]),
+        Ok(changes) => tr(
+            &locale,
+            "reload-changed",
+            &[("changes", changes.join("\n").into())],
+        ),
+        Err(e) => tr(&locale, "reload-failed", &[("error", e.to_string().into())]),
+    };
+    Reply::text(message).ephemeral().send(context).await?;
     Ok(())
 }
 
 #[poise::command(
     slash_command,
-    owners_only,
+    category = "owner",
+    check = "is_owner",
     description_localized(
         "en-US",
         "Changes the bot's username. YOU MAY LOSE THE DISCRIMINATOR UPON CHANGING BACK!"
@@ -137,15 +214,18 @@ pub async fn rename(
         .current_user()
         .edit(&context.discord(), |p| p.username(username))
         .await?;
-    context
-        .send(|m| m.content("Name set.").ephemeral(true))
+    let locale = reply_locale(context).await;
+    Reply::text(tr(&locale, "rename-done", &[]))
+        .ephemeral()
+        .send(context)
         .await?;
     Ok(())
 }
 
 #[poise::command(
     slash_command,
-    owners_only,
+    category = "owner",
+    check = "is_owner",
     guild_only,
     description_localized("en-US", "Changes the bot's nickname")
 )]
@@ -160,13 +240,15 @@ pub async fn nickname(
             .edit_nickname(u64::from(guild_id), nickname.as_deref())
             .await?
     }
-    context
-        .send(|m| m.content("Nickname set.").ephemeral(true))
+    let locale = reply_locale(context).await;
+    Reply::text(tr(&locale, "nickname-done", &[]))
+        .ephemeral()
+        .send(context)
         .await?;
     Ok(())
 }
 
-#[poise::command(slash_command, owners_only)]
+#[poise::command(slash_command, check = "is_owner", category = "owner")]
 pub async fn setavatar(
     context: Context<'_>,
     attachment: serenity::Attachment,
@@ -182,8 +264,10 @@ pub async fn setavatar(
     )));
     let map = hashmap_to_json_map(p.0);
     context.discord().http.edit_profile(&map).await?;
-    context
-        .send(|m| m.content("Set avatar.").ephemeral(true))
+    let locale = reply_locale(context).await;
+    Reply::text(tr(&locale, "setavatar-done", &[]))
+        .ephemeral()
+        .send(context)
         .await?;
     Ok(())
 }
@@ -229,7 +313,7 @@ struct TwitchModal {
     text: String,
 }
 
-#[poise::command(slash_command, owners_only)]
+#[poise::command(slash_command, check = "is_owner", category = "owner")]
 pub async fn presence(
     context: poise::ApplicationContext<'_, crate::Data, Error>,
     status: OnlineStatusChoice,
@@ -284,6 +368,54 @@ pub async fn presence(
             }
         }
     };
-    send_application_reply(context, |m| m.content("Set presence.").ephemeral(true)).await?;
+    let message = tr(
+        &reply_locale(Context::Application(context)).await,
+        "presence-done",
+        &[],
+    );
+    send_application_reply(context, |m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized("en-US", "Stops the bot once the running commands finish")
+)]
+pub async fn shutdown(context: Context<'_>) -> Result<(), Error> {
+    let locale = reply_locale(context).await;
+    Reply::text(tr(&locale, "shutdown-requested", &[]))
+        .ephemeral()
+        .send(context)
+        .await?;
+    context.data().shutdown.request(Mode::Stop);
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "owner",
+    check = "is_owner",
+    description_localized(
+        "en-US",
+        "Restarts the bot once the running commands finish, reporting back in this channel"
+    )
+)]
+pub async fn restart(context: Context<'_>) -> Result<(), Error> {
+    let locale = reply_locale(context).await;
+    let message = tr(&locale, "restart-requested", &[]);
+    let notice = RestartNotice {
+        channel_id: context.channel_id(),
+        requested_at: Utc::now().timestamp(),
+        locale,
+    };
+    context
+        .data()
+        .db
+        .run(move |connection| restart::save(connection, &notice))
+        .await?;
+    Reply::text(message).ephemeral().send(context).await?;
+    context.data().shutdown.request(Mode::Restart);
     Ok(())
 }
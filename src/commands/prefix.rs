@@ -0,0 +1,225 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude as serenity;
+use poise::BoxFuture;
+
+use crate::commands::settings::{log_change, reply_locale};
+use crate::i18n::tr;
+use crate::{Context, Data, Error};
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    category = "general",
+    default_member_permissions = "MANAGE_GUILD",
+    required_permissions = "MANAGE_GUILD",
+    subcommands("set", "reset", "show"),
+    description_localized("en-US", "Shows or changes the command prefix for this server.")
+)]
+pub async fn prefix(context: Context<'_>) -> Result<(), Error> {
+    show_prefixes(context).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Sets the command prefix for this server.")
+)]
+pub async fn set(
+    context: Context<'_>,
+    #[description = "New prefix"] prefix: String,
+) -> Result<(), Error> {
+    set_guild_prefix(context, Some(prefix)).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Resets the command prefix for this server to the default.")
+)]
+pub async fn reset(context: Context<'_>) -> Result<(), Error> {
+    set_guild_prefix(context, None).await
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Shows the command prefixes for this server.")
+)]
+pub async fn show(context: Context<'_>) -> Result<(), Error> {
+    show_prefixes(context).await
+}
+
+async fn show_prefixes(context: Context<'_>) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let guild_prefix = context.data().guild_settings(guild_id).await.prefix;
+    let (default_prefix, additional_prefixes) = {
+        let config = context.data().config.read().await;
+        (
+            config.general.prefix.clone(),
+            config.general.additional_prefixes.clone(),
+        )
+    };
+    let bot_id = context.discord().cache.current_user_id();
+    let locale = reply_locale(context).await;
+    let mut message = match guild_prefix {
+        Some(prefix) => tr(&locale, "prefix-current", &[("prefix", prefix.into())]),
+        None => tr(
+            &locale,
+            "prefix-default",
+            &[("prefix", default_prefix.into())],
+        ),
+    };
+    if !additional_prefixes.is_empty() {
+        let prefixes = additional_prefixes
+            .iter()
+            .map(|p| format!("`{}`", p))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message.push('\n');
+        message.push_str(&tr(
+            &locale,
+            "prefix-additional",
+            &[("prefixes", prefixes.into())],
+        ));
+    }
+    message.push('\n');
+    message.push_str(&tr(
+        &locale,
+        "prefix-mention",
+        &[("mention", format!("<@{}>", bot_id).into())],
+    ));
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Stores a guild's prefix, or removes it if `prefix` is `None`, and reports back to the invoker.
+pub(crate) async fn set_guild_prefix(
+    context: Context<'_>,
+    prefix: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let locale = reply_locale(context).await;
+    if let Some(prefix) = &prefix {
+        if let Err(reason) = validate_prefix(prefix) {
+            let reason = tr(&locale, reason, &[]);
+            context.send(|m| m.content(reason).ephemeral(true)).await?;
+            return Ok(());
+        }
+    }
+    let message = match &prefix {
+        Some(prefix) => tr(&locale, "prefix-changed", &[("prefix", prefix.into())]),
+        None => tr(&locale, "prefix-reset-to-default", &[]),
+    };
+    let settings = context
+        .data()
+        .update_guild_settings(guild_id, |s| s.prefix = prefix)
+        .await?;
+    log_change(context, guild_id, &settings, &message).await;
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Checks a prefix is usable for prefix commands, returning the message explaining why not.
+pub fn validate_prefix(prefix: &str) -> Result<(), &'static str> {
+    if prefix.is_empty() || prefix.contains(char::is_whitespace) {
+        Err("prefix-invalid-whitespace")
+    } else if prefix.chars().count() > 16 {
+        Err("prefix-too-long")
+    } else {
+        Ok(())
+    }
+}
+
+/// Uses the guild's prefix if it set one, the configured prefix otherwise.
+pub async fn dynamic_prefix(
+    context: poise::PartialContext<'_, Data, Error>,
+) -> Result<Option<String>, Error> {
+    if let Some(guild_id) = context.guild_id {
+        if let Some(prefix) = context.data.guild_settings(guild_id).await.prefix {
+            return Ok(Some(prefix));
+        }
+    }
+    Ok(Some(
+        context.data.config.read().await.general.prefix.clone(),
+    ))
+}
+
+/// Strips any of the configured additional prefixes from a message.
+pub fn strip_additional_prefix<'a>(
+    _context: &'a serenity::Context,
+    message: &'a serenity::Message,
+    data: &'a Data,
+) -> BoxFuture<'a, Result<Option<(&'a str, &'a str)>, Error>> {
+    Box::pin(async move {
+        let config = data.config.read().await;
+        Ok(split_prefix(
+            &message.content,
+            &config.general.additional_prefixes,
+        ))
+    })
+}
+
+/// Splits `content` after the longest of `prefixes` it starts with, so `!!` wins over `!`
+/// whatever order they are configured in.
+fn split_prefix<'a>(content: &'a str, prefixes: &[String]) -> Option<(&'a str, &'a str)> {
+    prefixes
+        .iter()
+        .filter(|prefix| content.starts_with(prefix.as_str()))
+        .max_by_key(|prefix| prefix.len())
+        .map(|prefix| content.split_at(prefix.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_need_no_whitespace_and_at_most_16_characters() {
+        assert_eq!(validate_prefix("!"), Ok(()));
+        assert_eq!(validate_prefix("ルプスレギナ"), Ok(()));
+        assert_eq!(validate_prefix(""), Err("prefix-invalid-whitespace"));
+        assert_eq!(validate_prefix("lupus "), Err("prefix-invalid-whitespace"));
+        assert_eq!(validate_prefix("a\tb"), Err("prefix-invalid-whitespace"));
+        assert_eq!(validate_prefix(&"!".repeat(16)), Ok(()));
+        assert_eq!(validate_prefix(&"!".repeat(17)), Err("prefix-too-long"));
+    }
+
+    #[test]
+    fn splits_after_the_longest_matching_prefix() {
+        let prefixes = vec!["lupus".to_owned(), "!".to_owned(), "!!".to_owned()];
+        assert_eq!(
+            split_prefix("lupusanime Frieren", &prefixes),
+            Some(("lupus", "anime Frieren"))
+        );
+        assert_eq!(split_prefix("!!ping", &prefixes), Some(("!!", "ping")));
+        assert_eq!(split_prefix("!ping", &prefixes), Some(("!", "ping")));
+        assert_eq!(split_prefix("~ping", &prefixes), None);
+        assert_eq!(split_prefix("!ping", &[]), None);
+    }
+}
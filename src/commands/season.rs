@@ -0,0 +1,343 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use chrono::{Datelike, NaiveDate, Utc};
+use graphql_client::GraphQLQuery;
+use reqwest::Url;
+
+use crate::commands::settings::reply_locale;
+use crate::commands::weeb::{anilist_config, link, ANILIST_ICON};
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::pagination::browse;
+use crate::reply::{Embed, Reply};
+use crate::{Context, Error};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/SeasonQuery.graphql",
+    response_derives = "Debug,Clone",
+    variables_derives = "Clone"
+)]
+struct SeasonQuery;
+
+/// Genres AniList tags anime with, leaving out adult ones.
+const GENRES: &[&str] = &[
+    "Action",
+    "Adventure",
+    "Comedy",
+    "Drama",
+    "Ecchi",
+    "Fantasy",
+    "Horror",
+    "Mahou Shoujo",
+    "Mecha",
+    "Music",
+    "Mystery",
+    "Psychological",
+    "Romance",
+    "Sci-Fi",
+    "Slice of Life",
+    "Sports",
+    "Supernatural",
+    "Thriller",
+];
+/// Entries per page of the chart.
+const SEASON_PAGE_SIZE: usize = 10;
+/// Stop fetching a season after this many pages of 50 entries.
+const MAX_SEASON_PAGES: i64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Season {
+    Winter,
+    Spring,
+    Summer,
+    Fall,
+}
+
+impl Season {
+    /// The season airing in `month` and the year AniList files it under. December belongs to the
+    /// winter season of the following year.
+    pub fn of_month(year: i32, month: u32) -> (Season, i32) {
+        match month {
+            12 => (Season::Winter, year + 1),
+            1 | 2 => (Season::Winter, year),
+            3..=5 => (Season::Spring, year),
+            6..=8 => (Season::Summer, year),
+            _ => (Season::Fall, year),
+        }
+    }
+}
+
+impl From<Season> for season_query::MediaSeason {
+    fn from(season: Season) -> Self {
+        match season {
+            Season::Winter => season_query::MediaSeason::WINTER,
+            Season::Spring => season_query::MediaSeason::SPRING,
+            Season::Summer => season_query::MediaSeason::SUMMER,
+            Season::Fall => season_query::MediaSeason::FALL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum SeasonSort {
+    Popularity,
+    Score,
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum SeasonFormat {
+    #[name = "TV"]
+    Tv,
+    #[name = "ONA"]
+    Ona,
+    Movie,
+}
+
+impl SeasonFormat {
+    fn media_formats(self) -> Vec<season_query::MediaFormat> {
+        match self {
+            SeasonFormat::Tv => vec![
+                season_query::MediaFormat::TV,
+                season_query::MediaFormat::TV_SHORT,
+            ],
+            SeasonFormat::Ona => vec![season_query::MediaFormat::ONA],
+            SeasonFormat::Movie => vec![season_query::MediaFormat::MOVIE],
+        }
+    }
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Lists the anime of a season from Anilist")
+)]
+pub async fn season(
+    context: Context<'_>,
+    #[description = "Season, the current one if left empty"] season: Option<Season>,
+    #[description = "Year, the current one if left empty"]
+    #[min = 1940]
+    #[max = 2100]
+    year: Option<i32>,
+    #[description = "Order of the entries, popularity if left empty"] sort: Option<SeasonSort>,
+    #[description = "Only show entries of this format"] format: Option<SeasonFormat>,
+    #[description = "Only show entries of this genre"]
+    #[autocomplete = "autocomplete_genre"]
+    genre: Option<String>,
+) -> Result<(), Error> {
+    let (season, year) = season_and_year(Utc::now().date_naive(), season, year);
+    let sort = sort.unwrap_or(SeasonSort::Popularity);
+    let formats = match format {
+        Some(format) => format.media_formats(),
+        None => [SeasonFormat::Tv, SeasonFormat::Ona, SeasonFormat::Movie]
+            .iter()
+            .flat_map(|format| format.media_formats())
+            .collect(),
+    };
+    let locale = reply_locale(context).await;
+    let genre = match genre {
+        Some(genre) => match GENRES.iter().find(|g| g.eq_ignore_ascii_case(genre.trim())) {
+            Some(genre) => Some(genre.to_string()),
+            None => {
+                let message = tr(&locale, "season-unknown-genre", &[("genre", genre.into())]);
+                Reply::text(message).ephemeral().send(context).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    let variables = season_query::Variables {
+        page: None,
+        season: Some(season.into()),
+        season_year: Some(year.into()),
+        formats: Some(formats.into_iter().map(Some).collect()),
+        genre: genre.clone(),
+        sort: Some(vec![Some(match sort {
+            SeasonSort::Popularity => season_query::MediaSort::POPULARITY_DESC,
+            SeasonSort::Score => season_query::MediaSort::SCORE_DESC,
+        })]),
+    };
+    context.defer().await?;
+    let media = season_query(&context.data().http, &endpoint, variables).await?;
+
+    let season_name = tr(
+        &locale,
+        "season-name",
+        &[("season", format!("{:?}", season).into())],
+    );
+    let title = tr(
+        &locale,
+        "season-title",
+        &[
+            ("season", season_name.into()),
+            ("year", year.into()),
+            ("format", format.map_or("none", |f| f.name()).into()),
+            ("genre", genre.as_deref().unwrap_or("none").into()),
+        ],
+    );
+    if media.is_empty() {
+        let message = tr(&locale, "season-no-results", &[("title", title.into())]);
+        Reply::text(message).ephemeral().send(context).await?;
+        return Ok(());
+    }
+    let lines = media
+        .into_iter()
+        .enumerate()
+        .map(|(index, media)| season_line(&locale, index + 1, media))
+        .collect::<Vec<_>>();
+    let pages = lines
+        .chunks(SEASON_PAGE_SIZE)
+        .map(|chunk| {
+            Embed::new()
+                .colour(colour)
+                .title(&title)
+                .description(chunk.join("\n"))
+                .footer(tr(&locale, "anilist-footer", &[]))
+                .footer_icon(ANILIST_ICON)
+                .truncated()
+                .into()
+        })
+        .collect();
+    browse(context, pages).await?;
+    Ok(())
+}
+
+/// The season and year to list on `today`, the current ones unless picked.
+fn season_and_year(today: NaiveDate, season: Option<Season>, year: Option<i32>) -> (Season, i32) {
+    let (current_season, current_year) = Season::of_month(today.year(), today.month());
+    let season = season.unwrap_or(current_season);
+    // In December, winter means the season that is about to start.
+    let year = year.unwrap_or(if season == Season::Winter {
+        current_year
+    } else {
+        today.year()
+    });
+    (season, year)
+}
+
+fn season_line(locale: &str, rank: usize, media: season_query::SeasonQueryPageMedia) -> String {
+    let title = media
+        .title
+        .and_then(|t| t.romaji)
+        .unwrap_or_else(|| tr(locale, "title-unavailable", &[]));
+    let mut line = format!("**{}.** {}", rank, link(&title, media.site_url.as_deref()));
+    if let Some(format) = media.format {
+        line.push_str(&format!(" · {:?}", format));
+    }
+    if let Some(episodes) = media.episodes {
+        let episodes = tr(locale, "season-episodes", &[("episodes", episodes.into())]);
+        line.push_str(&format!(" · {}", episodes));
+    }
+    if let Some(score) = media.average_score {
+        line.push_str(&format!(" · {}%", score));
+    }
+    if let Some(popularity) = media.popularity {
+        let members = tr(locale, "season-members", &[("members", popularity.into())]);
+        line.push_str(&format!(" · {}", members));
+    }
+    line
+}
+
+async fn autocomplete_genre<'a>(
+    _context: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    GENRES
+        .iter()
+        .filter(move |genre| genre.to_lowercase().starts_with(&partial.to_lowercase()))
+        .map(|genre| genre.to_string())
+}
+
+async fn season_query(
+    http: &HttpClient,
+    endpoint: &Url,
+    variables: season_query::Variables,
+) -> Result<Vec<season_query::SeasonQueryPageMedia>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut media = Vec::new();
+    for page in 1..=MAX_SEASON_PAGES {
+        let variables = season_query::Variables {
+            page: Some(page),
+            ..variables.clone()
+        };
+        let response = http.graphql::<SeasonQuery>(endpoint, variables).await?;
+        let page = response
+            .data
+            .and_then(|data| data.page)
+            .ok_or("Unable to get season from response.")?;
+        media.extend(page.media.unwrap_or_default().into_iter().flatten());
+        let has_next_page = page
+            .page_info
+            .and_then(|info| info.has_next_page)
+            .unwrap_or(false);
+        if !has_next_page {
+            break;
+        }
+    }
+    Ok(media)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn seasons_of_months() {
+        assert_eq!(Season::of_month(2023, 12), (Season::Winter, 2024));
+        assert_eq!(Season::of_month(2024, 1), (Season::Winter, 2024));
+        assert_eq!(Season::of_month(2024, 2), (Season::Winter, 2024));
+        assert_eq!(Season::of_month(2024, 3), (Season::Spring, 2024));
+        assert_eq!(Season::of_month(2024, 6), (Season::Summer, 2024));
+        assert_eq!(Season::of_month(2024, 9), (Season::Fall, 2024));
+        assert_eq!(Season::of_month(2024, 11), (Season::Fall, 2024));
+    }
+
+    #[test]
+    fn season_and_year_default_to_the_current_season() {
+        assert_eq!(
+            season_and_year(date(2024, 7, 1), None, None),
+            (Season::Summer, 2024)
+        );
+        assert_eq!(
+            season_and_year(date(2023, 12, 20), None, None),
+            (Season::Winter, 2024)
+        );
+    }
+
+    #[test]
+    fn picked_seasons_default_to_the_current_year() {
+        // In December, winter is the one about to start and the others are this year's.
+        assert_eq!(
+            season_and_year(date(2023, 12, 20), Some(Season::Winter), None),
+            (Season::Winter, 2024)
+        );
+        assert_eq!(
+            season_and_year(date(2023, 12, 20), Some(Season::Fall), None),
+            (Season::Fall, 2023)
+        );
+        assert_eq!(
+            season_and_year(date(2024, 2, 1), Some(Season::Spring), Some(2010)),
+            (Season::Spring, 2010)
+        );
+    }
+}
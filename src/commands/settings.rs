@@ -0,0 +1,327 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::{ChannelId, Colour, GuildChannel, GuildId};
+use tracing::warn;
+
+use crate::db::guild_settings::GuildSettings;
+use crate::i18n::{tr, FALLBACK_LOCALE};
+use crate::{Context, Error};
+
+/// Locales supported by the Discord client.
+pub const DISCORD_LOCALES: &[&str] = &[
+    "id", "da", "de", "en-GB", "en-US", "es-ES", "fr", "hr", "it", "lt", "hu", "nl", "no", "pl",
+    "pt-BR", "ro", "fi", "sv-SE", "vi", "tr", "cs", "el", "bg", "ru", "uk", "hi", "th", "zh-CN",
+    "ja", "zh-TW", "ko",
+];
+
+/// Command categories that can be turned off per guild.
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Category {
+    Fun,
+    Weeb,
+}
+
+impl Category {
+    /// The `category` given to the commands in this category.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Fun => "fun",
+            Category::Weeb => "weeb",
+        }
+    }
+}
+
+/// Global command check rejecting commands whose category is disabled in the current guild.
+pub async fn category_enabled(context: Context<'_>) -> Result<bool, Error> {
+    let (guild_id, category) = match (context.guild_id(), context.command().category) {
+        (Some(guild_id), Some(category)) => (guild_id, category),
+        _ => return Ok(true),
+    };
+    let settings = context.data().guild_settings(guild_id).await;
+    if !settings.disabled_categories.contains(category) {
+        return Ok(true);
+    }
+    let locale = reply_locale(context).await;
+    let message = tr(
+        &locale,
+        "settings-category-disabled",
+        &[("category", category.into())],
+    );
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(false)
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "general",
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("show", "prefix", "category", "logchannel", "locale"),
+    description_localized("en-US", "Shows or changes the bot's settings for this server.")
+)]
+pub async fn settings(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Shows the bot's settings for this server.")
+)]
+pub async fn show(context: Context<'_>) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let settings = context.data().guild_settings(guild_id).await;
+    let default_prefix = context.data().config.read().await.general.prefix.clone();
+    let locale = reply_locale(context).await;
+    let disabled = if settings.disabled_categories.is_empty() {
+        tr(&locale, "settings-none", &[])
+    } else {
+        let mut disabled = settings
+            .disabled_categories
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        disabled.sort();
+        disabled.join(", ")
+    };
+    context
+        .send(|m| {
+            m.embed(|e| {
+                e.colour(Colour::BLURPLE)
+                    .title(tr(&locale, "settings-title", &[]))
+                    .field(
+                        tr(&locale, "settings-prefix", &[]),
+                        format!("`{}`", settings.prefix.unwrap_or(default_prefix)),
+                        true,
+                    )
+                    .field(
+                        tr(&locale, "settings-disabled-categories", &[]),
+                        disabled,
+                        true,
+                    )
+                    .field(
+                        tr(&locale, "settings-log-channel", &[]),
+                        settings.log_channel.map_or_else(
+                            || tr(&locale, "settings-none", &[]),
+                            |c| format!("<#{}>", c),
+                        ),
+                        true,
+                    )
+                    .field(
+                        tr(&locale, "settings-locale", &[]),
+                        settings
+                            .locale
+                            .unwrap_or_else(|| tr(&locale, "settings-default", &[])),
+                        true,
+                    )
+            })
+            .ephemeral(true)
+        })
+        .await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Sets the prefix for prefix commands, or resets it.")
+)]
+pub async fn prefix(
+    context: Context<'_>,
+    #[description = "New prefix, leave empty to use the default"] prefix: Option<String>,
+) -> Result<(), Error> {
+    super::prefix::set_guild_prefix(context, prefix).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Enables or disables a category of commands.")
+)]
+pub async fn category(
+    context: Context<'_>,
+    #[description = "Category of commands"] category: Category,
+    #[description = "Whether the commands may be used"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let settings = context
+        .data()
+        .update_guild_settings(guild_id, |s| {
+            if enabled {
+                s.disabled_categories.remove(category.as_str());
+            } else {
+                s.disabled_categories.insert(category.as_str().to_owned());
+            }
+        })
+        .await?;
+    let message = tr(
+        &reply_locale(context).await,
+        "settings-category-changed",
+        &[
+            ("category", category.as_str().into()),
+            ("enabled", enabled.to_string().into()),
+        ],
+    );
+    log_change(context, guild_id, &settings, &message).await;
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Sets the channel settings changes are logged to.")
+)]
+pub async fn logchannel(
+    context: Context<'_>,
+    #[description = "Log channel, leave empty to stop logging"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let channel = channel.map(|c| c.id);
+    let locale = reply_locale(context).await;
+    let message = match channel {
+        Some(channel) => tr(
+            &locale,
+            "settings-log-channel-changed",
+            &[("channel", format!("<#{}>", channel).into())],
+        ),
+        None => tr(&locale, "settings-log-channel-removed", &[]),
+    };
+    // Log to the old channel as well, so removing it doesn't go unnoticed.
+    let previous = context.data().guild_settings(guild_id).await;
+    let settings = context
+        .data()
+        .update_guild_settings(guild_id, |s| s.log_channel = channel)
+        .await?;
+    if previous.log_channel != settings.log_channel {
+        log_change(context, guild_id, &previous, &message).await;
+    }
+    log_change(context, guild_id, &settings, &message).await;
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Sets the language used for replies in this server.")
+)]
+pub async fn locale(
+    context: Context<'_>,
+    #[description = "Discord locale code such as en-US or ja, leave empty for the default"]
+    #[autocomplete = "autocomplete_locale"]
+    locale: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    if let Some(locale) = &locale {
+        if !DISCORD_LOCALES.contains(&locale.as_str()) {
+            let message = tr(
+                &reply_locale(context).await,
+                "settings-locale-unsupported",
+                &[("locale", locale.into())],
+            );
+            context.send(|m| m.content(message).ephemeral(true)).await?;
+            return Ok(());
+        }
+    }
+    // Confirm in the new locale, so its effect is visible right away.
+    let reply_in = match &locale {
+        Some(locale) => locale.clone(),
+        None => context.locale().unwrap_or(FALLBACK_LOCALE).to_owned(),
+    };
+    let message = match &locale {
+        Some(locale) => tr(
+            &reply_in,
+            "settings-locale-changed",
+            &[("locale", locale.into())],
+        ),
+        None => tr(&reply_in, "settings-locale-reset", &[]),
+    };
+    let settings = context
+        .data()
+        .update_guild_settings(guild_id, |s| s.locale = locale)
+        .await?;
+    log_change(context, guild_id, &settings, &message).await;
+    context.send(|m| m.content(message).ephemeral(true)).await?;
+    Ok(())
+}
+
+async fn autocomplete_locale<'a>(
+    _context: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    DISCORD_LOCALES
+        .iter()
+        .filter(move |locale| locale.to_lowercase().starts_with(&partial.to_lowercase()))
+        .map(|locale| locale.to_string())
+}
+
+/// The locale replies to the invoker should use: the guild's setting, the invoker's Discord
+/// language for slash commands, or [`FALLBACK_LOCALE`].
+pub async fn reply_locale(context: Context<'_>) -> String {
+    if let Some(guild_id) = context.guild_id() {
+        if let Some(locale) = context.data().guild_settings(guild_id).await.locale {
+            return locale;
+        }
+    }
+    context.locale().unwrap_or(FALLBACK_LOCALE).to_owned()
+}
+
+/// Announces a settings change in the guild's log channel, if it has one.
+pub(crate) async fn log_change(
+    context: Context<'_>,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+    message: &str,
+) {
+    let channel: ChannelId = match settings.log_channel {
+        Some(channel) => channel,
+        None => return,
+    };
+    let author = context.author();
+    let locale = settings.locale.as_deref().unwrap_or(FALLBACK_LOCALE);
+    let title = tr(locale, "settings-changed", &[]);
+    let result = channel
+        .send_message(context.discord(), |m| {
+            m.embed(|e| {
+                e.colour(Colour::BLURPLE)
+                    .author(|a| a.name(author.tag()).icon_url(author.face()))
+                    .title(title)
+                    .description(message)
+            })
+        })
+        .await;
+    if let Err(e) = result {
+        warn!("Failed to log settings change in guild {}: {}", guild_id, e);
+    }
+}
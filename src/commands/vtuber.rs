@@ -0,0 +1,402 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{GuildChannel, Mentionable};
+use poise::AutocompleteChoice;
+use reqwest::Url;
+use tracing::warn;
+
+use crate::commands::settings::{log_change, reply_locale};
+use crate::commands::weeb::field_list;
+use crate::commands::wiki::{pick, reply};
+use crate::db::vtuber::{self, Follow};
+use crate::http::HttpClient;
+use crate::i18n::{format_date, tr};
+use crate::infobox::{Infobox, WikiDate};
+use crate::mediawiki::{self, Extraction};
+use crate::reply::{Embed, Reply};
+use crate::vtuber::{events_between, EventKind};
+use crate::{Context, Error};
+
+/// How many VTubers a single guild may follow.
+const MAX_FOLLOWS: usize = 50;
+/// Most events listed by `/vtuber upcoming`.
+const UPCOMING_LIMIT: usize = 10;
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    subcommands("search", "follow", "unfollow", "upcoming", "channel"),
+    description_localized("en-US", "Shows Virtual Youtubers and announces their birthdays")
+)]
+pub async fn vtuber(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Shows information about a Virtual Youtuber")
+)]
+pub async fn search(
+    context: Context<'_>,
+    #[description = "Name to search for"]
+    #[autocomplete = "autocomplete_vtuber"]
+    name: String,
+) -> Result<(), Error> {
+    let api = context.data().config.read().await.api.vtuber_wiki.clone();
+    let http = &context.data().http;
+    let picked = match pick(context, &api, &name, "vtubers").await? {
+        Some(picked) => picked,
+        None => return Ok(()),
+    };
+    let embed = vtuber_embed(http, &api, &picked.title, &picked.url).await?;
+    picked.show(context, embed).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "weeb",
+    required_permissions = "MANAGE_GUILD",
+    description_localized(
+        "en-US",
+        "Announces a Virtual Youtuber's birthday and debut anniversary in this server"
+    )
+)]
+pub async fn follow(
+    context: Context<'_>,
+    #[description = "Name to search for"]
+    #[autocomplete = "autocomplete_vtuber"]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let follows = context
+        .data()
+        .db
+        .run(move |connection| vtuber::for_guild(connection, guild_id))
+        .await?;
+    let locale = reply_locale(context).await;
+    if follows.len() >= MAX_FOLLOWS {
+        let message = tr(&locale, "vtuber-too-many", &[("max", MAX_FOLLOWS.into())]);
+        reply(context, None, message).await?;
+        return Ok(());
+    }
+    let api = context.data().config.read().await.api.vtuber_wiki.clone();
+    context.defer_ephemeral().await?;
+    let picked = match pick(context, &api, &name, "vtubers").await? {
+        Some(picked) => picked,
+        None => return Ok(()),
+    };
+    let infobox = vtuber_infobox(&context.data().http, &api, &picked.title, &picked.url).await?;
+    let follow = Follow {
+        guild_id,
+        title: picked.title.clone(),
+        url: picked.url.clone(),
+        birthday: infobox.birthday_date(),
+        debut: infobox.debut(),
+    };
+    if follow.birthday.is_none() && follow.debut.is_none() {
+        let message = tr(
+            &locale,
+            "vtuber-no-dates",
+            &[("name", follow.title.as_str().into())],
+        );
+        reply(context, picked.prompt, message).await?;
+        return Ok(());
+    }
+
+    let date = |date: Option<WikiDate>| date.map(|date| format_wiki_date(&locale, date));
+    let dates = match (date(follow.birthday), date(follow.debut)) {
+        (Some(birthday), Some(debut)) => tr(
+            &locale,
+            "vtuber-dates-both",
+            &[("birthday", birthday.into()), ("debut", debut.into())],
+        ),
+        (Some(birthday), None) => tr(
+            &locale,
+            "vtuber-dates-birthday",
+            &[("birthday", birthday.into())],
+        ),
+        (None, debut) => tr(
+            &locale,
+            "vtuber-dates-debut",
+            &[("debut", debut.unwrap_or_default().into())],
+        ),
+    };
+    let stored = follow.clone();
+    let added = context
+        .data()
+        .db
+        .run(move |connection| vtuber::follow(connection, &stored))
+        .await?;
+    let channel = context
+        .data()
+        .db
+        .run(move |connection| vtuber::channel(connection, guild_id))
+        .await?;
+    let message = tr(
+        &locale,
+        if added {
+            "vtuber-followed"
+        } else {
+            "vtuber-updated"
+        },
+        &[
+            ("name", follow.title.as_str().into()),
+            ("dates", dates.into()),
+            (
+                "channel",
+                channel
+                    .map_or_else(|| "none".to_owned(), |c| c.mention().to_string())
+                    .into(),
+            ),
+        ],
+    );
+    reply(context, picked.prompt, message).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "weeb",
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Stops announcing a Virtual Youtuber in this server")
+)]
+pub async fn unfollow(
+    context: Context<'_>,
+    #[description = "Followed VTuber"]
+    #[autocomplete = "autocomplete_follows"]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let follows = context
+        .data()
+        .db
+        .run(move |connection| vtuber::for_guild(connection, guild_id))
+        .await?;
+    let locale = reply_locale(context).await;
+    let title = match follows
+        .into_iter()
+        .find(|follow| follow.title.eq_ignore_ascii_case(name.trim()))
+    {
+        Some(follow) => follow.title,
+        None => {
+            let message = tr(&locale, "vtuber-not-following", &[("name", name.into())]);
+            reply(context, None, message).await?;
+            return Ok(());
+        }
+    };
+    let removed = title.clone();
+    context
+        .data()
+        .db
+        .run(move |connection| vtuber::unfollow(connection, guild_id, &removed))
+        .await?;
+    let message = tr(&locale, "vtuber-unfollowed", &[("name", title.into())]);
+    reply(context, None, message).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "weeb",
+    description_localized(
+        "en-US",
+        "Lists the next birthdays and debut anniversaries of the VTubers this server follows"
+    )
+)]
+pub async fn upcoming(context: Context<'_>) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let follows = context
+        .data()
+        .db
+        .run(move |connection| vtuber::for_guild(connection, guild_id))
+        .await?;
+    let locale = reply_locale(context).await;
+    if follows.is_empty() {
+        let message = tr(&locale, "vtuber-none-followed", &[]);
+        reply(context, None, message).await?;
+        return Ok(());
+    }
+    let today = Utc::now().date_naive();
+    let lines = events_between(&follows, today, today + Duration::days(365))
+        .into_iter()
+        .take(UPCOMING_LIMIT)
+        .filter_map(|event| {
+            let timestamp = event.date.and_hms_opt(0, 0, 0)?.timestamp();
+            let emoji = match event.kind {
+                EventKind::Birthday => "🎂",
+                EventKind::Anniversary(_) => "🎉",
+            };
+            Some(format!(
+                "<t:{0}:D> (<t:{0}:R>) {1} [{2}]({3})",
+                timestamp,
+                emoji,
+                event.describe(&locale),
+                event.follow.url
+            ))
+        })
+        .collect::<Vec<_>>();
+    let colour = context.data().config.read().await.colours.vtuber;
+    let embed = Embed::new()
+        .colour(colour)
+        .title(tr(&locale, "vtuber-upcoming-title", &[]))
+        .description(lines.join("\n"))
+        .footer(tr(&locale, "vtuber-wiki-footer", &[]));
+    Reply::embed(embed).send(context).await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "weeb",
+    required_permissions = "MANAGE_GUILD",
+    description_localized(
+        "en-US",
+        "Sets the channel birthdays and debut anniversaries are announced in"
+    )
+)]
+pub async fn channel(
+    context: Context<'_>,
+    #[description = "Announcement channel, leave empty to stop announcing"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let channel = channel.map(|c| c.id);
+    context
+        .data()
+        .db
+        .run(move |connection| vtuber::set_channel(connection, guild_id, channel))
+        .await?;
+    let locale = reply_locale(context).await;
+    let message = match channel {
+        Some(channel) => tr(
+            &locale,
+            "vtuber-channel-set",
+            &[("channel", channel.mention().to_string().into())],
+        ),
+        None => tr(&locale, "vtuber-channel-removed", &[]),
+    };
+    let settings = context.data().guild_settings(guild_id).await;
+    log_change(context, guild_id, &settings, &message).await;
+    reply(context, None, message).await
+}
+
+async fn autocomplete_vtuber(context: Context<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.trim();
+    if partial.chars().count() < 2 {
+        return Vec::new();
+    }
+    let api = context.data().config.read().await.api.vtuber_wiki.clone();
+    match mediawiki::search(&context.data().http, &api, partial).await {
+        Ok(matches) => matches.into_iter().map(|(title, _)| title).collect(),
+        Err(e) => {
+            warn!("Failed to autocomplete VTuber names: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn autocomplete_follows(
+    context: Context<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice<String>> {
+    let guild_id = match context.guild_id() {
+        Some(guild_id) => guild_id,
+        None => return Vec::new(),
+    };
+    let follows = context
+        .data()
+        .db
+        .run(move |connection| vtuber::for_guild(connection, guild_id))
+        .await
+        .unwrap_or_default();
+    let partial = partial.to_lowercase();
+    follows
+        .into_iter()
+        .filter(|follow| follow.title.to_lowercase().contains(&partial))
+        .take(25)
+        .map(|follow| AutocompleteChoice {
+            name: follow.title.chars().take(100).collect(),
+            value: follow.title,
+        })
+        .collect()
+}
+
+/// Builds an embed from the introduction and infobox of the wiki article `title`.
+pub async fn vtuber_embed(
+    http: &HttpClient,
+    api: &Url,
+    title: &str,
+    url: &str,
+) -> Result<Embed, Error> {
+    let html = mediawiki::article_html(http, api, title).await?;
+    let extraction = Extraction {
+        start: Some("</aside>".to_owned()),
+        end: None,
+    };
+    let mut e =
+        mediawiki::article_embed_from_html(http, api, title, url, &html, &extraction).await?;
+    let infobox = Url::parse(url)
+        .ok()
+        .and_then(|base| Infobox::parse(&html, &base))
+        .unwrap_or_default();
+    for (name, value) in infobox.fields() {
+        e = e.field(
+            name,
+            field_list(value.lines().map(str::to_owned).collect()),
+            true,
+        );
+    }
+    Ok(e)
+}
+
+/// Parses the infobox of the wiki article `title`, which is empty if the article has none.
+pub async fn vtuber_infobox(
+    http: &HttpClient,
+    api: &Url,
+    title: &str,
+    url: &str,
+) -> Result<Infobox, Error> {
+    let html = mediawiki::article_html(http, api, title).await?;
+    Ok(Url::parse(url)
+        .ok()
+        .and_then(|base| Infobox::parse(&html, &base))
+        .unwrap_or_default())
+}
+
+/// Formats a date such as "June 20" or "September 13, 2020" in `locale`.
+fn format_wiki_date(locale: &str, date: WikiDate) -> String {
+    format_date(
+        locale,
+        date.year.map(Into::into),
+        date.month.into(),
+        Some(date.day.into()),
+    )
+    .unwrap_or_default()
+}
@@ -15,18 +15,24 @@
  */
 
 use chrono::Utc;
-use graphql_client::{GraphQLQuery, Response};
-use html2text::from_read_with_decorator;
-use serde::Deserialize;
+use graphql_client::GraphQLQuery;
+use tracing::warn;
 
 use crate::{Context, Error};
-use poise::serenity_prelude::Colour;
-use reqwest::Client as ReqwestClient;
-use serde_json::{Map, Value};
+use poise::serenity_prelude::{Colour, CreateEmbed, UserId};
+use poise::AutocompleteChoice;
+use reqwest::Url;
 use std::collections::HashMap;
-use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::Duration;
 
-use crate::util::DiscordMarkdownDecorator;
+use crate::commands::settings::reply_locale;
+use crate::error::BotError;
+use crate::http::HttpClient;
+use crate::i18n::{format_date, tr};
+use crate::markdown;
+use crate::pagination::paginate;
+use crate::reply::{Embed, FIELD_VALUE_LIMIT};
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -44,40 +50,246 @@ struct AnimeQuery;
 )]
 struct MangaQuery;
 
-const ANILIST_ICON: &str = "https://anilist.co/img/icons/apple-touch-icon-152x152.png";
-const ANILIST_API_ENDPOINT: &str = "https://graphql.anilist.co";
-const ANILIST_MANGA_PATH: &str = "https://anilist.co/manga/";
-const ANILIST_ANIME_PATH: &str = "https://anilist.co/anime/";
-const VIRTUALYOUTUBER_WIKI_API: &str = "https://virtualyoutuber.fandom.com/api.php";
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/MediaTitleQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+struct MediaTitleQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/CharacterQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+struct CharacterQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/StaffQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+struct StaffQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/anilist/schema.graphql",
+    query_path = "src/anilist/StudioQuery.graphql",
+    response_derives = "Debug,Clone"
+)]
+struct StudioQuery;
+
+pub(crate) const ANILIST_ICON: &str = "https://anilist.co/img/icons/apple-touch-icon-152x152.png";
+/// Marks autocomplete values as AniList IDs, so a picked title can be told apart from a typed one.
+pub(crate) const ANILIST_ID_PREFIX: &str = "id:";
+/// How long to wait for further keystrokes before asking AniList for titles.
+const AUTOCOMPLETE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, poise::ChoiceParameter)]
+pub(crate) enum MediaKind {
+    Anime,
+    Manga,
+}
+
+/// Title suggestions for the `anime` and `manga` commands.
+///
+/// The titles found for a search are kept in the shared response cache like any other query.
+#[derive(Default)]
+pub struct TitleAutocomplete {
+    /// The most recent autocomplete interaction of each user, older ones are dropped.
+    latest: Mutex<HashMap<UserId, u64>>,
+}
+
+impl TitleAutocomplete {
+    /// Records `interaction` as the user's latest and waits to see if another one follows.
+    ///
+    /// Returns `false` if the interaction was superseded while waiting.
+    async fn debounce(&self, user: UserId, interaction: u64) -> bool {
+        self.latest.lock().unwrap().insert(user, interaction);
+        tokio::time::sleep(AUTOCOMPLETE_DEBOUNCE).await;
+        let mut latest = self.latest.lock().unwrap();
+        if latest.get(&user) == Some(&interaction) {
+            latest.remove(&user);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub(crate) async fn autocomplete_anime(
+    context: Context<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice<String>> {
+    autocomplete_media(context, partial, MediaKind::Anime).await
+}
+
+async fn autocomplete_manga(
+    context: Context<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice<String>> {
+    autocomplete_media(context, partial, MediaKind::Manga).await
+}
+
+async fn autocomplete_media(
+    context: Context<'_>,
+    partial: &str,
+    kind: MediaKind,
+) -> Vec<AutocompleteChoice<String>> {
+    let partial = partial.trim();
+    if partial.chars().count() < 2 {
+        return Vec::new();
+    }
+    let endpoint = context.data().config.read().await.api.anilist.clone();
+    let media_type = match kind {
+        MediaKind::Anime => media_title_query::MediaType::ANIME,
+        MediaKind::Manga => media_title_query::MediaType::MANGA,
+    };
+    // AniList searches ignore case, lowercasing lets differently typed searches share a response.
+    let variables = || media_title_query::Variables {
+        search: Some(partial.to_lowercase()),
+        type_: Some(media_type.clone()),
+    };
+    let http = &context.data().http;
+    if !http
+        .graphql_is_cached::<MediaTitleQuery>(&endpoint, variables())
+        .await
+        && !context
+            .data()
+            .title_autocomplete
+            .debounce(context.author().id, context.id())
+            .await
+    {
+        return Vec::new();
+    }
+    let titles = match http
+        .graphql::<MediaTitleQuery>(&endpoint, variables())
+        .await
+    {
+        Ok(response) => response
+            .data
+            .and_then(|data| data.page)
+            .and_then(|page| page.media)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .filter_map(|media| Some((choice_name(media.title?)?, media.id))),
+        Err(e) => {
+            warn!("Failed to autocomplete {:?} titles: {}", kind, e);
+            return Vec::new();
+        }
+    };
+    titles
+        .map(|(name, id)| AutocompleteChoice {
+            name,
+            value: format!("{}{}", ANILIST_ID_PREFIX, id),
+        })
+        .collect()
+}
+
+/// Joins the distinct romaji, English and native titles into a choice name of at most 100
+/// characters, the most Discord allows.
+fn choice_name(title: media_title_query::MediaTitleQueryPageMediaTitle) -> Option<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in vec![title.romaji, title.english, title.native]
+        .into_iter()
+        .flatten()
+    {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let name = names.join(" | ");
+    if name.is_empty() {
+        None
+    } else if name.chars().count() > 100 {
+        Some(name.chars().take(99).chain(Some('…')).collect())
+    } else {
+        Some(name)
+    }
+}
+
+/// Splits a command argument into an AniList ID picked from autocomplete or a title to search.
+pub(crate) fn id_or_title(argument: String) -> (Option<i64>, Option<String>) {
+    match argument
+        .strip_prefix(ANILIST_ID_PREFIX)
+        .and_then(|id| id.parse().ok())
+    {
+        Some(id) => (Some(id), None),
+        None => (None, Some(argument)),
+    }
+}
 
 #[poise::command(
     slash_command,
-    description_localized("en-US", "Shows information about an Anime from Anilist"),
-    global_cooldown = 1
+    category = "weeb",
+    description_localized("en-US", "Shows information about an Anime from Anilist")
 )]
-pub async fn anime(context: Context<'_>, anime_title: String) -> Result<(), Error> {
-    let anime = anime_query(anime_query::Variables {
-        title: Some(anime_title),
-    })
+pub async fn anime(
+    context: Context<'_>,
+    #[description = "Title to search for"]
+    #[autocomplete = "autocomplete_anime"]
+    anime_title: String,
+) -> Result<(), Error> {
+    let (endpoint, site, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let pages = anime_pages(
+        &context.data().http,
+        &endpoint,
+        &site,
+        colour,
+        &locale,
+        anime_title,
+    )
     .await?;
-    let anime = anime
+    paginate(context, pages).await?;
+    Ok(())
+}
+
+/// Looks up an anime by title, or by ID if picked from autocomplete, and builds an embed in
+/// `locale` for every match.
+pub async fn anime_pages(
+    http: &HttpClient,
+    endpoint: &Url,
+    site: &Url,
+    colour: Colour,
+    locale: &str,
+    anime_title: String,
+) -> Result<Vec<CreateEmbed>, Error> {
+    let (id, title) = id_or_title(anime_title.clone());
+    let anime = http
+        .graphql::<AnimeQuery>(endpoint, anime_query::Variables { title, id })
+        .await?;
+    let media = anime
         .data
-        .and_then(|data| {
-            data.page.and_then(|page| {
-                page.media
-                    .and_then(|media| media.first().cloned().map_or_else(|| None, |m| m))
-            })
-        })
-        .ok_or("Unable to get anime from response.")?;
-    let id = anime.id;
+        .and_then(|data| data.page)
+        .and_then(|page| page.media)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if media.is_empty() {
+        return Err(BotError::NotFound(anime_title).into());
+    }
+    media
+        .into_iter()
+        .map(|anime| anime_embed(locale, anime, site, colour).map(|e| e.truncated().into()))
+        .collect()
+}
+
+fn anime_embed(
+    locale: &str,
+    anime: anime_query::AnimeQueryPageMedia,
+    site: &Url,
+    colour: Colour,
+) -> Result<Embed, Error> {
+    let url = site.join(&format!("anime/{}", anime.id))?;
     let title = anime.title.ok_or("Unable to get title field from anime.")?;
     let cover_image = anime.cover_image.and_then(|img| img.large);
-    let description = anime.description;
-    let status = anime.status;
-    let episodes = anime.episodes;
-    let genres = anime.genres;
-    let average_score = anime.average_score;
-    let season = anime.season;
     let start_date = anime.start_date.map_or_else(
         || "0000/00/00".to_string(),
         |sd| {
@@ -101,91 +313,133 @@ pub async fn anime(context: Context<'_>, anime_title: String) -> Result<(), Erro
         },
     );
 
-    context
-        .send(|m| {
-            m.embed(|mut e| {
-                e = e
-                    .color(Colour::BLUE)
-                    .url(format!("{}{}", ANILIST_ANIME_PATH, id));
-                match (&title.romaji, &title.native) {
-                    (Some(romaji), Some(native)) => e = e.title(format!("{} | {}", romaji, native)),
-                    (Some(title), None) | (None, Some(title)) => e = e.title(title),
-                    (None, None) => e = e.title("Title unavailable."),
-                }
-                if let Some(description) = description {
-                    e = e.description(format_desc(description));
-                } else {
-                    e = e.description("No description available.");
-                }
-                if let Some(cover_image) = cover_image {
-                    e = e.thumbnail(cover_image);
-                }
-                if let Some(status) = status {
-                    e = e.field("Status", format!("{:?}", status), true);
-                }
-                if let Some(episodes) = episodes {
-                    e = e.field("Episodes", episodes, true);
-                }
-                if let Some(genres) = genres {
-                    e = e.field(
-                        "Genres",
-                        genres
-                            .into_iter()
-                            .flatten()
-                            .collect::<Vec<String>>()
-                            .join(", "),
-                        true,
-                    );
-                }
-                if let Some(score) = average_score {
-                    e = e.field("Average Score", format!("{}%", score), true);
-                }
-                if let Some(season) = season {
-                    e = e.field("Season", format!("{:?}", season), true);
-                }
-                if start_date != "0000/00/00" {
-                    e = e.field("Start Date", start_date, true);
-                }
-                if end_date != "0000/00/00" {
-                    e = e.field("End Date", end_date, true)
-                }
-                e = e
-                    .timestamp(Utc::now())
-                    .footer(|f| f.text("Data provided by Anilist.co").icon_url(ANILIST_ICON));
-                e
-            })
-        })
-        .await?;
-    Ok(())
+    let mut e = Embed::new().colour(colour).url(url);
+    e = match (&title.romaji, &title.native) {
+        (Some(romaji), Some(native)) => e.title(format!("{} | {}", romaji, native)),
+        (Some(title), None) | (None, Some(title)) => e.title(title),
+        (None, None) => e.title(tr(locale, "title-unavailable", &[])),
+    };
+    if let Some(description) = anime.description {
+        e = e.description(markdown::from_html(&description, None));
+    } else {
+        e = e.description(tr(locale, "description-unavailable", &[]));
+    }
+    if let Some(cover_image) = cover_image {
+        e = e.thumbnail(cover_image);
+    }
+    if let Some(status) = anime.status {
+        e = e.field(
+            tr(locale, "weeb-status", &[]),
+            format!("{:?}", status),
+            true,
+        );
+    }
+    if let Some(episodes) = anime.episodes {
+        e = e.field(tr(locale, "weeb-episodes", &[]), episodes, true);
+    }
+    if let Some(genres) = anime.genres {
+        e = e.field(
+            tr(locale, "weeb-genres", &[]),
+            genres
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(", "),
+            true,
+        );
+    }
+    if let Some(score) = anime.average_score {
+        e = e.field(
+            tr(locale, "weeb-average-score", &[]),
+            format!("{}%", score),
+            true,
+        );
+    }
+    if let Some(season) = anime.season {
+        e = e.field(
+            tr(locale, "weeb-season", &[]),
+            format!("{:?}", season),
+            true,
+        );
+    }
+    if start_date != "0000/00/00" {
+        e = e.field(tr(locale, "weeb-start-date", &[]), start_date, true);
+    }
+    if end_date != "0000/00/00" {
+        e = e.field(tr(locale, "weeb-end-date", &[]), end_date, true);
+    }
+    Ok(e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON))
 }
 
 #[poise::command(
     slash_command,
-    description_localized("en-US", "Shows information about a manga from Anilist"),
-    global_cooldown = 1
+    category = "weeb",
+    description_localized("en-US", "Shows information about a manga from Anilist")
 )]
-pub async fn manga(context: Context<'_>, manga_title: String) -> Result<(), Error> {
-    let manga = manga_query(manga_query::Variables {
-        title: Some(manga_title),
-    })
+pub async fn manga(
+    context: Context<'_>,
+    #[description = "Title to search for"]
+    #[autocomplete = "autocomplete_manga"]
+    manga_title: String,
+) -> Result<(), Error> {
+    let (endpoint, site, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let pages = manga_pages(
+        &context.data().http,
+        &endpoint,
+        &site,
+        colour,
+        &locale,
+        manga_title,
+    )
     .await?;
-    let manga = manga
+    paginate(context, pages).await?;
+    Ok(())
+}
+
+/// Looks up a manga by title, or by ID if picked from autocomplete, and builds an embed in
+/// `locale` for every match.
+pub async fn manga_pages(
+    http: &HttpClient,
+    endpoint: &Url,
+    site: &Url,
+    colour: Colour,
+    locale: &str,
+    manga_title: String,
+) -> Result<Vec<CreateEmbed>, Error> {
+    let (id, title) = id_or_title(manga_title.clone());
+    let manga = http
+        .graphql::<MangaQuery>(endpoint, manga_query::Variables { title, id })
+        .await?;
+    let media = manga
         .data
-        .and_then(|data| {
-            data.page.and_then(|page| {
-                page.media
-                    .and_then(|media| media.first().cloned().map_or_else(|| None, |m| m))
-            })
-        })
-        .ok_or("Unable to get manga from response.")?;
-    let id = manga.id;
+        .and_then(|data| data.page)
+        .and_then(|page| page.media)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if media.is_empty() {
+        return Err(BotError::NotFound(manga_title).into());
+    }
+    media
+        .into_iter()
+        .map(|manga| manga_embed(locale, manga, site, colour).map(|e| e.truncated().into()))
+        .collect()
+}
+
+fn manga_embed(
+    locale: &str,
+    manga: manga_query::MangaQueryPageMedia,
+    site: &Url,
+    colour: Colour,
+) -> Result<Embed, Error> {
+    let url = site.join(&format!("manga/{}", manga.id))?;
     let title = manga.title.ok_or("Unable to get title field from manga.")?;
     let cover_image = manga.cover_image.and_then(|img| img.large);
-    let description = manga.description;
-    let status = manga.status;
-    let chapters = manga.chapters;
-    let genres = manga.genres;
-    let average_score = manga.average_score;
     let start_date = manga.start_date.map_or_else(
         || "0000/00/00".to_string(),
         |sd| {
@@ -209,233 +463,755 @@ pub async fn manga(context: Context<'_>, manga_title: String) -> Result<(), Erro
         },
     );
 
-    context
-        .send(|m| {
-            m.embed(|mut e| {
-                e = e
-                    .color(Colour::BLUE)
-                    .url(format!("{}{}", ANILIST_MANGA_PATH, id));
-                match (&title.romaji, &title.native) {
-                    (Some(romaji), Some(native)) => e = e.title(format!("{} | {}", romaji, native)),
-                    (Some(title), None) | (None, Some(title)) => e = e.title(title),
-                    (None, None) => e = e.title("Title unavailable."),
-                }
-                if let Some(description) = description {
-                    e = e.description(format_desc(description));
-                } else {
-                    e = e.description("No description available.");
-                }
-                if let Some(cover_image) = cover_image {
-                    e = e.thumbnail(cover_image);
-                }
-                if let Some(status) = status {
-                    e = e.field("Status", format!("{:?}", status), true);
-                }
-                if let Some(chapters) = chapters {
-                    e = e.field("Chapters", chapters, true);
-                }
-                if let Some(genres) = genres {
-                    e = e.field(
-                        "Genres",
-                        genres
-                            .into_iter()
-                            .flatten()
-                            .collect::<Vec<String>>()
-                            .join(", "),
-                        true,
-                    );
-                }
-                if let Some(score) = average_score {
-                    e = e.field("Average Score", format!("{}%", score), true);
-                }
-                if start_date != "0000/00/00" {
-                    e = e.field("Start Date", start_date, true);
-                }
-                if end_date != "0000/00/00" {
-                    e = e.field("End Date", end_date, true)
-                }
-                e = e
-                    .timestamp(Utc::now())
-                    .footer(|f| f.text("Data provided by Anilist.co").icon_url(ANILIST_ICON));
-                e
-            })
-        })
-        .await?;
-    Ok(())
-}
-
-async fn anime_query(
-    variables: anime_query::Variables,
-) -> Result<Response<anime_query::ResponseData>, Box<dyn std::error::Error + Send + Sync>> {
-    let request_body = AnimeQuery::build_query(variables);
-    let client = ReqwestClient::new();
-    let res = client
-        .post(ANILIST_API_ENDPOINT)
-        .json(&request_body)
-        .send()
-        .await?;
-    res.json().await.map_err(From::from)
+    let mut e = Embed::new().colour(colour).url(url);
+    e = match (&title.romaji, &title.native) {
+        (Some(romaji), Some(native)) => e.title(format!("{} | {}", romaji, native)),
+        (Some(title), None) | (None, Some(title)) => e.title(title),
+        (None, None) => e.title(tr(locale, "title-unavailable", &[])),
+    };
+    if let Some(description) = manga.description {
+        e = e.description(markdown::from_html(&description, None));
+    } else {
+        e = e.description(tr(locale, "description-unavailable", &[]));
+    }
+    if let Some(cover_image) = cover_image {
+        e = e.thumbnail(cover_image);
+    }
+    if let Some(status) = manga.status {
+        e = e.field(
+            tr(locale, "weeb-status", &[]),
+            format!("{:?}", status),
+            true,
+        );
+    }
+    if let Some(chapters) = manga.chapters {
+        e = e.field(tr(locale, "weeb-chapters", &[]), chapters, true);
+    }
+    if let Some(genres) = manga.genres {
+        e = e.field(
+            tr(locale, "weeb-genres", &[]),
+            genres
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(", "),
+            true,
+        );
+    }
+    if let Some(score) = manga.average_score {
+        e = e.field(
+            tr(locale, "weeb-average-score", &[]),
+            format!("{}%", score),
+            true,
+        );
+    }
+    if start_date != "0000/00/00" {
+        e = e.field(tr(locale, "weeb-start-date", &[]), start_date, true);
+    }
+    if end_date != "0000/00/00" {
+        e = e.field(tr(locale, "weeb-end-date", &[]), end_date, true);
+    }
+    Ok(e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON))
 }
 
-async fn manga_query(
-    variables: manga_query::Variables,
-) -> Result<Response<manga_query::ResponseData>, Box<dyn std::error::Error + Send + Sync>> {
-    let request_body = MangaQuery::build_query(variables);
-    let client = ReqwestClient::new();
-    let res = client
-        .post(ANILIST_API_ENDPOINT)
-        .json(&request_body)
-        .send()
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized("en-US", "Shows information about a character from Anilist")
+)]
+pub async fn character(
+    context: Context<'_>,
+    #[description = "Name to search for"] name: String,
+) -> Result<(), Error> {
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let response = context
+        .data()
+        .http
+        .graphql::<CharacterQuery>(
+            &endpoint,
+            character_query::Variables {
+                search: Some(name.clone()),
+            },
+        )
         .await?;
-    res.json().await.map_err(From::from)
-}
-
-#[derive(Deserialize, Clone)]
-struct WikiOpenSearchResults(String, Vec<String>, Vec<String>, Vec<String>);
-
-#[derive(Deserialize, Clone)]
-struct WikiQueryResults {
-    #[allow(dead_code)]
-    batchcomplete: String,
-    #[allow(dead_code)]
-    query: Query,
+    let characters = response
+        .data
+        .and_then(|data| data.page)
+        .and_then(|page| page.characters)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if characters.is_empty() {
+        return Err(BotError::NotFound(name).into());
+    }
+    let pages = characters
+        .into_iter()
+        .map(|character| {
+            character_embed(&locale, character, colour)
+                .truncated()
+                .into()
+        })
+        .collect::<Vec<_>>();
+    paginate(context, pages).await?;
+    Ok(())
 }
 
-#[derive(Deserialize, Clone)]
-struct Query {
-    #[allow(dead_code)]
-    pages: Vec<Map<String, Value>>,
-}
+fn character_embed(
+    locale: &str,
+    character: character_query::CharacterQueryPageCharacters,
+    colour: Colour,
+) -> Embed {
+    let mut e = Embed::new().colour(colour);
+    let name = character.name.as_ref();
+    e = e.title(display_name(
+        locale,
+        name.and_then(|n| n.full.as_deref()),
+        name.and_then(|n| n.native.as_deref()),
+    ));
+    if let Some(url) = &character.site_url {
+        e = e.url(url);
+    }
+    if let Some(description) = character.description {
+        e = e.description(markdown::from_html(&description, None));
+    } else {
+        e = e.description(tr(locale, "description-unavailable", &[]));
+    }
+    if let Some(image) = character.image.and_then(|img| img.large) {
+        e = e.thumbnail(image);
+    }
+    if let Some(favourites) = character.favourites {
+        e = e.field(tr(locale, "weeb-favourites", &[]), favourites, true);
+    }
+    if let Some(gender) = character.gender {
+        e = e.field(tr(locale, "weeb-gender", &[]), gender, true);
+    }
+    if let Some(age) = character.age {
+        e = e.field(tr(locale, "weeb-age", &[]), age, true);
+    }
+    if let Some(birthday) = character
+        .date_of_birth
+        .and_then(|date| format_date(locale, date.year, date.month?, date.day))
+    {
+        e = e.field(tr(locale, "weeb-birthday", &[]), birthday, true);
+    }
+    let alternative = name
+        .and_then(|n| n.alternative.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if !alternative.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-also-known-as", &[]),
+            alternative.join(", "),
+            false,
+        );
+    }
 
-#[derive(Deserialize, Clone)]
-struct ParseDetails {
-    parse: Parse,
+    let edges = character
+        .media
+        .and_then(|media| media.edges)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let appearances = edges
+        .iter()
+        .filter_map(|edge| {
+            let node = edge.node.as_ref()?;
+            let title = node.title.as_ref()?.romaji.as_ref()?;
+            let mut line = link(title, node.site_url.as_deref());
+            if let Some(role) = &edge.character_role {
+                line.push_str(&format!(" ({:?})", role));
+            }
+            Some(line)
+        })
+        .collect::<Vec<_>>();
+    if !appearances.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-appears-in", &[]),
+            field_list(appearances),
+            false,
+        );
+    }
+    // Voice actors are listed per media, so the same person shows up once for every show.
+    let mut voice_actors: Vec<(String, Vec<String>)> = Vec::new();
+    for actor in edges
+        .into_iter()
+        .flat_map(|edge| edge.voice_actors.unwrap_or_default())
+        .flatten()
+    {
+        let name = match actor.name.and_then(|name| name.full) {
+            Some(name) => link(&name, actor.site_url.as_deref()),
+            None => continue,
+        };
+        let language = actor
+            .language_v2
+            .unwrap_or_else(|| tr(locale, "weeb-unknown-language", &[]));
+        match voice_actors.iter_mut().find(|(l, _)| *l == language) {
+            Some((_, names)) if !names.contains(&name) => names.push(name),
+            Some(_) => {}
+            None => voice_actors.push((language, vec![name])),
+        }
+    }
+    for (language, names) in voice_actors.into_iter().take(6) {
+        e = e.field(
+            tr(
+                locale,
+                "weeb-voice-actors",
+                &[("language", language.into())],
+            ),
+            field_list(names),
+            true,
+        );
+    }
+    e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON)
 }
 
-#[derive(Deserialize, Clone)]
-struct Parse {
-    #[allow(dead_code)]
-    title: String,
-    #[allow(dead_code)]
-    pageid: u64,
-    text: HashMap<String, String>,
+#[poise::command(
+    slash_command,
+    category = "weeb",
+    description_localized(
+        "en-US",
+        "Shows information about a voice actor or staff member from Anilist"
+    )
+)]
+pub async fn staff(
+    context: Context<'_>,
+    #[description = "Name to search for"] name: String,
+) -> Result<(), Error> {
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let response = context
+        .data()
+        .http
+        .graphql::<StaffQuery>(
+            &endpoint,
+            staff_query::Variables {
+                search: Some(name.clone()),
+            },
+        )
+        .await?;
+    let staff = response
+        .data
+        .and_then(|data| data.page)
+        .and_then(|page| page.staff)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if staff.is_empty() {
+        return Err(BotError::NotFound(name).into());
+    }
+    let pages = staff
+        .into_iter()
+        .map(|staff| staff_embed(&locale, staff, colour).truncated().into())
+        .collect::<Vec<_>>();
+    paginate(context, pages).await?;
+    Ok(())
 }
 
-#[derive(Deserialize, Clone)]
-struct ArticleImage {
-    image: HashMap<String, String>,
+fn staff_embed(locale: &str, staff: staff_query::StaffQueryPageStaff, colour: Colour) -> Embed {
+    let mut e = Embed::new().colour(colour);
+    let name = staff.name.as_ref();
+    e = e.title(display_name(
+        locale,
+        name.and_then(|n| n.full.as_deref()),
+        name.and_then(|n| n.native.as_deref()),
+    ));
+    if let Some(url) = &staff.site_url {
+        e = e.url(url);
+    }
+    if let Some(description) = staff.description {
+        e = e.description(markdown::from_html(&description, None));
+    } else {
+        e = e.description(tr(locale, "description-unavailable", &[]));
+    }
+    if let Some(image) = staff.image.and_then(|img| img.large) {
+        e = e.thumbnail(image);
+    }
+    if let Some(favourites) = staff.favourites {
+        e = e.field(tr(locale, "weeb-favourites", &[]), favourites, true);
+    }
+    if let Some(language) = staff.language_v2 {
+        e = e.field(tr(locale, "weeb-language", &[]), language, true);
+    }
+    if let Some(birthday) = staff
+        .date_of_birth
+        .and_then(|date| format_date(locale, date.year, date.month?, date.day))
+    {
+        e = e.field(tr(locale, "weeb-birthday", &[]), birthday, true);
+    }
+    if let Some(home_town) = staff.home_town {
+        e = e.field(tr(locale, "weeb-hometown", &[]), home_town, true);
+    }
+    let occupations = staff
+        .primary_occupations
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if !occupations.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-occupations", &[]),
+            occupations.join(", "),
+            true,
+        );
+    }
+    let works = staff
+        .staff_media
+        .and_then(|media| media.edges)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter_map(|edge| {
+            let node = edge.node?;
+            let title = node.title?.romaji?;
+            let mut line = link(&title, node.site_url.as_deref());
+            if let Some(role) = edge.staff_role {
+                line.push_str(&format!(" ({})", role));
+            }
+            Some(line)
+        })
+        .collect::<Vec<_>>();
+    if !works.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-notable-works", &[]),
+            field_list(works),
+            false,
+        );
+    }
+    let characters = staff
+        .characters
+        .and_then(|characters| characters.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter_map(|character| {
+            let name = character.name?.full?;
+            Some(link(&name, character.site_url.as_deref()))
+        })
+        .collect::<Vec<_>>();
+    if !characters.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-notable-characters", &[]),
+            field_list(characters),
+            false,
+        );
+    }
+    e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON)
 }
 
 #[poise::command(
     slash_command,
-    description_localized("en-US", "Shows information about a Virtual Youtuber"),
-    global_cooldown = 1
+    category = "weeb",
+    description_localized("en-US", "Shows information about a studio from Anilist")
 )]
-pub async fn vtuber(context: Context<'_>, name: String) -> Result<(), Error> {
-    let search = search_vtuber_wiki(name).await?;
-    let title = search.1[0].clone();
-    let url = search.3[0].clone();
-    let text: String = get_vtuber_article_text(title.clone())
-        .await?
-        .parse
-        .text
-        .get("*")
-        .ok_or("Failed to get text")?
-        .clone();
-    let start = text
-        .find("</aside>")
-        .ok_or("Unable to find start of description")?;
-    let image = get_vtuber_article_image(title.clone()).await?;
-    let parsed_text = from_read_with_decorator(
-        text[start..].as_bytes(),
-        256,
-        DiscordMarkdownDecorator::new(),
-    );
-    let end = parsed_text
-        .find("##")
-        .ok_or("Unable to find end of description")?;
-    let desc = &parsed_text[..end];
-    context
-        .send(|m| {
-            m.embed(|e| {
-                e.title(&title).url(url).description(desc);
-                if let Some(thumbnail) = image.image.get("imageserving") {
-                    e.thumbnail(thumbnail);
-                }
-                e
-            })
-        })
+pub async fn studio(
+    context: Context<'_>,
+    #[description = "Name to search for"] name: String,
+) -> Result<(), Error> {
+    let (endpoint, _, colour) = anilist_config(&context).await;
+    let locale = reply_locale(context).await;
+    context.defer().await?;
+    let response = context
+        .data()
+        .http
+        .graphql::<StudioQuery>(
+            &endpoint,
+            studio_query::Variables {
+                search: Some(name.clone()),
+            },
+        )
         .await?;
+    let studios = response
+        .data
+        .and_then(|data| data.page)
+        .and_then(|page| page.studios)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if studios.is_empty() {
+        return Err(BotError::NotFound(name).into());
+    }
+    let pages = studios
+        .into_iter()
+        .map(|studio| studio_embed(&locale, studio, colour).truncated().into())
+        .collect::<Vec<_>>();
+    paginate(context, pages).await?;
     Ok(())
 }
 
-async fn search_vtuber_wiki(
-    search: String,
-) -> Result<WikiOpenSearchResults, Box<dyn std::error::Error + Send + Sync>> {
-    let client = ReqwestClient::new();
-    client
-        .get(VIRTUALYOUTUBER_WIKI_API)
-        .query(&[
-            ("action", "opensearch"),
-            ("limit", "1"),
-            ("search", &search),
-            ("redirects", "resolve"),
-            ("format", "json"),
-        ])
-        .send()
-        .await?
-        .json::<WikiOpenSearchResults>()
-        .await
-        .map_err(|_| Box::try_from(format!("No search results for {}", &search)).unwrap())
+fn studio_embed(
+    locale: &str,
+    studio: studio_query::StudioQueryPageStudios,
+    colour: Colour,
+) -> Embed {
+    let mut e = Embed::new().colour(colour).title(&studio.name);
+    if let Some(url) = &studio.site_url {
+        e = e.url(url);
+    }
+    if let Some(favourites) = studio.favourites {
+        e = e.field(tr(locale, "weeb-favourites", &[]), favourites, true);
+    }
+    e = e.field(
+        tr(locale, "weeb-type", &[]),
+        tr(
+            locale,
+            if studio.is_animation_studio {
+                "weeb-animation-studio"
+            } else {
+                "weeb-producer"
+            },
+            &[],
+        ),
+        true,
+    );
+    let works = studio
+        .media
+        .and_then(|media| media.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter_map(|media| {
+            let title = media.title?.romaji?;
+            let mut line = link(&title, media.site_url.as_deref());
+            match (media.format, media.start_date.and_then(|date| date.year)) {
+                (Some(format), Some(year)) => line.push_str(&format!(" ({:?}, {})", format, year)),
+                (Some(format), None) => line.push_str(&format!(" ({:?})", format)),
+                (None, Some(year)) => line.push_str(&format!(" ({})", year)),
+                (None, None) => {}
+            }
+            Some(line)
+        })
+        .collect::<Vec<_>>();
+    if !works.is_empty() {
+        e = e.field(
+            tr(locale, "weeb-notable-works", &[]),
+            field_list(works),
+            false,
+        );
+    }
+    e.timestamp(Utc::now())
+        .footer(tr(locale, "anilist-footer", &[]))
+        .footer_icon(ANILIST_ICON)
 }
 
-async fn get_vtuber_article_text(
-    title: String,
-) -> Result<ParseDetails, Box<dyn std::error::Error + Send + Sync>> {
-    let client = ReqwestClient::new();
-    client
-        .get(VIRTUALYOUTUBER_WIKI_API)
-        .query(&[
-            ("action", "parse"),
-            ("page", &title),
-            ("format", "json"),
-            ("prop", "text"),
-        ])
-        .send()
-        .await?
-        .json()
-        .await
-        .map_err(|_| Box::try_from(format!("Unable to get article {}", &title)).unwrap())
+/// Joins a name and its native spelling the same way media titles are shown.
+fn display_name(locale: &str, full: Option<&str>, native: Option<&str>) -> String {
+    match (full, native) {
+        (Some(full), Some(native)) => format!("{} | {}", full, native),
+        (Some(name), None) | (None, Some(name)) => name.to_owned(),
+        (None, None) => tr(locale, "name-unavailable", &[]),
+    }
 }
 
-async fn get_vtuber_article_image(
-    title: String,
-) -> Result<ArticleImage, Box<dyn std::error::Error + Send + Sync>> {
-    let client = ReqwestClient::new();
-    client
-        .get(VIRTUALYOUTUBER_WIKI_API)
-        .query(&[
-            ("action", "imageserving"),
-            ("wisTitle", &title),
-            ("format", "json"),
-        ])
-        .send()
-        .await?
-        .json()
-        .await
-        .map_err(|_| Box::try_from(format!("Unable to get image for article {}", &title)).unwrap())
+/// A markdown link to `url` if there is one, escaping brackets in `text` and parentheses in `url`
+/// so neither ends the link early.
+pub(crate) fn link(text: &str, url: Option<&str>) -> String {
+    let text = markdown::escape(text);
+    match url {
+        Some(url) => format!(
+            "[{}]({})",
+            text,
+            url.replace('(', "%28").replace(')', "%29")
+        ),
+        None => text,
+    }
+}
+
+/// Puts one entry per line, leaving out whatever doesn't fit into an embed field.
+pub(crate) fn field_list(entries: Vec<String>) -> String {
+    let mut list = String::new();
+    for entry in entries {
+        if list.chars().count() + entry.chars().count() + 1 > FIELD_VALUE_LIMIT {
+            break;
+        }
+        if !list.is_empty() {
+            list.push('\n');
+        }
+        list.push_str(&entry);
+    }
+    list
 }
 
-fn format_desc(desc: String) -> String {
-    desc.replace("<br>", "\n")
-        .replace("<br/>", "\n")
-        .replace("<br />", "\n")
-        .replace("</br>", "\n")
-        .replace("</ br>", "\n")
-        .replace("<i>", "*")
-        .replace("</i>", "*")
-        .replace("<b>", "**")
-        .replace("</b>", "**")
-        .replace("&rsquo;", "'")
-        .replace("&hellip;", "…")
+/// The AniList endpoint, website and embed colour from the current configuration.
+pub(crate) async fn anilist_config(context: &Context<'_>) -> (Url, Url, Colour) {
+    let config = context.data().config.read().await;
+    (
+        config.api.anilist.clone(),
+        config.api.anilist_site.clone(),
+        config.colours.anilist,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn title(
+        romaji: Option<&str>,
+        english: Option<&str>,
+        native: Option<&str>,
+    ) -> media_title_query::MediaTitleQueryPageMediaTitle {
+        media_title_query::MediaTitleQueryPageMediaTitle {
+            romaji: romaji.map(str::to_owned),
+            english: english.map(str::to_owned),
+            native: native.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn choice_names_join_distinct_titles() {
+        assert_eq!(
+            choice_name(title(
+                Some("Overlord"),
+                Some("Overlord"),
+                Some("オーバーロード")
+            ))
+            .as_deref(),
+            Some("Overlord | オーバーロード")
+        );
+        assert_eq!(
+            choice_name(title(None, Some("Overlord"), None)).as_deref(),
+            Some("Overlord")
+        );
+        assert_eq!(choice_name(title(None, None, None)), None);
+    }
+
+    #[test]
+    fn choice_names_fit_discord() {
+        let long = "a".repeat(80);
+        let name = choice_name(title(Some(&long), Some(&"b".repeat(80)), None)).unwrap();
+        assert_eq!(name.chars().count(), 100);
+        assert!(name.starts_with(&long) && name.ends_with('…'));
+    }
+
+    #[test]
+    fn picked_ids_round_trip() {
+        let value = format!("{}{}", ANILIST_ID_PREFIX, 20832);
+        assert_eq!(id_or_title(value), (Some(20832), None));
+        assert_eq!(
+            id_or_title("Overlord".to_owned()),
+            (None, Some("Overlord".to_owned()))
+        );
+        // Typed text that only looks like a picked ID is searched for.
+        assert_eq!(
+            id_or_title("id:Overlord".to_owned()),
+            (None, Some("id:Overlord".to_owned()))
+        );
+    }
+
+    #[test]
+    fn links_escape_brackets_and_parentheses() {
+        assert_eq!(
+            link("Re:Zero [Director's Cut]", Some("https://a.b/Re_(Zero)")),
+            "[Re:Zero \\[Director's Cut\\]](https://a.b/Re_%28Zero%29)"
+        );
+        assert_eq!(link("a_b", None), "a\\_b");
+    }
+
+    #[test]
+    fn character_embeds_group_voice_actors_by_language() {
+        let actor = |name: &str, language: &str| {
+            json!({
+                "siteUrl": format!("https://anilist.co/staff/{}", name),
+                "languageV2": language,
+                "name": { "full": name },
+            })
+        };
+        let character: character_query::CharacterQueryPageCharacters =
+            serde_json::from_value(json!({
+                "id": 1,
+                "name": {
+                    "full": "Lupusregina Beta",
+                    "native": "ルプスレギナ・ベータ",
+                    "alternative": ["Lupu", ""],
+                },
+                "image": { "large": "https://s4.anilist.co/lupusregina.png" },
+                "description": "<p>A <b>battle maid</b>.</p>",
+                "siteUrl": "https://anilist.co/character/1",
+                "favourites": 500,
+                "gender": "Female",
+                "age": null,
+                "dateOfBirth": { "year": null, "month": 5, "day": 8 },
+                "media": {
+                    "edges": [
+                        {
+                            "characterRole": "SUPPORTING",
+                            "node": {
+                                "type": "ANIME",
+                                "siteUrl": "https://anilist.co/anime/20832",
+                                "title": { "romaji": "Overlord" },
+                            },
+                            "voiceActors": [actor("Ai", "Japanese"), actor("Bea", "English")],
+                        },
+                        {
+                            "characterRole": "SUPPORTING",
+                            "node": {
+                                "type": "ANIME",
+                                "siteUrl": "https://anilist.co/anime/21383",
+                                "title": { "romaji": "Overlord II" },
+                            },
+                            "voiceActors": [actor("Ai", "Japanese")],
+                        },
+                    ],
+                },
+            }))
+            .unwrap();
+
+        let embed = character_embed("en-US", character, Colour::BLUE);
+
+        assert_eq!(
+            embed.title.as_deref(),
+            Some("Lupusregina Beta | ルプスレギナ・ベータ")
+        );
+        assert_eq!(embed.description.as_deref(), Some("A **battle maid**."));
+        assert_eq!(embed.field_value("Gender"), Some("Female"));
+        assert_eq!(embed.field_value("Age"), None);
+        assert_eq!(embed.field_value("Birthday"), Some("May 8"));
+        assert_eq!(embed.field_value("Also Known As"), Some("Lupu"));
+        assert_eq!(
+            embed.field_value("Appears In"),
+            Some(
+                "[Overlord](https://anilist.co/anime/20832) (SUPPORTING)\n\
+                 [Overlord II](https://anilist.co/anime/21383) (SUPPORTING)"
+            )
+        );
+        assert_eq!(
+            embed.field_value("Voice Actors (Japanese)"),
+            Some("[Ai](https://anilist.co/staff/Ai)")
+        );
+        assert_eq!(
+            embed.field_value("Voice Actors (English)"),
+            Some("[Bea](https://anilist.co/staff/Bea)")
+        );
+    }
+
+    #[test]
+    fn staff_embeds_list_works_and_characters() {
+        let staff: staff_query::StaffQueryPageStaff = serde_json::from_value(json!({
+            "id": 1,
+            "name": { "full": "Satoshi Hino", "native": null },
+            "image": null,
+            "description": null,
+            "siteUrl": "https://anilist.co/staff/1",
+            "favourites": 1000,
+            "languageV2": "Japanese",
+            "primaryOccupations": ["Voice Actor", "Singer"],
+            "homeTown": "Tokyo",
+            "dateOfBirth": { "year": 1978, "month": 8, "day": 4 },
+            "staffMedia": {
+                "edges": [{
+                    "staffRole": "Theme Song Performance",
+                    "node": {
+                        "siteUrl": "https://anilist.co/anime/20832",
+                        "title": { "romaji": "Overlord" },
+                    },
+                }],
+            },
+            "characters": {
+                "nodes": [{
+                    "siteUrl": "https://anilist.co/character/2",
+                    "name": { "full": "Ainz Ooal Gown" },
+                }],
+            },
+        }))
+        .unwrap();
+
+        let embed = staff_embed("en-US", staff, Colour::BLUE);
+
+        assert_eq!(embed.title.as_deref(), Some("Satoshi Hino"));
+        assert_eq!(
+            embed.description.as_deref(),
+            Some("No description available.")
+        );
+        assert_eq!(embed.field_value("Birthday"), Some("August 4, 1978"));
+        assert_eq!(embed.field_value("Hometown"), Some("Tokyo"));
+        assert_eq!(
+            embed.field_value("Occupations"),
+            Some("Voice Actor, Singer")
+        );
+        assert_eq!(
+            embed.field_value("Notable Works"),
+            Some("[Overlord](https://anilist.co/anime/20832) (Theme Song Performance)")
+        );
+        assert_eq!(
+            embed.field_value("Notable Characters"),
+            Some("[Ainz Ooal Gown](https://anilist.co/character/2)")
+        );
+    }
+
+    #[test]
+    fn studio_embeds_show_type_and_works() {
+        let studio: studio_query::StudioQueryPageStudios = serde_json::from_value(json!({
+            "id": 1,
+            "name": "MADHOUSE",
+            "siteUrl": "https://anilist.co/studio/11",
+            "favourites": null,
+            "isAnimationStudio": true,
+            "media": {
+                "nodes": [
+                    {
+                        "siteUrl": "https://anilist.co/anime/20832",
+                        "format": "TV",
+                        "title": { "romaji": "Overlord" },
+                        "startDate": { "year": 2015 },
+                    },
+                    {
+                        "siteUrl": null,
+                        "format": null,
+                        "title": { "romaji": "Untitled" },
+                        "startDate": { "year": null },
+                    },
+                ],
+            },
+        }))
+        .unwrap();
+
+        let embed = studio_embed("en-US", studio, Colour::BLUE);
+
+        assert_eq!(embed.title.as_deref(), Some("MADHOUSE"));
+        assert_eq!(embed.field_value("Favourites"), None);
+        assert_eq!(embed.field_value("Type"), Some("Animation Studio"));
+        assert_eq!(
+            embed.field_value("Notable Works"),
+            Some("[Overlord](https://anilist.co/anime/20832) (TV, 2015)\nUntitled")
+        );
+    }
+
+    #[tokio::test]
+    async fn only_the_latest_keystroke_is_looked_up() {
+        let autocomplete = Arc::new(TitleAutocomplete::default());
+        let first = tokio::spawn({
+            let autocomplete = autocomplete.clone();
+            async move { autocomplete.debounce(UserId(1), 1).await }
+        });
+        tokio::time::sleep(AUTOCOMPLETE_DEBOUNCE / 3).await;
+        let (second, other_user) = tokio::join!(
+            autocomplete.debounce(UserId(1), 2),
+            autocomplete.debounce(UserId(2), 3)
+        );
+
+        assert!(!first.await.unwrap());
+        assert!(second);
+        assert!(other_user);
+        assert!(autocomplete.latest.lock().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,572 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use poise::serenity_prelude::Colour;
+use poise::{AutocompleteChoice, ReplyHandle};
+use reqwest::Url;
+
+use crate::commands::settings::{log_change, reply_locale};
+use crate::db::wikis::{self, Wiki};
+use crate::i18n::tr;
+use crate::mediawiki::{self, Extraction};
+use crate::pagination::{browse, choose};
+use crate::reply::{Embed, Reply};
+use crate::{Context, Error};
+
+/// Name of the wiki every server can use, the configured VTuber wiki.
+const BUILT_IN: &str = "vtuber";
+/// Site name of the built-in wiki.
+const BUILT_IN_SITE_NAME: &str = "Virtual YouTuber Wiki";
+/// How many wikis a single guild may register.
+const MAX_WIKIS: usize = 25;
+
+#[poise::command(
+    slash_command,
+    category = "general",
+    description_localized("en-US", "Shows an article from one of this server's wikis")
+)]
+pub async fn wiki(
+    context: Context<'_>,
+    #[description = "Wiki to search"]
+    #[autocomplete = "autocomplete_site"]
+    site: String,
+    #[description = "Article to search for"] query: String,
+) -> Result<(), Error> {
+    let wiki = match find_wiki(context, &site).await? {
+        Some(wiki) => wiki,
+        None => {
+            let locale = reply_locale(context).await;
+            let message = tr(&locale, "wiki-unknown", &[("name", site.into())]);
+            reply(context, None, message).await?;
+            return Ok(());
+        }
+    };
+    let picked = match pick(context, &wiki.api, &query, "articles").await? {
+        Some(picked) => picked,
+        None => return Ok(()),
+    };
+    let embed = mediawiki::article_embed(
+        &context.data().http,
+        &wiki.api,
+        &picked.title,
+        &picked.url,
+        &wiki.extraction,
+    )
+    .await?
+    .footer(wiki.site_name);
+    picked.show(context, embed).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "general",
+    subcommands("add", "remove", "list"),
+    description_localized("en-US", "Manages the wikis available to /wiki in this server")
+)]
+pub async fn wikis(_context: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "general",
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Adds a MediaWiki site to /wiki, or changes one")
+)]
+pub async fn add(
+    context: Context<'_>,
+    #[description = "Short name to pick the wiki by, such as overlord"] name: String,
+    #[description = "The wiki's api.php URL, or its main page"] url: String,
+    #[description = "HTML after which descriptions start, by default after the infobox"]
+    start: Option<String>,
+    #[description = "HTML before which descriptions end, by default the first heading"] end: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let name = name.trim().to_lowercase();
+    let locale = reply_locale(context).await;
+    if let Err(reason) = validate_name(&name) {
+        reply(context, None, tr(&locale, reason, &[])).await?;
+        return Ok(());
+    }
+    let api = match api_url(&url) {
+        Ok(api) => api,
+        Err(reason) => {
+            reply(context, None, tr(&locale, reason, &[])).await?;
+            return Ok(());
+        }
+    };
+    if !resolves_publicly(&api).await {
+        reply(context, None, tr(&locale, "wiki-url-not-public", &[])).await?;
+        return Ok(());
+    }
+    let existing = context
+        .data()
+        .db
+        .run(move |connection| wikis::for_guild(connection, guild_id))
+        .await?;
+    if existing.len() >= MAX_WIKIS && !existing.iter().any(|wiki| wiki.name == name) {
+        let message = tr(&locale, "wiki-too-many", &[("max", MAX_WIKIS.into())]);
+        reply(context, None, message).await?;
+        return Ok(());
+    }
+    context.defer_ephemeral().await?;
+    let site_name = match mediawiki::site_name(&context.data().http, &api).await {
+        Ok(site_name) => site_name,
+        Err(e) => {
+            let message = tr(
+                &locale,
+                "wiki-unusable",
+                &[
+                    ("url", api.as_str().into()),
+                    ("error", e.to_string().into()),
+                ],
+            );
+            reply(context, None, message).await?;
+            return Ok(());
+        }
+    };
+    let non_empty = |marker: Option<String>| marker.filter(|m| !m.trim().is_empty());
+    let wiki = Wiki {
+        guild_id,
+        name,
+        site_name,
+        api,
+        extraction: Extraction {
+            start: non_empty(start),
+            end: non_empty(end),
+        },
+    };
+    let message = tr(
+        &locale,
+        "wiki-added",
+        &[
+            ("name", wiki.name.as_str().into()),
+            ("site", wiki.site_name.as_str().into()),
+            ("url", wiki.api.as_str().into()),
+        ],
+    );
+    context
+        .data()
+        .db
+        .run(move |connection| wikis::save(connection, &wiki))
+        .await?;
+    let settings = context.data().guild_settings(guild_id).await;
+    log_change(context, guild_id, &settings, &message).await;
+    reply(context, None, message).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "general",
+    required_permissions = "MANAGE_GUILD",
+    description_localized("en-US", "Removes a wiki from /wiki")
+)]
+pub async fn remove(
+    context: Context<'_>,
+    #[description = "Name of the wiki"]
+    #[autocomplete = "autocomplete_site"]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let name = name.trim().to_lowercase();
+    let removed_name = name.clone();
+    let removed = context
+        .data()
+        .db
+        .run(move |connection| wikis::remove(connection, guild_id, &removed_name))
+        .await?;
+    let locale = reply_locale(context).await;
+    if !removed {
+        let message = tr(&locale, "wiki-not-found", &[("name", name.into())]);
+        reply(context, None, message).await?;
+        return Ok(());
+    }
+    let message = tr(&locale, "wiki-removed", &[("name", name.into())]);
+    let settings = context.data().guild_settings(guild_id).await;
+    log_change(context, guild_id, &settings, &message).await;
+    reply(context, None, message).await
+}
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "general",
+    description_localized("en-US", "Lists the wikis available to /wiki")
+)]
+pub async fn list(context: Context<'_>) -> Result<(), Error> {
+    let guild_id = context
+        .guild_id()
+        .ok_or("Failed to get GuildID from Message.")?;
+    let wikis = context
+        .data()
+        .db
+        .run(move |connection| wikis::for_guild(connection, guild_id))
+        .await?;
+    let built_in = context.data().config.read().await.api.vtuber_wiki.clone();
+    let mut lines = vec![format!(
+        "`{}` {} ({})",
+        BUILT_IN, BUILT_IN_SITE_NAME, built_in
+    )];
+    lines.extend(
+        wikis
+            .iter()
+            .map(|wiki| format!("`{}` {} ({})", wiki.name, wiki.site_name, wiki.api)),
+    );
+    let locale = reply_locale(context).await;
+    let embed = Embed::new()
+        .colour(Colour::BLURPLE)
+        .title(tr(&locale, "wiki-list-title", &[]))
+        .description(lines.join("\n"));
+    Reply::embed(embed).ephemeral().send(context).await?;
+    Ok(())
+}
+
+/// The built-in wiki or one registered in the current guild.
+async fn find_wiki(context: Context<'_>, name: &str) -> Result<Option<Wiki>, Error> {
+    let name = name.trim().to_lowercase();
+    if name == BUILT_IN {
+        let api = context.data().config.read().await.api.vtuber_wiki.clone();
+        return Ok(Some(Wiki {
+            guild_id: context.guild_id().unwrap_or_default(),
+            name,
+            site_name: BUILT_IN_SITE_NAME.to_owned(),
+            api,
+            extraction: Extraction::default(),
+        }));
+    }
+    let guild_id = match context.guild_id() {
+        Some(guild_id) => guild_id,
+        None => return Ok(None),
+    };
+    Ok(context
+        .data()
+        .db
+        .run(move |connection| wikis::get(connection, guild_id, &name))
+        .await?)
+}
+
+async fn autocomplete_site(context: Context<'_>, partial: &str) -> Vec<AutocompleteChoice<String>> {
+    let mut wikis = vec![(BUILT_IN.to_owned(), BUILT_IN_SITE_NAME.to_owned())];
+    if let Some(guild_id) = context.guild_id() {
+        let registered = context
+            .data()
+            .db
+            .run(move |connection| wikis::for_guild(connection, guild_id))
+            .await
+            .unwrap_or_default();
+        wikis.extend(
+            registered
+                .into_iter()
+                .map(|wiki| (wiki.name, wiki.site_name)),
+        );
+    }
+    let partial = partial.to_lowercase();
+    wikis
+        .into_iter()
+        .filter(|(name, site_name)| {
+            name.contains(&partial) || site_name.to_lowercase().contains(&partial)
+        })
+        .take(25)
+        .map(|(name, site_name)| AutocompleteChoice {
+            name: format!("{} ({})", name, site_name)
+                .chars()
+                .take(100)
+                .collect(),
+            value: name,
+        })
+        .collect()
+}
+
+/// Checks a name for a wiki, failing with the message explaining what is wrong with it.
+fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name == BUILT_IN {
+        Err("wiki-name-taken")
+    } else if name.is_empty() || name.chars().count() > 32 {
+        Err("wiki-name-length")
+    } else if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        Err("wiki-name-characters")
+    } else {
+        Ok(())
+    }
+}
+
+/// The `api.php` URL for a URL given by a guild admin.
+///
+/// Only public HTTPS sites are accepted, so the bot can't be pointed at its own network. Fails
+/// with the message explaining why a URL isn't.
+fn api_url(url: &str) -> Result<Url, &'static str> {
+    let mut url = Url::parse(url.trim()).map_err(|_| "wiki-url-invalid")?;
+    if url.scheme() != "https" {
+        return Err("wiki-url-not-https");
+    }
+    let public = url.host_str().is_some_and(|host| {
+        host.contains('.')
+            && !host.ends_with(".local")
+            && host
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse::<IpAddr>()
+                .is_err()
+    });
+    if !public {
+        return Err("wiki-url-not-public");
+    }
+    if !url.path().ends_with("api.php") {
+        let path = format!("{}/api.php", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+    }
+    url.set_query(None);
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Whether every address the host of `url` resolves to is public, so a wiki can't be used to
+/// reach the bot's own network by a name that resolves to it.
+async fn resolves_publicly(url: &Url) -> bool {
+    let (host, port) = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => (host, port),
+        _ => return false,
+    };
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(addresses) => {
+            let addresses: Vec<_> = addresses.collect();
+            !addresses.is_empty() && addresses.iter().all(|address| is_public(address.ip()))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether `ip` is reachable over the internet, rather than loopback, private, link-local or
+/// otherwise reserved.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // Shared address space used by carrier-grade NAT.
+        || (a == 100 && (64..128).contains(&b))
+        // Reserved for future use.
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local addresses.
+        || (first & 0xfe00) == 0xfc00
+        // Link-local addresses.
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// A wiki article picked from the search results.
+pub(crate) struct Picked<'a> {
+    pub title: String,
+    pub url: String,
+    /// The message the article was picked in, if there were several matches.
+    pub prompt: Option<ReplyHandle<'a>>,
+}
+
+impl Picked<'_> {
+    /// Shows `embed` in the prompt, or in a new reply if nothing had to be picked or it doesn't
+    /// fit on a single page.
+    pub async fn show(self, context: Context<'_>, embed: Embed) -> Result<(), Error> {
+        let mut pages = embed.pages();
+        match self.prompt {
+            Some(prompt) if pages.len() == 1 => {
+                let page = pages.remove(0).into();
+                prompt
+                    .edit(context, |m| {
+                        m.content("").embed(|e| {
+                            *e = page;
+                            e
+                        })
+                    })
+                    .await?;
+            }
+            _ => {
+                browse(context, pages.into_iter().map(Into::into).collect()).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the article on the wiki at `api` that `query` refers to, letting the user choose if
+/// several match. `kind`, either `articles` or `vtubers`, picks the wording of the replies.
+///
+/// Defers the response unless the caller already has. Replies and returns `None` if nothing
+/// matches or nothing was chosen.
+pub(crate) async fn pick<'a>(
+    context: Context<'a>,
+    api: &Url,
+    query: &str,
+    kind: &str,
+) -> Result<Option<Picked<'a>>, Error> {
+    context.defer().await?;
+    let mut matches = mediawiki::search(&context.data().http, api, query).await?;
+    let locale = reply_locale(context).await;
+    if matches.is_empty() {
+        let message = tr(
+            &locale,
+            "wiki-no-matches",
+            &[("kind", kind.into()), ("query", query.into())],
+        );
+        reply(context, None, message).await?;
+        return Ok(None);
+    }
+    // Autocompleted names are exact titles, as are searches that happen to match one.
+    let exact = matches
+        .iter()
+        .position(|(title, _)| title.eq_ignore_ascii_case(query.trim()));
+    if let Some(index) = exact.or_else(|| (matches.len() == 1).then_some(0)) {
+        let (title, url) = matches.swap_remove(index);
+        return Ok(Some(Picked {
+            title,
+            url,
+            prompt: None,
+        }));
+    }
+
+    let titles = matches
+        .iter()
+        .map(|(title, _)| title.clone())
+        .collect::<Vec<_>>();
+    let prompt = tr(
+        &locale,
+        "wiki-several-matches",
+        &[("kind", kind.into()), ("query", query.into())],
+    );
+    Ok(choose(context, &prompt, &titles)
+        .await?
+        .map(|(index, prompt)| {
+            let (title, url) = matches.swap_remove(index);
+            Picked {
+                title,
+                url,
+                prompt: Some(prompt),
+            }
+        }))
+}
+
+/// Answers privately, or in the prompt an article was picked in.
+pub(crate) async fn reply(
+    context: Context<'_>,
+    prompt: Option<ReplyHandle<'_>>,
+    message: String,
+) -> Result<(), Error> {
+    match prompt {
+        Some(prompt) => {
+            prompt.edit(context, |m| m.content(message)).await?;
+        }
+        None => {
+            context.send(|m| m.content(message).ephemeral(true)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_url_points_at_api_php() {
+        assert_eq!(
+            api_url("https://overlord.fandom.com/").unwrap().as_str(),
+            "https://overlord.fandom.com/api.php"
+        );
+        assert_eq!(
+            api_url("https://en.wikipedia.org/w/api.php?action=query")
+                .unwrap()
+                .as_str(),
+            "https://en.wikipedia.org/w/api.php"
+        );
+    }
+
+    #[test]
+    fn api_url_rejects_private_hosts() {
+        assert!(api_url("http://overlord.fandom.com").is_err());
+        assert!(api_url("https://localhost/api.php").is_err());
+        assert!(api_url("https://127.0.0.1/api.php").is_err());
+        assert!(api_url("https://[::1]/api.php").is_err());
+        assert!(api_url("https://printer.local/api.php").is_err());
+    }
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        assert!(is_public("151.101.0.1".parse().unwrap()));
+        assert!(is_public("2606:4700::1".parse().unwrap()));
+        for private in &[
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(!is_public(private.parse().unwrap()), "{}", private);
+        }
+    }
+
+    #[tokio::test]
+    async fn names_resolving_to_private_addresses_are_rejected() {
+        let url = Url::parse("https://localhost/api.php").unwrap();
+        assert!(!resolves_publicly(&url).await);
+    }
+
+    #[test]
+    fn names_are_validated() {
+        assert!(validate_name("overlord").is_ok());
+        assert!(validate_name("vtuber").is_err());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("two words").is_err());
+    }
+}
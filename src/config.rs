@@ -0,0 +1,671 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use ini::Ini;
+use poise::serenity_prelude::{Colour, UserId};
+use poise::CooldownConfig;
+use reqwest::Url;
+use tracing_subscriber::filter::Targets;
+
+use crate::http::longest_request;
+
+/// Environment variables starting with this prefix override keys in `settings.ini`.
+///
+/// `LUPUSREGINA_GENERAL_PREFIX=!` overrides `prefix` in the `[general]` section. A double
+/// underscore in the key part stands in for a dot, so `LUPUSREGINA_COOLDOWNS_ANIME__USER`
+/// overrides `anime.user` in `[cooldowns]`.
+pub const ENV_PREFIX: &str = "LUPUSREGINA_";
+
+/// Most retries `http.retries` may ask for, past that the backoff delay is longer than a command
+/// can wait anyway.
+pub const MAX_RETRIES: u32 = 5;
+
+/// Longest a request may take with every retry, timeout and backoff delay. Commands defer and then
+/// have 15 minutes to reply, this leaves room for the few requests a command makes in that time.
+pub const MAX_REQUEST_TIME: Duration = Duration::from_secs(180);
+
+/// Bot configuration parsed from `settings.ini`.
+///
+/// Every key is optional; anything missing falls back to [`Config::default`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub general: General,
+    pub cooldowns: Cooldowns,
+    pub api: Api,
+    pub http: Http,
+    pub cache: Cache,
+    pub colours: Colours,
+    pub logging: Logging,
+}
+
+/// The `[general]` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct General {
+    /// Prefix for prefix commands, unless a guild sets its own.
+    pub prefix: String,
+    /// Prefixes accepted in every guild, in addition to the guild's prefix.
+    pub additional_prefixes: Vec<String>,
+    /// Users allowed to run owner commands in addition to the application owners.
+    pub owners: HashSet<UserId>,
+}
+
+/// The `[cooldowns]` section.
+///
+/// Keys are command names, optionally followed by a bucket: `anime = 1` sets the global cooldown
+/// of `anime` to one second, `anime.user = 5` sets its per-user cooldown. Valid buckets are
+/// `global`, `user`, `guild`, `channel` and `member`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cooldowns {
+    pub commands: HashMap<String, CooldownConfig>,
+}
+
+/// The `[api]` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Api {
+    /// AniList GraphQL endpoint.
+    pub anilist: Url,
+    /// AniList website, used to link to media pages.
+    pub anilist_site: Url,
+    /// MediaWiki API endpoint of the Virtual YouTuber wiki.
+    pub vtuber_wiki: Url,
+}
+
+/// The `[http]` section, for requests to AniList and the wiki.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Http {
+    /// How long a single request may take.
+    pub timeout: Duration,
+    /// `User-Agent` header sent with every request.
+    pub user_agent: String,
+    /// How often a request failing with a server error is retried.
+    pub retries: u32,
+}
+
+/// The `[cache]` section, for responses from AniList and the wiki.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cache {
+    /// How long responses are reused, zero turns the cache off.
+    pub ttl: Duration,
+    /// Most responses kept at once.
+    pub size: usize,
+    /// Whether the cache is saved in the project cache directory and loaded on startup.
+    pub persist: bool,
+}
+
+/// The `[colours]` section, as `#RRGGBB` hex values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Colours {
+    pub anilist: Colour,
+    pub about: Colour,
+    pub info: Colour,
+    /// VTuber birthday and debut anniversary announcements.
+    pub vtuber: Colour,
+}
+
+/// The `[logging]` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Logging {
+    /// Filter directives in `RUST_LOG` syntax, e.g. `info,serenity=warn`.
+    ///
+    /// Ignored when `RUST_LOG` is set.
+    pub level: String,
+}
+
+impl Logging {
+    pub fn filter(&self) -> Targets {
+        let level = env::var("RUST_LOG").unwrap_or_else(|_| self.level.clone());
+        // Both sources are validated when the configuration is loaded.
+        Targets::from_str(&level).unwrap_or_default()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut commands = HashMap::new();
+        for command in [
+            "anime",
+            "manga",
+            "character",
+            "staff",
+            "studio",
+            "season",
+            "vtuber",
+            "wiki",
+        ] {
+            commands.insert(
+                command.to_string(),
+                CooldownConfig {
+                    global: Some(Duration::from_secs(1)),
+                    ..Default::default()
+                },
+            );
+        }
+        Config {
+            general: General {
+                prefix: "~".to_string(),
+                additional_prefixes: Vec::new(),
+                owners: HashSet::new(),
+            },
+            cooldowns: Cooldowns { commands },
+            api: Api {
+                anilist: Url::parse("https://graphql.anilist.co").unwrap(),
+                anilist_site: Url::parse("https://anilist.co").unwrap(),
+                vtuber_wiki: Url::parse("https://virtualyoutuber.fandom.com/api.php").unwrap(),
+            },
+            http: Http {
+                timeout: Duration::from_secs(10),
+                // Header values have to be ASCII, so the package name can't be used here.
+                user_agent: format!(
+                    "Lupusregina/{} (+https://github.com/flat/Lupusregina-)",
+                    env!("CARGO_PKG_VERSION")
+                ),
+                retries: 2,
+            },
+            cache: Cache {
+                ttl: Duration::from_secs(600),
+                size: 1000,
+                persist: false,
+            },
+            colours: Colours {
+                anilist: Colour::BLUE,
+                about: Colour::new(0x00D2_5148),
+                info: Colour::FABLED_PINK,
+                vtuber: Colour::new(0x00EC_407A),
+            },
+            logging: Logging {
+                level: "info".to_string(),
+            },
+        }
+    }
+}
+
+/// A key in `settings.ini` that could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The offending key as `section.key`.
+    pub key: String,
+    pub reason: String,
+}
+
+impl ConfigError {
+    fn new(section: &str, key: &str, reason: impl Into<String>) -> Self {
+        ConfigError {
+            key: format!("{}.{}", section, key),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid configuration key `{}`: {}",
+            self.key, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Builds a configuration from a parsed ini file, on top of the defaults.
+    pub fn from_ini(ini: &Ini) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (section, properties) in ini.iter() {
+            let section = match section {
+                Some(section) => section,
+                None => match properties.iter().next() {
+                    Some((key, _)) => {
+                        return Err(ConfigError::new(
+                            "",
+                            key,
+                            "keys must be placed inside a section",
+                        ))
+                    }
+                    None => continue,
+                },
+            };
+            for (key, value) in properties.iter() {
+                let value = value.trim();
+                let error = |reason: String| ConfigError::new(section, key, reason);
+                match (section, key) {
+                    ("general", "prefix") => {
+                        if value.is_empty() || value.contains(char::is_whitespace) {
+                            return Err(error(
+                                "the prefix must be non-empty and contain no whitespace".into(),
+                            ));
+                        }
+                        config.general.prefix = value.to_string();
+                    }
+                    ("general", "additional_prefixes") => {
+                        config.general.additional_prefixes = split_list(value)
+                            .map(|prefix| {
+                                if prefix.contains(char::is_whitespace) {
+                                    Err(error(format!("`{}` contains whitespace", prefix)))
+                                } else {
+                                    Ok(prefix.to_string())
+                                }
+                            })
+                            .collect::<Result<_, _>>()?;
+                    }
+                    ("general", "owners") => {
+                        config.general.owners = split_list(value)
+                            .map(|id| {
+                                id.parse::<u64>()
+                                    .map(UserId)
+                                    .map_err(|_| error(format!("`{}` is not a valid user ID", id)))
+                            })
+                            .collect::<Result<_, _>>()?;
+                    }
+                    ("cooldowns", key) => {
+                        let (command, bucket) = key.split_once('.').unwrap_or((key, "global"));
+                        let seconds = value.parse::<u64>().map_err(|_| {
+                            error(format!("`{}` is not a whole number of seconds", value))
+                        })?;
+                        let duration = Some(Duration::from_secs(seconds)).filter(|d| !d.is_zero());
+                        let cooldown = config
+                            .cooldowns
+                            .commands
+                            .entry(command.to_string())
+                            .or_default();
+                        match bucket {
+                            "global" => cooldown.global = duration,
+                            "user" => cooldown.user = duration,
+                            "guild" => cooldown.guild = duration,
+                            "channel" => cooldown.channel = duration,
+                            "member" => cooldown.member = duration,
+                            _ => {
+                                return Err(error(format!("unknown cooldown bucket `{}`", bucket)))
+                            }
+                        }
+                    }
+                    ("api", "anilist") => config.api.anilist = parse_url(value).map_err(error)?,
+                    ("api", "anilist_site") => {
+                        config.api.anilist_site = parse_url(value).map_err(error)?
+                    }
+                    ("api", "vtuber_wiki") => {
+                        config.api.vtuber_wiki = parse_url(value).map_err(error)?
+                    }
+                    ("http", "timeout") => {
+                        let seconds =
+                            value
+                                .parse::<u64>()
+                                .ok()
+                                .filter(|s| *s > 0)
+                                .ok_or_else(|| {
+                                    error(format!(
+                                        "`{}` is not a positive number of seconds",
+                                        value
+                                    ))
+                                })?;
+                        config.http.timeout = Duration::from_secs(seconds);
+                    }
+                    ("http", "user_agent") => {
+                        if value.is_empty() {
+                            return Err(error("the user agent must not be empty".into()));
+                        }
+                        if !value.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+                            return Err(error("the user agent must be printable ASCII".into()));
+                        }
+                        config.http.user_agent = value.to_string();
+                    }
+                    ("http", "retries") => {
+                        config.http.retries = value
+                            .parse()
+                            .ok()
+                            .filter(|retries| *retries <= MAX_RETRIES)
+                            .ok_or_else(|| {
+                                error(format!(
+                                    "`{}` is not a whole number up to {}",
+                                    value, MAX_RETRIES
+                                ))
+                            })?;
+                    }
+                    ("cache", "ttl") => {
+                        let seconds = value.parse::<u64>().map_err(|_| {
+                            error(format!("`{}` is not a whole number of seconds", value))
+                        })?;
+                        config.cache.ttl = Duration::from_secs(seconds);
+                    }
+                    ("cache", "size") => {
+                        config.cache.size = value
+                            .parse()
+                            .map_err(|_| error(format!("`{}` is not a whole number", value)))?;
+                    }
+                    ("cache", "persist") => {
+                        config.cache.persist = value
+                            .parse()
+                            .map_err(|_| error(format!("`{}` is not true or false", value)))?;
+                    }
+                    ("colours", "anilist") => {
+                        config.colours.anilist = parse_colour(value).map_err(error)?
+                    }
+                    ("colours", "about") => {
+                        config.colours.about = parse_colour(value).map_err(error)?
+                    }
+                    ("colours", "info") => {
+                        config.colours.info = parse_colour(value).map_err(error)?
+                    }
+                    ("colours", "vtuber") => {
+                        config.colours.vtuber = parse_colour(value).map_err(error)?
+                    }
+                    ("logging", "level") => {
+                        Targets::from_str(value).map_err(|e| error(e.to_string()))?;
+                        config.logging.level = value.to_string();
+                    }
+                    _ => return Err(error("unknown key".into())),
+                }
+            }
+        }
+        if let Ok(level) = env::var("RUST_LOG") {
+            Targets::from_str(&level)
+                .map_err(|e| ConfigError::new("logging", "level", format!("RUST_LOG: {}", e)))?;
+        }
+        let longest = longest_request(config.http.timeout, config.http.retries);
+        if longest > MAX_REQUEST_TIME {
+            return Err(ConfigError::new(
+                "http",
+                "timeout",
+                format!(
+                    "with {} retries a request may take {}s, more than the {}s allowed",
+                    config.http.retries,
+                    longest.as_secs(),
+                    MAX_REQUEST_TIME.as_secs()
+                ),
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Every setting as `section.key` and a printable value.
+    pub fn entries(&self) -> BTreeMap<String, String> {
+        let mut entries = BTreeMap::new();
+        let mut owners = self
+            .general
+            .owners
+            .iter()
+            .map(|id| id.0)
+            .collect::<Vec<_>>();
+        owners.sort_unstable();
+        entries.insert("general.prefix".into(), self.general.prefix.clone());
+        entries.insert(
+            "general.additional_prefixes".into(),
+            self.general.additional_prefixes.join(", "),
+        );
+        entries.insert(
+            "general.owners".into(),
+            owners
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        for (command, cooldown) in &self.cooldowns.commands {
+            for (bucket, duration) in [
+                ("global", cooldown.global),
+                ("user", cooldown.user),
+                ("guild", cooldown.guild),
+                ("channel", cooldown.channel),
+                ("member", cooldown.member),
+            ] {
+                if let Some(duration) = duration {
+                    entries.insert(
+                        format!("cooldowns.{}.{}", command, bucket),
+                        duration.as_secs().to_string(),
+                    );
+                }
+            }
+        }
+        entries.insert("api.anilist".into(), self.api.anilist.to_string());
+        entries.insert("api.anilist_site".into(), self.api.anilist_site.to_string());
+        entries.insert("api.vtuber_wiki".into(), self.api.vtuber_wiki.to_string());
+        entries.insert(
+            "http.timeout".into(),
+            self.http.timeout.as_secs().to_string(),
+        );
+        entries.insert("http.user_agent".into(), self.http.user_agent.clone());
+        entries.insert("http.retries".into(), self.http.retries.to_string());
+        entries.insert("cache.ttl".into(), self.cache.ttl.as_secs().to_string());
+        entries.insert("cache.size".into(), self.cache.size.to_string());
+        entries.insert("cache.persist".into(), self.cache.persist.to_string());
+        for (key, colour) in [
+            ("colours.anilist", self.colours.anilist),
+            ("colours.about", self.colours.about),
+            ("colours.info", self.colours.info),
+            ("colours.vtuber", self.colours.vtuber),
+        ] {
+            entries.insert(key.into(), format!("#{:06X}", colour.0));
+        }
+        entries.insert("logging.level".into(), self.logging.level.clone());
+        entries
+    }
+
+    /// Describes every key whose value differs between `self` and `new`.
+    pub fn diff(&self, new: &Config) -> Vec<String> {
+        let old = self.entries();
+        let new = new.entries();
+        let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+        keys.into_iter()
+            .filter_map(|key| match (old.get(key), new.get(key)) {
+                (Some(old), Some(new)) if old == new => None,
+                (Some(old), Some(new)) => Some(format!("{}: `{}` -> `{}`", key, old, new)),
+                (Some(old), None) => Some(format!("{}: `{}` removed", key, old)),
+                (None, Some(new)) => Some(format!("{}: set to `{}`", key, new)),
+                (None, None) => None,
+            })
+            .collect()
+    }
+
+    /// Cooldowns for a command, or no cooldown if none are configured.
+    pub fn cooldown(&self, command: &str) -> CooldownConfig {
+        self.cooldowns
+            .commands
+            .get(command)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Applies `LUPUSREGINA_<SECTION>_<KEY>` environment variables on top of the ini file.
+///
+/// Variables that don't name a known key are skipped, see [`unknown_env_overrides`].
+pub fn apply_env_overrides(ini: &mut Ini) {
+    apply_overrides(ini, env::vars());
+}
+
+/// Names of the `LUPUSREGINA_` environment variables that don't name a known key.
+pub fn unknown_env_overrides() -> Vec<String> {
+    env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| {
+            matches!(override_key(name), Some((section, key)) if !is_known_key(&section, &key))
+        })
+        .collect()
+}
+
+fn apply_overrides(ini: &mut Ini, vars: impl Iterator<Item = (String, String)>) {
+    for (name, value) in vars {
+        if let Some((section, key)) = override_key(&name) {
+            if is_known_key(&section, &key) {
+                ini.with_section(Some(section)).set(key, value);
+            }
+        }
+    }
+}
+
+/// The section and key an environment variable overrides, if it has the prefix.
+fn override_key(name: &str) -> Option<(String, String)> {
+    let (section, key) = name.strip_prefix(ENV_PREFIX)?.split_once('_')?;
+    Some((
+        section.to_lowercase(),
+        key.to_lowercase().replace("__", "."),
+    ))
+}
+
+/// Whether `from_ini` accepts `key` in `section`. Any key is a command in `[cooldowns]`.
+fn is_known_key(section: &str, key: &str) -> bool {
+    matches!(
+        (section, key),
+        ("general", "prefix" | "additional_prefixes" | "owners")
+            | ("cooldowns", _)
+            | ("api", "anilist" | "anilist_site" | "vtuber_wiki")
+            | ("http", "timeout" | "user_agent" | "retries")
+            | ("cache", "ttl" | "size" | "persist")
+            | ("colours", "anilist" | "about" | "info" | "vtuber")
+            | ("logging", "level")
+    )
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn parse_url(value: &str) -> Result<Url, String> {
+    let url = Url::parse(value).map_err(|e| format!("`{}` is not a valid URL: {}", value, e))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!("unsupported URL scheme `{}`", scheme)),
+    }
+}
+
+fn parse_colour(value: &str) -> Result<Colour, String> {
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    match u32::from_str_radix(hex, 16) {
+        Ok(colour) if hex.len() == 6 => Ok(Colour::new(colour)),
+        _ => Err(format!("`{}` is not a colour in the form #RRGGBB", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(ini: &str) -> Result<Config, ConfigError> {
+        Config::from_ini(&Ini::load_from_str(ini).unwrap())
+    }
+
+    #[test]
+    fn retries_are_capped() {
+        let config = parse("[http]\nretries = 5").unwrap();
+        assert_eq!(config.http.retries, MAX_RETRIES);
+        let error = parse("[http]\nretries = 6").unwrap_err();
+        assert_eq!(error.key, "http.retries");
+    }
+
+    #[test]
+    fn requests_fit_in_the_followup_window() {
+        assert!(parse("[http]\ntimeout = 25\nretries = 5").is_ok());
+        let error = parse("[http]\ntimeout = 30\nretries = 5").unwrap_err();
+        assert_eq!(error.key, "http.timeout");
+        assert!(parse("[http]\ntimeout = 180\nretries = 0").is_ok());
+    }
+
+    #[test]
+    fn env_overrides_skip_unknown_keys() {
+        let mut ini = Ini::load_from_str("[general]\nprefix = ~").unwrap();
+        let vars = [
+            ("LUPUSREGINA_GENERAL_PREFIX", "!"),
+            ("LUPUSREGINA_COOLDOWNS_ANIME__USER", "5"),
+            ("LUPUSREGINA_GENERAL_NICKNAME", "Lupus"),
+            ("LUPUSREGINA_TOKEN", "secret"),
+            ("DISCORD_TOKEN", "secret"),
+        ];
+        apply_overrides(
+            &mut ini,
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        let config = Config::from_ini(&ini).unwrap();
+        assert_eq!(config.general.prefix, "!");
+        assert_eq!(config.cooldown("anime").user, Some(Duration::from_secs(5)));
+        assert_eq!(ini.section(Some("general")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn every_entry_is_a_known_key() {
+        for key in Config::default().entries().keys() {
+            let (section, key) = key.split_once('.').unwrap();
+            assert!(is_known_key(section, key), "{}.{}", section, key);
+        }
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_the_defaults() {
+        let config =
+            parse("[general]\nprefix = !\nowners = 1, 2\n[cooldowns]\nanime.user = 5").unwrap();
+        assert_eq!(config.general.prefix, "!");
+        assert_eq!(config.general.owners.len(), 2);
+        assert_eq!(config.cooldown("anime").user, Some(Duration::from_secs(5)));
+        assert_eq!(
+            config.cooldown("anime").global,
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(config.http, Config::default().http);
+    }
+
+    #[test]
+    fn errors_name_the_key() {
+        for (ini, key) in [
+            ("prefix = !", ".prefix"),
+            ("[general]\nprefix = a b", "general.prefix"),
+            ("[general]\nowners = 1, two", "general.owners"),
+            ("[cooldowns]\nanime.team = 5", "cooldowns.anime.team"),
+            ("[api]\nanilist = ftp://anilist.co", "api.anilist"),
+            ("[http]\ntimeout = 0", "http.timeout"),
+            ("[colours]\ninfo = pink", "colours.info"),
+            ("[logging]\nlevel = serenity=loud", "logging.level"),
+            ("[general]\nnickname = Lupus", "general.nickname"),
+        ] {
+            let error = parse(ini).unwrap_err();
+            assert_eq!(error.key, key, "{}", ini);
+            assert!(error.to_string().contains(key));
+        }
+    }
+
+    #[test]
+    fn colours_are_six_hex_digits() {
+        assert_eq!(parse_colour("#D25148"), Ok(Colour::new(0x00D2_5148)));
+        assert_eq!(parse_colour("0xd25148"), Ok(Colour::new(0x00D2_5148)));
+        assert_eq!(parse_colour("D25148"), Ok(Colour::new(0x00D2_5148)));
+        assert!(parse_colour("#FFF").is_err());
+        assert!(parse_colour("#GGGGGG").is_err());
+    }
+
+    #[test]
+    fn diff_lists_changed_keys() {
+        let old = Config::default();
+        assert!(old.diff(&old).is_empty());
+        let new = parse("[general]\nprefix = !\n[cooldowns]\nanime = 0\nwiki.user = 3").unwrap();
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                "cooldowns.anime.global: `1` removed",
+                "cooldowns.wiki.user: set to `3`",
+                "general.prefix: `~` -> `!`",
+            ]
+        );
+    }
+}
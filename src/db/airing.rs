@@ -0,0 +1,233 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+/// Where new episodes of a followed anime are announced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// Posted to a guild channel.
+    Channel(ChannelId),
+    /// Sent to a user by DM.
+    User(UserId),
+}
+
+impl Target {
+    fn kind(self) -> &'static str {
+        match self {
+            Target::Channel(_) => "channel",
+            Target::User(_) => "user",
+        }
+    }
+
+    fn id(self) -> u64 {
+        match self {
+            Target::Channel(channel) => channel.0,
+            Target::User(user) => user.0,
+        }
+    }
+}
+
+/// An anime followed with `/airing follow`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Subscription {
+    /// AniList ID of the anime.
+    pub media_id: i64,
+    /// Title at the time it was followed, for listing subscriptions without asking AniList.
+    pub title: String,
+    pub target: Target,
+    /// Guild of the channel, `None` for DMs.
+    pub guild_id: Option<GuildId>,
+}
+
+const COLUMNS: &str = "media_id, title, target, target_id, guild_id";
+
+fn from_row(row: &Row) -> rusqlite::Result<Subscription> {
+    let kind: String = row.get(2)?;
+    let target_id: u64 = row.get(3)?;
+    let target = match kind.as_str() {
+        "channel" => Target::Channel(ChannelId(target_id)),
+        _ => Target::User(UserId(target_id)),
+    };
+    Ok(Subscription {
+        media_id: row.get(0)?,
+        title: row.get(1)?,
+        target,
+        guild_id: row.get::<_, Option<u64>>(4)?.map(GuildId),
+    })
+}
+
+/// Loads every subscription.
+pub fn all(connection: &mut Connection) -> rusqlite::Result<Vec<Subscription>> {
+    let mut statement =
+        connection.prepare(&format!("SELECT {} FROM airing_subscriptions", COLUMNS))?;
+    let rows = statement.query_map([], from_row)?;
+    rows.collect()
+}
+
+/// Loads the subscriptions of a single channel or user.
+pub fn for_target(
+    connection: &mut Connection,
+    target: Target,
+) -> rusqlite::Result<Vec<Subscription>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM airing_subscriptions WHERE target = ?1 AND target_id = ?2 ORDER BY title",
+        COLUMNS
+    ))?;
+    let rows = statement.query_map(params![target.kind(), target.id()], from_row)?;
+    rows.collect()
+}
+
+/// Loads the subscriptions of all channels in a guild.
+pub fn for_guild(
+    connection: &mut Connection,
+    guild_id: GuildId,
+) -> rusqlite::Result<Vec<Subscription>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM airing_subscriptions WHERE guild_id = ?1 ORDER BY title",
+        COLUMNS
+    ))?;
+    let rows = statement.query_map(params![guild_id.0], from_row)?;
+    rows.collect()
+}
+
+/// Stores a subscription, returning `false` if it already existed.
+pub fn subscribe(
+    connection: &mut Connection,
+    subscription: &Subscription,
+) -> rusqlite::Result<bool> {
+    let inserted = connection.execute(
+        "INSERT OR IGNORE INTO airing_subscriptions (media_id, title, target, target_id, guild_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            subscription.media_id,
+            subscription.title,
+            subscription.target.kind(),
+            subscription.target.id(),
+            subscription.guild_id.map(|g| g.0)
+        ],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Removes a subscription, returning `false` if there was none.
+pub fn unsubscribe(
+    connection: &mut Connection,
+    media_id: i64,
+    target: Target,
+) -> rusqlite::Result<bool> {
+    let deleted = connection.execute(
+        "DELETE FROM airing_subscriptions WHERE media_id = ?1 AND target = ?2 AND target_id = ?3",
+        params![media_id, target.kind(), target.id()],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// Unix time up to which aired episodes have been announced.
+pub fn last_checked(connection: &mut Connection) -> rusqlite::Result<Option<i64>> {
+    connection
+        .query_row(
+            "SELECT last_checked FROM airing_poll WHERE id = 0",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+}
+
+pub fn set_last_checked(connection: &mut Connection, last_checked: i64) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT INTO airing_poll (id, last_checked) VALUES (0, ?1)
+         ON CONFLICT (id) DO UPDATE SET last_checked = excluded.last_checked",
+        params![last_checked],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn subscription(
+        media_id: i64,
+        title: &str,
+        target: Target,
+        guild_id: Option<u64>,
+    ) -> Subscription {
+        Subscription {
+            media_id,
+            title: title.to_owned(),
+            target,
+            guild_id: guild_id.map(GuildId),
+        }
+    }
+
+    #[test]
+    fn subscriptions_are_stored_once() {
+        let mut connection = test_connection();
+        let overlord = subscription(1, "Overlord", Target::User(UserId(10)), None);
+
+        assert!(subscribe(&mut connection, &overlord).unwrap());
+        assert!(!subscribe(&mut connection, &overlord).unwrap());
+        assert_eq!(all(&mut connection).unwrap(), vec![overlord.clone()]);
+
+        assert!(unsubscribe(&mut connection, 1, overlord.target).unwrap());
+        assert!(!unsubscribe(&mut connection, 1, overlord.target).unwrap());
+        assert!(all(&mut connection).unwrap().is_empty());
+    }
+
+    #[test]
+    fn subscriptions_by_target_and_guild() {
+        let mut connection = test_connection();
+        let channel = Target::Channel(ChannelId(20));
+        let user = Target::User(UserId(20));
+        let subscriptions = [
+            subscription(1, "Overlord", channel, Some(30)),
+            subscription(2, "Konosuba", channel, Some(30)),
+            subscription(1, "Overlord", user, None),
+            subscription(3, "Re:Zero", Target::Channel(ChannelId(21)), Some(31)),
+        ];
+        for subscription in &subscriptions {
+            subscribe(&mut connection, subscription).unwrap();
+        }
+
+        // A channel and a user with the same ID are different targets.
+        assert_eq!(
+            for_target(&mut connection, channel).unwrap(),
+            vec![subscriptions[1].clone(), subscriptions[0].clone()]
+        );
+        assert_eq!(
+            for_target(&mut connection, user).unwrap(),
+            vec![subscriptions[2].clone()]
+        );
+        assert_eq!(
+            for_guild(&mut connection, GuildId(30)).unwrap(),
+            vec![subscriptions[1].clone(), subscriptions[0].clone()]
+        );
+        assert_eq!(all(&mut connection).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn last_checked_is_replaced() {
+        let mut connection = test_connection();
+
+        assert_eq!(last_checked(&mut connection).unwrap(), None);
+        set_last_checked(&mut connection, 100).unwrap();
+        set_last_checked(&mut connection, 200).unwrap();
+        assert_eq!(last_checked(&mut connection).unwrap(), Some(200));
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::{ChannelId, UserId};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Where owner alerts are sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertTarget {
+    Channel(ChannelId),
+    /// Direct messages to an owner.
+    Dm(UserId),
+}
+
+/// Whether and where errors are reported to the owners. Off until an owner turns it on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    pub target: Option<AlertTarget>,
+}
+
+pub fn load(connection: &mut Connection) -> rusqlite::Result<AlertSettings> {
+    let row = connection
+        .query_row(
+            "SELECT enabled, channel_id, user_id FROM alert_settings WHERE id = 0",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, bool>(0)?,
+                    row.get::<_, Option<u64>>(1)?,
+                    row.get::<_, Option<u64>>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+    Ok(match row {
+        Some((enabled, channel_id, user_id)) => AlertSettings {
+            enabled,
+            target: match (channel_id, user_id) {
+                (Some(channel_id), _) => Some(AlertTarget::Channel(ChannelId(channel_id))),
+                (None, Some(user_id)) => Some(AlertTarget::Dm(UserId(user_id))),
+                (None, None) => None,
+            },
+        },
+        None => AlertSettings::default(),
+    })
+}
+
+pub fn save(connection: &mut Connection, settings: &AlertSettings) -> rusqlite::Result<()> {
+    let (channel_id, user_id) = match settings.target {
+        Some(AlertTarget::Channel(channel_id)) => (Some(channel_id.0), None),
+        Some(AlertTarget::Dm(user_id)) => (None, Some(user_id.0)),
+        None => (None, None),
+    };
+    connection.execute(
+        "INSERT INTO alert_settings (id, enabled, channel_id, user_id) VALUES (0, ?1, ?2, ?3)
+         ON CONFLICT (id) DO UPDATE SET
+             enabled = excluded.enabled,
+             channel_id = excluded.channel_id,
+             user_id = excluded.user_id",
+        params![settings.enabled, channel_id, user_id],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::UserId;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// The AniList account a Discord user linked with `/anilist link`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnilistLink {
+    /// AniList user ID, which stays the same when the account is renamed.
+    pub anilist_id: i64,
+    /// Name of the account when it was linked.
+    pub username: String,
+}
+
+pub fn get(connection: &mut Connection, user_id: UserId) -> rusqlite::Result<Option<AnilistLink>> {
+    connection
+        .query_row(
+            "SELECT anilist_id, username FROM anilist_links WHERE user_id = ?1",
+            params![user_id.0],
+            |row| {
+                Ok(AnilistLink {
+                    anilist_id: row.get(0)?,
+                    username: row.get(1)?,
+                })
+            },
+        )
+        .optional()
+}
+
+/// Links a Discord user to an AniList account, replacing any previous link.
+pub fn set(
+    connection: &mut Connection,
+    user_id: UserId,
+    link: &AnilistLink,
+) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT INTO anilist_links (user_id, anilist_id, username) VALUES (?1, ?2, ?3)
+         ON CONFLICT (user_id) DO UPDATE SET
+            anilist_id = excluded.anilist_id,
+            username = excluded.username",
+        params![user_id.0, link.anilist_id, link.username],
+    )?;
+    Ok(())
+}
+
+/// Removes a Discord user's link, returning `false` if there was none.
+pub fn remove(connection: &mut Connection, user_id: UserId) -> rusqlite::Result<bool> {
+    let deleted = connection.execute(
+        "DELETE FROM anilist_links WHERE user_id = ?1",
+        params![user_id.0],
+    )?;
+    Ok(deleted > 0)
+}
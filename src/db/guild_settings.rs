@@ -0,0 +1,163 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use poise::serenity_prelude::{ChannelId, GuildId};
+use rusqlite::{params, Connection};
+
+/// Settings a guild's admins can change with `/settings`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// Prefix for prefix commands, replacing the configured one.
+    pub prefix: Option<String>,
+    /// Command categories that may not be used in the guild.
+    pub disabled_categories: HashSet<String>,
+    /// Channel that receives a message whenever the settings change.
+    pub log_channel: Option<ChannelId>,
+    /// Discord locale code, e.g. `en-US`.
+    pub locale: Option<String>,
+}
+
+/// Loads the settings of every guild that has changed any.
+pub fn load_all(connection: &mut Connection) -> rusqlite::Result<HashMap<GuildId, GuildSettings>> {
+    let mut settings = HashMap::new();
+    let mut statement =
+        connection.prepare("SELECT guild_id, prefix, log_channel, locale FROM guild_settings")?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            GuildId(row.get(0)?),
+            GuildSettings {
+                prefix: row.get(1)?,
+                log_channel: row.get::<_, Option<u64>>(2)?.map(ChannelId),
+                locale: row.get(3)?,
+                ..Default::default()
+            },
+        ))
+    })?;
+    for row in rows {
+        let (guild_id, guild_settings) = row?;
+        settings.insert(guild_id, guild_settings);
+    }
+
+    let mut statement =
+        connection.prepare("SELECT guild_id, category FROM guild_disabled_categories")?;
+    let rows = statement.query_map([], |row| {
+        Ok((GuildId(row.get(0)?), row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (guild_id, category) = row?;
+        settings
+            .entry(guild_id)
+            .or_default()
+            .disabled_categories
+            .insert(category);
+    }
+    Ok(settings)
+}
+
+/// Replaces the stored settings of a guild.
+pub fn save(
+    connection: &mut Connection,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO guild_settings (guild_id, prefix, log_channel, locale)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (guild_id) DO UPDATE SET
+            prefix = excluded.prefix,
+            log_channel = excluded.log_channel,
+            locale = excluded.locale",
+        params![
+            guild_id.0,
+            settings.prefix,
+            settings.log_channel.map(|c| c.0),
+            settings.locale
+        ],
+    )?;
+    transaction.execute(
+        "DELETE FROM guild_disabled_categories WHERE guild_id = ?1",
+        params![guild_id.0],
+    )?;
+    for category in &settings.disabled_categories {
+        transaction.execute(
+            "INSERT INTO guild_disabled_categories (guild_id, category) VALUES (?1, ?2)",
+            params![guild_id.0, category],
+        )?;
+    }
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn settings(prefix: Option<&str>, disabled: &[&str]) -> GuildSettings {
+        GuildSettings {
+            prefix: prefix.map(str::to_owned),
+            disabled_categories: disabled.iter().map(|c| c.to_string()).collect(),
+            log_channel: Some(ChannelId(20)),
+            locale: Some("ja".to_owned()),
+        }
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut connection = test_connection();
+        let first = settings(Some("?"), &["fun", "weeb"]);
+        let second = GuildSettings::default();
+
+        save(&mut connection, GuildId(1), &first).unwrap();
+        save(&mut connection, GuildId(2), &second).unwrap();
+        let loaded = load_all(&mut connection).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[&GuildId(1)], first);
+        assert_eq!(loaded[&GuildId(2)], second);
+    }
+
+    #[test]
+    fn saving_replaces_the_disabled_categories() {
+        let mut connection = test_connection();
+
+        save(
+            &mut connection,
+            GuildId(1),
+            &settings(None, &["fun", "weeb"]),
+        )
+        .unwrap();
+        save(&mut connection, GuildId(1), &settings(None, &["general"])).unwrap();
+        save(&mut connection, GuildId(2), &settings(None, &["fun"])).unwrap();
+        let loaded = load_all(&mut connection).unwrap();
+
+        assert_eq!(
+            loaded[&GuildId(1)].disabled_categories,
+            settings(None, &["general"]).disabled_categories
+        );
+        assert_eq!(
+            loaded[&GuildId(2)].disabled_categories,
+            settings(None, &["fun"]).disabled_categories
+        );
+
+        save(&mut connection, GuildId(1), &settings(None, &[])).unwrap();
+        assert!(load_all(&mut connection).unwrap()[&GuildId(1)]
+            .disabled_categories
+            .is_empty());
+    }
+}
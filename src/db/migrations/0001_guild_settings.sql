@@ -0,0 +1,12 @@
+CREATE TABLE guild_settings (
+    guild_id INTEGER PRIMARY KEY NOT NULL,
+    prefix TEXT,
+    log_channel INTEGER,
+    locale TEXT
+);
+
+CREATE TABLE guild_disabled_categories (
+    guild_id INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    PRIMARY KEY (guild_id, category)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE airing_subscriptions (
+    media_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    target TEXT NOT NULL CHECK (target IN ('channel', 'user')),
+    target_id INTEGER NOT NULL,
+    guild_id INTEGER,
+    PRIMARY KEY (media_id, target, target_id)
+);
+
+CREATE TABLE airing_poll (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    last_checked INTEGER NOT NULL
+);
@@ -0,0 +1,5 @@
+CREATE TABLE anilist_links (
+    user_id INTEGER PRIMARY KEY NOT NULL,
+    anilist_id INTEGER NOT NULL,
+    username TEXT NOT NULL
+);
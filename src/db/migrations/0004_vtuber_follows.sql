@@ -0,0 +1,21 @@
+CREATE TABLE vtuber_follows (
+    guild_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    birthday_month INTEGER,
+    birthday_day INTEGER,
+    debut_year INTEGER,
+    debut_month INTEGER,
+    debut_day INTEGER,
+    PRIMARY KEY (guild_id, title)
+);
+
+CREATE TABLE vtuber_channels (
+    guild_id INTEGER PRIMARY KEY NOT NULL,
+    channel_id INTEGER NOT NULL
+);
+
+CREATE TABLE vtuber_announced (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    date TEXT NOT NULL
+);
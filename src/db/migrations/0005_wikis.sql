@@ -0,0 +1,9 @@
+CREATE TABLE wikis (
+    guild_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    site_name TEXT NOT NULL,
+    api TEXT NOT NULL,
+    description_start TEXT,
+    description_end TEXT,
+    PRIMARY KEY (guild_id, name)
+);
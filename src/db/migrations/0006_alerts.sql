@@ -0,0 +1,6 @@
+CREATE TABLE alert_settings (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    enabled INTEGER NOT NULL,
+    channel_id INTEGER,
+    user_id INTEGER
+);
@@ -0,0 +1,6 @@
+CREATE TABLE restart_notice (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    channel_id INTEGER NOT NULL,
+    requested_at INTEGER NOT NULL,
+    locale TEXT NOT NULL
+);
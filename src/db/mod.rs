@@ -0,0 +1,173 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::util::get_project_dirs;
+
+pub mod airing;
+pub mod alerts;
+pub mod anilist_links;
+pub mod guild_settings;
+pub mod restart;
+pub mod vtuber;
+pub mod wikis;
+
+/// Schema migrations, applied in order. The schema version is tracked in `PRAGMA user_version`,
+/// so new migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/0001_guild_settings.sql"),
+    include_str!("migrations/0002_airing_subscriptions.sql"),
+    include_str!("migrations/0003_anilist_links.sql"),
+    include_str!("migrations/0004_vtuber_follows.sql"),
+    include_str!("migrations/0005_wikis.sql"),
+    include_str!("migrations/0006_alerts.sql"),
+    include_str!("migrations/0007_restart.sql"),
+];
+
+/// Handle to the bot's SQLite database.
+///
+/// Cheap to clone; all clones share a single connection.
+#[derive(Clone)]
+pub struct Database {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    /// Opens the database in the project data directory, creating it if needed.
+    pub fn open_default() -> Result<Database> {
+        let project_dirs =
+            get_project_dirs().ok_or_else(|| anyhow!("Failed to get project directories!"))?;
+        let data_dir = project_dirs.data_dir();
+        fs::create_dir_all(data_dir)?;
+        Database::open(data_dir.join("lupusregina.db"))
+    }
+
+    /// Opens the database at `path` and brings its schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Database> {
+        let mut connection = Connection::open(path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut connection)?;
+        Ok(Database {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs `f` against the connection on the blocking thread pool.
+    pub async fn run<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| anyhow!("Database connection poisoned"))?;
+            f(&mut connection).map_err(|e| e.into())
+        })
+        .await?
+    }
+}
+
+fn migrate(connection: &mut Connection) -> Result<()> {
+    let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+        info!("Applied database migration {}", index + 1);
+    }
+    Ok(())
+}
+
+/// An in-memory database with the current schema, for tests.
+#[cfg(test)]
+pub(crate) fn test_connection() -> Connection {
+    let mut connection = Connection::open_in_memory().unwrap();
+    connection
+        .pragma_update(None, "foreign_keys", true)
+        .unwrap();
+    migrate(&mut connection).unwrap();
+    connection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(connection: &Connection) -> usize {
+        connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrations_bring_a_new_database_up_to_date() {
+        let connection = test_connection();
+
+        assert_eq!(user_version(&connection), MIGRATIONS.len());
+        let tables: Vec<String> = connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(tables.contains(&"guild_settings".to_owned()));
+        assert!(tables.contains(&"restart_notice".to_owned()));
+    }
+
+    #[test]
+    fn migrations_continue_from_the_stored_version() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(MIGRATIONS[0]).unwrap();
+        connection.pragma_update(None, "user_version", 1).unwrap();
+        connection
+            .execute(
+                "INSERT INTO guild_settings (guild_id, prefix) VALUES (1, '?')",
+                [],
+            )
+            .unwrap();
+
+        migrate(&mut connection).unwrap();
+        // Running them again changes nothing.
+        migrate(&mut connection).unwrap();
+
+        assert_eq!(user_version(&connection), MIGRATIONS.len());
+        let prefix: String = connection
+            .query_row(
+                "SELECT prefix FROM guild_settings WHERE guild_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(prefix, "?");
+        let subscriptions: i64 = connection
+            .query_row("SELECT COUNT(*) FROM airing_subscriptions", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(subscriptions, 0);
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::ChannelId;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Left by `/restart` so the new process can report back where it was asked to restart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestartNotice {
+    pub channel_id: ChannelId,
+    /// Unix time the restart was requested at.
+    pub requested_at: i64,
+    /// Locale to report back in, that of whoever asked for the restart.
+    pub locale: String,
+}
+
+pub fn save(connection: &mut Connection, notice: &RestartNotice) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT INTO restart_notice (id, channel_id, requested_at, locale) VALUES (0, ?1, ?2, ?3)
+         ON CONFLICT (id) DO UPDATE SET
+             channel_id = excluded.channel_id,
+             requested_at = excluded.requested_at,
+             locale = excluded.locale",
+        params![notice.channel_id.0, notice.requested_at, notice.locale],
+    )?;
+    Ok(())
+}
+
+/// Removes and returns the notice, if a restart left one.
+pub fn take(connection: &mut Connection) -> rusqlite::Result<Option<RestartNotice>> {
+    let transaction = connection.transaction()?;
+    let notice = transaction
+        .query_row(
+            "SELECT channel_id, requested_at, locale FROM restart_notice WHERE id = 0",
+            [],
+            |row| {
+                Ok(RestartNotice {
+                    channel_id: ChannelId(row.get(0)?),
+                    requested_at: row.get(1)?,
+                    locale: row.get(2)?,
+                })
+            },
+        )
+        .optional()?;
+    transaction.execute("DELETE FROM restart_notice", [])?;
+    transaction.commit()?;
+    Ok(notice)
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use chrono::NaiveDate;
+use poise::serenity_prelude::{ChannelId, GuildId};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::infobox::WikiDate;
+
+/// A VTuber whose birthday and debut anniversary are announced in a guild.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Follow {
+    pub guild_id: GuildId,
+    /// Title of the wiki article.
+    pub title: String,
+    pub url: String,
+    pub birthday: Option<WikiDate>,
+    pub debut: Option<WikiDate>,
+}
+
+const COLUMNS: &str = "guild_id, title, url, birthday_month, birthday_day, debut_year, \
+                       debut_month, debut_day";
+
+fn from_row(row: &Row) -> rusqlite::Result<Follow> {
+    let date = |year: Option<i32>, month: Option<u32>, day: Option<u32>| match (month, day) {
+        (Some(month), Some(day)) => Some(WikiDate { year, month, day }),
+        _ => None,
+    };
+    Ok(Follow {
+        guild_id: GuildId(row.get(0)?),
+        title: row.get(1)?,
+        url: row.get(2)?,
+        birthday: date(None, row.get(3)?, row.get(4)?),
+        debut: date(row.get(5)?, row.get(6)?, row.get(7)?),
+    })
+}
+
+/// Loads every follow.
+pub fn all(connection: &mut Connection) -> rusqlite::Result<Vec<Follow>> {
+    let mut statement = connection.prepare(&format!("SELECT {} FROM vtuber_follows", COLUMNS))?;
+    let rows = statement.query_map([], from_row)?;
+    rows.collect()
+}
+
+/// Loads the follows of a guild.
+pub fn for_guild(connection: &mut Connection, guild_id: GuildId) -> rusqlite::Result<Vec<Follow>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM vtuber_follows WHERE guild_id = ?1 ORDER BY title",
+        COLUMNS
+    ))?;
+    let rows = statement.query_map(params![guild_id.0], from_row)?;
+    rows.collect()
+}
+
+/// Stores a follow, replacing the dates of an existing one. Returns `false` if it already
+/// existed.
+pub fn follow(connection: &mut Connection, follow: &Follow) -> rusqlite::Result<bool> {
+    let existed = connection
+        .query_row(
+            "SELECT 1 FROM vtuber_follows WHERE guild_id = ?1 AND title = ?2",
+            params![follow.guild_id.0, follow.title],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    connection.execute(
+        "INSERT INTO vtuber_follows (guild_id, title, url, birthday_month, birthday_day,
+             debut_year, debut_month, debut_day)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT (guild_id, title) DO UPDATE SET
+             url = excluded.url,
+             birthday_month = excluded.birthday_month,
+             birthday_day = excluded.birthday_day,
+             debut_year = excluded.debut_year,
+             debut_month = excluded.debut_month,
+             debut_day = excluded.debut_day",
+        params![
+            follow.guild_id.0,
+            follow.title,
+            follow.url,
+            follow.birthday.map(|d| d.month),
+            follow.birthday.map(|d| d.day),
+            follow.debut.and_then(|d| d.year),
+            follow.debut.map(|d| d.month),
+            follow.debut.map(|d| d.day)
+        ],
+    )?;
+    Ok(!existed)
+}
+
+/// Removes a follow, returning `false` if there was none.
+pub fn unfollow(
+    connection: &mut Connection,
+    guild_id: GuildId,
+    title: &str,
+) -> rusqlite::Result<bool> {
+    let deleted = connection.execute(
+        "DELETE FROM vtuber_follows WHERE guild_id = ?1 AND title = ?2",
+        params![guild_id.0, title],
+    )?;
+    Ok(deleted > 0)
+}
+
+/// The channel a guild's announcements are posted in.
+pub fn channel(
+    connection: &mut Connection,
+    guild_id: GuildId,
+) -> rusqlite::Result<Option<ChannelId>> {
+    connection
+        .query_row(
+            "SELECT channel_id FROM vtuber_channels WHERE guild_id = ?1",
+            params![guild_id.0],
+            |row| row.get(0).map(ChannelId),
+        )
+        .optional()
+}
+
+/// Sets the channel a guild's announcements are posted in, or stops them if `channel` is `None`.
+pub fn set_channel(
+    connection: &mut Connection,
+    guild_id: GuildId,
+    channel: Option<ChannelId>,
+) -> rusqlite::Result<()> {
+    match channel {
+        Some(channel) => connection.execute(
+            "INSERT INTO vtuber_channels (guild_id, channel_id) VALUES (?1, ?2)
+             ON CONFLICT (guild_id) DO UPDATE SET channel_id = excluded.channel_id",
+            params![guild_id.0, channel.0],
+        )?,
+        None => connection.execute(
+            "DELETE FROM vtuber_channels WHERE guild_id = ?1",
+            params![guild_id.0],
+        )?,
+    };
+    Ok(())
+}
+
+/// The last day whose events have been announced.
+pub fn last_announced(connection: &mut Connection) -> rusqlite::Result<Option<NaiveDate>> {
+    let date: Option<String> = connection
+        .query_row(
+            "SELECT date FROM vtuber_announced WHERE id = 0",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(date.and_then(|date| date.parse().ok()))
+}
+
+pub fn set_last_announced(connection: &mut Connection, date: NaiveDate) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT INTO vtuber_announced (id, date) VALUES (0, ?1)
+         ON CONFLICT (id) DO UPDATE SET date = excluded.date",
+        params![date.to_string()],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use poise::serenity_prelude::GuildId;
+use reqwest::Url;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::mediawiki::Extraction;
+
+/// A MediaWiki site registered in a guild for `/wiki`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wiki {
+    pub guild_id: GuildId,
+    /// Name used to pick the wiki in `/wiki`.
+    pub name: String,
+    /// The wiki's own name, as reported by its API.
+    pub site_name: String,
+    pub api: Url,
+    pub extraction: Extraction,
+}
+
+const COLUMNS: &str = "guild_id, name, site_name, api, description_start, description_end";
+
+fn from_row(row: &Row) -> rusqlite::Result<Wiki> {
+    let api: String = row.get(3)?;
+    let api = Url::parse(&api)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(e)))?;
+    Ok(Wiki {
+        guild_id: GuildId(row.get(0)?),
+        name: row.get(1)?,
+        site_name: row.get(2)?,
+        api,
+        extraction: Extraction {
+            start: row.get(4)?,
+            end: row.get(5)?,
+        },
+    })
+}
+
+/// Loads the wikis of a guild.
+pub fn for_guild(connection: &mut Connection, guild_id: GuildId) -> rusqlite::Result<Vec<Wiki>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM wikis WHERE guild_id = ?1 ORDER BY name",
+        COLUMNS
+    ))?;
+    let rows = statement.query_map(params![guild_id.0], from_row)?;
+    rows.collect()
+}
+
+/// Loads a single wiki of a guild by name.
+pub fn get(
+    connection: &mut Connection,
+    guild_id: GuildId,
+    name: &str,
+) -> rusqlite::Result<Option<Wiki>> {
+    connection
+        .query_row(
+            &format!(
+                "SELECT {} FROM wikis WHERE guild_id = ?1 AND name = ?2",
+                COLUMNS
+            ),
+            params![guild_id.0, name],
+            from_row,
+        )
+        .optional()
+}
+
+/// Stores a wiki, replacing one of the same name.
+pub fn save(connection: &mut Connection, wiki: &Wiki) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT INTO wikis (guild_id, name, site_name, api, description_start, description_end)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (guild_id, name) DO UPDATE SET
+             site_name = excluded.site_name,
+             api = excluded.api,
+             description_start = excluded.description_start,
+             description_end = excluded.description_end",
+        params![
+            wiki.guild_id.0,
+            wiki.name,
+            wiki.site_name,
+            wiki.api.as_str(),
+            wiki.extraction.start,
+            wiki.extraction.end
+        ],
+    )?;
+    Ok(())
+}
+
+/// Removes a wiki, returning `false` if there was none.
+pub fn remove(
+    connection: &mut Connection,
+    guild_id: GuildId,
+    name: &str,
+) -> rusqlite::Result<bool> {
+    let deleted = connection.execute(
+        "DELETE FROM wikis WHERE guild_id = ?1 AND name = ?2",
+        params![guild_id.0, name],
+    )?;
+    Ok(deleted > 0)
+}
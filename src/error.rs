@@ -0,0 +1,245 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! The failures commands report back to their invoker.
+
+use std::fmt;
+use std::time::Duration;
+
+use poise::serenity_prelude::{self as serenity, ModelError, Permissions};
+use tracing::{error, info, warn, Level};
+
+use crate::alerts::Alert;
+use crate::commands::settings::reply_locale;
+use crate::http::HttpError;
+use crate::i18n::tr;
+use crate::{Context, Error};
+
+/// Why a command failed, deciding what the invoker is told and how loudly it is logged.
+///
+/// Commands can return these directly; any other error is sorted into a variant by
+/// [`BotError::from`] before it is reported.
+#[derive(Debug)]
+pub enum BotError {
+    /// Nothing matched what was searched for.
+    NotFound(String),
+    /// A remote service didn't answer within the configured timeout.
+    UpstreamTimeout { service: String },
+    /// A remote service rate limited the bot.
+    RateLimited { retry_after: Duration },
+    /// The invoker, or the bot if `bot` is set, lacks `permissions`.
+    MissingPermission { permissions: Permissions, bot: bool },
+    /// The arguments can't be used, for the given reason.
+    InvalidInput(String),
+    /// Discord rejected a request.
+    Discord(serenity::Error),
+    /// Anything else, a bug or an unexpected answer from somewhere.
+    Internal(Error),
+}
+
+impl BotError {
+    /// How loudly the error is logged; only what owners may have to act on is an error.
+    pub fn level(&self) -> Level {
+        match self {
+            BotError::NotFound(_)
+            | BotError::InvalidInput(_)
+            | BotError::MissingPermission { .. } => Level::INFO,
+            BotError::UpstreamTimeout { .. } | BotError::RateLimited { .. } => Level::WARN,
+            BotError::Discord(_) | BotError::Internal(_) => Level::ERROR,
+        }
+    }
+
+    /// What the invoker is told, in the language of `locale` if there is a translation.
+    pub fn message(&self, locale: &str) -> String {
+        match self {
+            BotError::NotFound(query) => tr(locale, "error-not-found", &[("query", query.into())]),
+            BotError::UpstreamTimeout { service } => tr(
+                locale,
+                "error-upstream-timeout",
+                &[("service", service.into())],
+            ),
+            BotError::RateLimited { retry_after } => tr(
+                locale,
+                "error-rate-limited",
+                &[("seconds", retry_after.as_secs().max(1).into())],
+            ),
+            BotError::MissingPermission { permissions, bot } => {
+                let id = if *bot {
+                    "error-missing-permission-bot"
+                } else {
+                    "error-missing-permission-user"
+                };
+                let names = permissions.get_permission_names().join(", ");
+                tr(locale, id, &[("permissions", names.into())])
+            }
+            BotError::InvalidInput(reason) => {
+                tr(locale, "error-invalid-input", &[("reason", reason.into())])
+            }
+            BotError::Discord(_) => tr(locale, "error-discord", &[]),
+            BotError::Internal(_) => tr(locale, "error-internal", &[]),
+        }
+    }
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::NotFound(query) => write!(f, "nothing found for {}", query),
+            BotError::UpstreamTimeout { service } => write!(f, "{} timed out", service),
+            BotError::RateLimited { retry_after } => {
+                write!(f, "rate limited for {}s", retry_after.as_secs())
+            }
+            BotError::MissingPermission { permissions, bot } => write!(
+                f,
+                "{} missing permissions {}",
+                if *bot { "bot" } else { "user" },
+                permissions.get_permission_names().join(", ")
+            ),
+            BotError::InvalidInput(reason) => write!(f, "invalid input: {}", reason),
+            BotError::Discord(e) => write!(f, "Discord error: {}", e),
+            BotError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::Discord(e) => Some(e),
+            // `Display` already shows the wrapped error itself.
+            BotError::Internal(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for BotError {
+    fn from(error: Error) -> Self {
+        let error = match error.downcast::<BotError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<HttpError>() {
+            Ok(error) => return BotError::from(*error),
+            Err(error) => error,
+        };
+        match error.downcast::<serenity::Error>() {
+            Ok(error) => BotError::from(*error),
+            Err(error) => BotError::Internal(error),
+        }
+    }
+}
+
+impl From<HttpError> for BotError {
+    fn from(error: HttpError) -> Self {
+        match error {
+            HttpError::RateLimited { retry_after, .. } => BotError::RateLimited { retry_after },
+            HttpError::Timeout { host } => BotError::UpstreamTimeout { service: host },
+            error => BotError::Internal(error.into()),
+        }
+    }
+}
+
+impl From<serenity::Error> for BotError {
+    fn from(error: serenity::Error) -> Self {
+        match error {
+            serenity::Error::Model(ModelError::InvalidPermissions(permissions)) => {
+                BotError::MissingPermission {
+                    permissions,
+                    bot: true,
+                }
+            }
+            error => BotError::Discord(error),
+        }
+    }
+}
+
+/// A short random ID, logged along with an error and shown to the invoker so owners can find
+/// the log entry for a report.
+pub fn error_id() -> String {
+    format!("{:08x}", rand::random::<u32>())
+}
+
+/// Logs `error` at its level, queues an owner alert for errors, and tells the invoker what went
+/// wrong.
+pub async fn report(context: Context<'_>, error: BotError) {
+    let id = error_id();
+    let command = &context.command().qualified_name;
+    match error.level() {
+        Level::ERROR => error!("Error {} in command `{}`: {:?}", id, command, error),
+        Level::WARN => warn!("Error {} in command `{}`: {:?}", id, command, error),
+        _ => info!("Error {} in command `{}`: {:?}", id, command, error),
+    }
+    if error.level() == Level::ERROR {
+        context.data().alerts.push(Alert::new(context, &error, &id));
+    }
+    let locale = reply_locale(context).await;
+    let message = format!(
+        "{}\n{}: `{}`",
+        error.message(&locale),
+        tr(&locale, "error-id", &[]),
+        id
+    );
+    if let Err(e) = context.send(|m| m.content(message).ephemeral(true)).await {
+        warn!("Unable to report error {} to the invoker: {}", id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_errors_into_variants() {
+        let timeout: Error = HttpError::Timeout {
+            host: "graphql.anilist.co".to_owned(),
+        }
+        .into();
+        assert!(matches!(
+            BotError::from(timeout),
+            BotError::UpstreamTimeout { service } if service == "graphql.anilist.co"
+        ));
+
+        let not_found: Error = BotError::NotFound("Bebop".to_owned()).into();
+        assert!(matches!(BotError::from(not_found), BotError::NotFound(_)));
+
+        let other: Error = "Failed to get GuildID from Message.".into();
+        let other = BotError::from(other);
+        assert!(matches!(other, BotError::Internal(_)));
+        assert_eq!(other.level(), Level::ERROR);
+    }
+
+    #[test]
+    fn messages_fall_back_to_english() {
+        let error = BotError::RateLimited {
+            retry_after: Duration::from_millis(200),
+        };
+        assert_eq!(error.message("en-US"), "Rate limited, try again in 1s.");
+        assert_eq!(error.message("ko"), "Rate limited, try again in 1s.");
+        assert_eq!(
+            error.message("es-419"),
+            "Demasiadas solicitudes, inténtalo de nuevo en 1s."
+        );
+        let error = BotError::MissingPermission {
+            permissions: Permissions::MANAGE_GUILD,
+            bot: false,
+        };
+        assert_eq!(
+            error.message("es-ES"),
+            "Necesitas el permiso Manage Guilds para esto."
+        );
+    }
+}
@@ -0,0 +1,477 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use graphql_client::{GraphQLQuery, Response};
+use reqwest::header::{HeaderMap, RETRY_AFTER, USER_AGENT};
+use reqwest::redirect::Policy;
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::cache::ResponseCache;
+use crate::util::ConfigHandle;
+
+/// Delay before the first retry, doubled for every further one.
+const BACKOFF: Duration = Duration::from_millis(500);
+/// How long to back off when a host runs out of requests without saying for how long.
+/// AniList counts requests per minute.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// A request that failed after all retries.
+#[derive(Debug)]
+pub enum HttpError {
+    /// The host rate limited the bot, no requests are sent to it until `retry_after` has passed.
+    RateLimited {
+        host: String,
+        retry_after: Duration,
+    },
+    /// The host didn't answer within the configured timeout.
+    Timeout {
+        host: String,
+    },
+    /// The host answered with an error status.
+    Status {
+        host: String,
+        status: StatusCode,
+    },
+    Request(reqwest::Error),
+    /// The response wasn't the JSON that was expected.
+    Decode(serde_json::Error),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::RateLimited { host, retry_after } => write!(
+                f,
+                "rate limited by {}, try again in {}s",
+                host,
+                retry_after.as_secs().max(1)
+            ),
+            HttpError::Timeout { host } => write!(f, "{} did not respond in time", host),
+            HttpError::Status { host, status } => write!(f, "{} responded with {}", host, status),
+            HttpError::Request(e) => write!(f, "request failed: {}", e),
+            HttpError::Decode(e) => write!(f, "unexpected response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HttpError::Request(e) => Some(e),
+            HttpError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// HTTP client shared by everything talking to AniList and the wiki.
+///
+/// Applies the `[http]` configuration to every request, retries server errors with exponential
+/// backoff and stops sending requests to hosts that rate limited the bot. Successful responses
+/// are kept in a [`ResponseCache`] for the configured TTL. Redirects aren't followed, so a wiki
+/// registered by a guild can't send the bot on to a host that wasn't checked. Cheap to clone.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    config: ConfigHandle,
+    cache: Arc<ResponseCache>,
+    /// When each rate limited host may be asked again.
+    rate_limits: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl HttpClient {
+    pub fn new(config: ConfigHandle, cache: Arc<ResponseCache>) -> Self {
+        HttpClient {
+            client: Client::builder()
+                .redirect(Policy::none())
+                .build()
+                .expect("Failed to build the HTTP client"),
+            config,
+            cache,
+            rate_limits: Default::default(),
+        }
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    /// Sends a GraphQL query to `endpoint`, or reuses the response to an identical query.
+    ///
+    /// Client errors aren't failures here, GraphQL APIs such as AniList describe them in the
+    /// response body.
+    pub async fn graphql<Q: GraphQLQuery>(
+        &self,
+        endpoint: &Url,
+        variables: Q::Variables,
+    ) -> Result<Response<Q::ResponseData>, HttpError> {
+        self.graphql_inner::<Q>(endpoint, variables, true).await
+    }
+
+    /// Sends a GraphQL query to `endpoint` without using the cache, for queries that are unlikely
+    /// to repeat or must be up to date.
+    pub async fn graphql_uncached<Q: GraphQLQuery>(
+        &self,
+        endpoint: &Url,
+        variables: Q::Variables,
+    ) -> Result<Response<Q::ResponseData>, HttpError> {
+        self.graphql_inner::<Q>(endpoint, variables, false).await
+    }
+
+    async fn graphql_inner<Q: GraphQLQuery>(
+        &self,
+        endpoint: &Url,
+        variables: Q::Variables,
+        cached: bool,
+    ) -> Result<Response<Q::ResponseData>, HttpError> {
+        let body = Q::build_query(variables);
+        let key = if cached {
+            Some(graphql_key(endpoint, &body)?)
+        } else {
+            None
+        };
+        let text = self
+            .send(endpoint, key, true, |client| {
+                client.post(endpoint.clone()).json(&body)
+            })
+            .await?;
+        serde_json::from_str(&text).map_err(HttpError::Decode)
+    }
+
+    /// Whether the response to a GraphQL query is cached, so [`HttpClient::graphql`] won't send it.
+    pub async fn graphql_is_cached<Q: GraphQLQuery>(
+        &self,
+        endpoint: &Url,
+        variables: Q::Variables,
+    ) -> bool {
+        if self.config.read().await.cache.ttl.is_zero() {
+            return false;
+        }
+        graphql_key(endpoint, &Q::build_query(variables))
+            .map(|key| self.cache.contains(&key))
+            .unwrap_or(false)
+    }
+
+    /// Sends a GET request with the given query parameters, or reuses the response to an
+    /// identical request, and decodes the JSON response.
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &Url,
+        query: &[(&str, &str)],
+    ) -> Result<T, HttpError> {
+        let mut full_url = url.clone();
+        full_url.query_pairs_mut().extend_pairs(query);
+        let key = format!("GET {}", full_url);
+        let text = self
+            .send(url, Some(key), false, |client| {
+                client.get(url.clone()).query(query)
+            })
+            .await?;
+        serde_json::from_str(&text).map_err(HttpError::Decode)
+    }
+
+    /// Sends the request made by `build`, retrying server errors. Successful responses are cached
+    /// under `cache_key`, if there is one.
+    async fn send<F>(
+        &self,
+        url: &Url,
+        cache_key: Option<String>,
+        accept_client_errors: bool,
+        build: F,
+    ) -> Result<String, HttpError>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let (cache_ttl, cache_size) = {
+            let config = self.config.read().await;
+            (config.cache.ttl, config.cache.size)
+        };
+        let cache_key = cache_key.filter(|_| !cache_ttl.is_zero());
+        if let Some(body) = cache_key.as_deref().and_then(|key| self.cache.get(key)) {
+            return Ok(body);
+        }
+        let host = url.host_str().unwrap_or_default().to_owned();
+        if let Some(retry_after) = self.rate_limited_for(&host) {
+            return Err(HttpError::RateLimited { host, retry_after });
+        }
+        let (timeout, user_agent, retries) = {
+            let config = self.config.read().await;
+            (
+                config.http.timeout,
+                config.http.user_agent.clone(),
+                config.http.retries,
+            )
+        };
+
+        let mut attempt = 0;
+        loop {
+            let response = build(&self.client)
+                .timeout(timeout)
+                .header(USER_AGENT, &user_agent)
+                .send()
+                .await;
+            let error = match response {
+                Ok(response) => {
+                    let status = response.status();
+                    if let Some(retry_after) = rate_limit(status, response.headers()) {
+                        debug!("Rate limited by {} for {:?}", host, retry_after);
+                        self.rate_limits
+                            .lock()
+                            .unwrap()
+                            .insert(host.clone(), Instant::now() + retry_after);
+                        if status == StatusCode::TOO_MANY_REQUESTS {
+                            return Err(HttpError::RateLimited { host, retry_after });
+                        }
+                    }
+                    if status.is_server_error() {
+                        HttpError::Status {
+                            host: host.clone(),
+                            status,
+                        }
+                    } else if status.is_redirection()
+                        || (status.is_client_error() && !accept_client_errors)
+                    {
+                        return Err(HttpError::Status { host, status });
+                    } else {
+                        let body = response.text().await.map_err(HttpError::Request)?;
+                        if let Some(key) = cache_key.filter(|_| status.is_success()) {
+                            self.cache.insert(key, body.clone(), cache_ttl, cache_size);
+                        }
+                        return Ok(body);
+                    }
+                }
+                Err(e) if e.is_timeout() => return Err(HttpError::Timeout { host }),
+                Err(e) if e.is_connect() => HttpError::Request(e),
+                Err(e) => return Err(HttpError::Request(e)),
+            };
+            if attempt >= retries {
+                return Err(error);
+            }
+            let delay = backoff(attempt);
+            warn!("Retrying request to {} in {:?}: {}", host, delay, error);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// How much longer a host is rate limited for, if it is.
+    fn rate_limited_for(&self, host: &str) -> Option<Duration> {
+        let mut rate_limits = self.rate_limits.lock().unwrap();
+        let until = *rate_limits.get(host)?;
+        match until.checked_duration_since(Instant::now()) {
+            Some(remaining) if !remaining.is_zero() => Some(remaining),
+            _ => {
+                rate_limits.remove(host);
+                None
+            }
+        }
+    }
+}
+
+/// Cache key of a GraphQL query.
+fn graphql_key<V: serde::Serialize>(
+    endpoint: &Url,
+    body: &graphql_client::QueryBody<V>,
+) -> Result<String, HttpError> {
+    Ok(format!(
+        "POST {} {}",
+        endpoint,
+        serde_json::to_string(body).map_err(HttpError::Decode)?
+    ))
+}
+
+/// Longest a request may take when every attempt times out, including the delays between them.
+pub(crate) fn longest_request(timeout: Duration, retries: u32) -> Duration {
+    (0..retries).fold(timeout * (retries + 1), |total, attempt| {
+        total + backoff(attempt)
+    })
+}
+
+/// Delay before retry number `attempt`, counting from zero.
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF.saturating_mul(2u32.checked_pow(attempt).unwrap_or(u32::MAX))
+}
+
+/// How long to wait before the next request, if a response says the rate limit was reached.
+///
+/// AniList answers with 429 and `Retry-After` once the limit is exceeded, and counts down the
+/// requests left in `X-RateLimit-Remaining` before that.
+fn rate_limit(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let exhausted = header("x-ratelimit-remaining") == Some(0);
+    if status != StatusCode::TOO_MANY_REQUESTS && !exhausted {
+        return None;
+    }
+    let retry_after = header(RETRY_AFTER.as_str())
+        .map(Duration::from_secs)
+        .or_else(|| {
+            let reset = UNIX_EPOCH + Duration::from_secs(header("x-ratelimit-reset")?);
+            reset.duration_since(SystemTime::now()).ok()
+        })
+        .unwrap_or(DEFAULT_RETRY_AFTER);
+    Some(retry_after)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::reload;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::config::Config;
+
+    fn client(retries: u32) -> HttpClient {
+        let mut config = Config::default();
+        config.http.retries = retries;
+        let (_, log_filter) = reload::Layer::new(Targets::new());
+        HttpClient::new(
+            ConfigHandle::new(config, log_filter),
+            Arc::new(ResponseCache::default()),
+        )
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    reqwest::header::HeaderName::from_static(name),
+                    HeaderValue::from_str(value).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn backoff_doubles_and_saturates() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert!(backoff(31) > backoff(30));
+        assert_eq!(backoff(32), backoff(u32::MAX));
+    }
+
+    #[test]
+    fn rate_limit_reads_the_headers() {
+        assert_eq!(rate_limit(StatusCode::OK, &HeaderMap::new()), None);
+        assert_eq!(
+            rate_limit(StatusCode::OK, &headers(&[("x-ratelimit-remaining", "5")])),
+            None
+        );
+        assert_eq!(
+            rate_limit(
+                StatusCode::TOO_MANY_REQUESTS,
+                &headers(&[("retry-after", " 30 ")])
+            ),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            rate_limit(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new()),
+            Some(DEFAULT_RETRY_AFTER)
+        );
+        let reset = SystemTime::now() + Duration::from_secs(120);
+        let reset = reset.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let retry_after = rate_limit(
+            StatusCode::OK,
+            &headers(&[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", &reset.to_string()),
+            ]),
+        )
+        .unwrap();
+        assert!(retry_after > Duration::from_secs(100) && retry_after <= Duration::from_secs(120));
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_stops_further_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "30"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = client(2);
+        let url = Url::parse(&server.uri()).unwrap();
+
+        for _ in 0..2 {
+            let error = client
+                .get_json::<serde_json::Value>(&url, &[])
+                .await
+                .unwrap_err();
+            match error {
+                HttpError::RateLimited { retry_after, .. } => {
+                    assert!(retry_after <= Duration::from_secs(30))
+                }
+                error => panic!("Expected a rate limit, got {:?}", error),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).unwrap();
+
+        let error = client(1)
+            .get_json::<serde_json::Value>(&url, &[])
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, HttpError::Status { status, .. } if status == StatusCode::SERVICE_UNAVAILABLE)
+        );
+    }
+
+    #[tokio::test]
+    async fn redirects_are_not_followed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(302).insert_header("Location", "http://169.254.169.254/"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let url = Url::parse(&server.uri()).unwrap();
+
+        let error = client(1)
+            .get_json::<serde_json::Value>(&url, &[])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, HttpError::Status { status, .. } if status == StatusCode::FOUND));
+    }
+}
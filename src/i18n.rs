@@ -0,0 +1,373 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use lazy_static::lazy_static;
+use poise::Command;
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+use crate::{Data, Error};
+
+/// Locale used for anything missing from the invoker's locale.
+pub const FALLBACK_LOCALE: &str = "en-US";
+
+/// The bundled translations, by Discord locale.
+const SOURCES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.ftl")),
+    ("es-ES", include_str!("../locales/es-ES.ftl")),
+    ("ja", include_str!("../locales/ja.ftl")),
+];
+
+lazy_static! {
+    static ref TRANSLATIONS: Translations =
+        Translations::parse(SOURCES).expect("Failed to load the bundled translations");
+}
+
+/// Fluent bundles for every locale the bot has translations for.
+pub struct Translations {
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+}
+
+impl Translations {
+    /// Parses `(locale, ftl)` pairs into one bundle per locale.
+    pub fn parse(sources: &[(&str, &str)]) -> Result<Self> {
+        let mut bundles = HashMap::new();
+        for (locale, source) in sources {
+            let id: LanguageIdentifier = locale
+                .parse()
+                .map_err(|e| anyhow!("Invalid locale {}: {}", locale, e))?;
+            let resource = FluentResource::try_new(source.to_string()).map_err(|(_, errors)| {
+                anyhow!("Invalid translations for {}: {:?}", locale, errors)
+            })?;
+            let mut bundle = FluentBundle::new_concurrent(vec![id]);
+            // The isolation marks would end up in Discord messages, where nothing needs them.
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .map_err(|errors| anyhow!("Duplicate translations for {}: {:?}", locale, errors))?;
+            bundles.insert(locale.to_string(), bundle);
+        }
+        Ok(Translations { bundles })
+    }
+
+    /// Bundles to look a message up in for `locale`: its own, one of the same language, then the
+    /// fallback.
+    fn candidates<'a>(
+        &'a self,
+        locale: &'a str,
+    ) -> impl Iterator<Item = &'a FluentBundle<FluentResource>> + 'a {
+        let language = locale.split('-').next().unwrap_or(locale);
+        let same_language = self
+            .bundles
+            .iter()
+            .filter(move |(other, _)| {
+                other.as_str() != locale && other.split('-').next() == Some(language)
+            })
+            .map(|(_, bundle)| bundle);
+        self.bundles
+            .get(locale)
+            .into_iter()
+            .chain(same_language)
+            .chain(self.bundles.get(FALLBACK_LOCALE))
+    }
+
+    /// Formats message `id`, or its attribute if `id` is written as `message.attribute`.
+    pub fn get(&self, locale: &str, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        self.candidates(locale)
+            .find_map(|bundle| format(bundle, id, args))
+    }
+
+    /// Formats message `id` only if `locale` itself translates it, without falling back.
+    fn exact(&self, locale: &str, id: &str) -> Option<String> {
+        format(self.bundles.get(locale)?, id, None)
+    }
+
+    /// Locales with translations other than the fallback.
+    fn translated_locales(&self) -> impl Iterator<Item = &str> {
+        self.bundles
+            .keys()
+            .map(String::as_str)
+            .filter(|locale| *locale != FALLBACK_LOCALE)
+    }
+
+    /// Adds the translated names and descriptions of `commands`, their subcommands and
+    /// parameters, so Discord shows them in the user's language.
+    ///
+    /// A command's message is `cmd-` and its qualified name with dashes, such as `cmd-prefix-set`.
+    /// Its value is the name, its `description` attribute the description, and an attribute named
+    /// after a parameter describes that parameter.
+    pub fn localize_commands(&self, commands: &mut [Command<Data, Error>]) {
+        self.localize_subcommands("cmd", commands);
+    }
+
+    // Qualified names are only filled in once the framework starts, so the message ID is built
+    // from the parents instead.
+    fn localize_subcommands(&self, parent: &str, commands: &mut [Command<Data, Error>]) {
+        for command in commands {
+            let id = format!("{}-{}", parent, command.name);
+            for locale in self.translated_locales() {
+                if let Some(name) = self.exact(locale, &id) {
+                    command.name_localizations.insert(locale.to_owned(), name);
+                }
+                if let Some(description) = self.exact(locale, &format!("{}.description", id)) {
+                    command
+                        .description_localizations
+                        .insert(locale.to_owned(), description);
+                }
+                for parameter in &mut command.parameters {
+                    let key = format!("{}.{}", id, parameter.name);
+                    if let Some(description) = self.exact(locale, &key) {
+                        parameter
+                            .description_localizations
+                            .insert(locale.to_owned(), description);
+                    }
+                }
+            }
+            // Discord needs a default description, which otherwise is only set for `en-US`.
+            if command.description.is_none() {
+                command.description = command
+                    .description_localizations
+                    .get(FALLBACK_LOCALE)
+                    .cloned();
+            }
+            self.localize_subcommands(&id, &mut command.subcommands);
+        }
+    }
+}
+
+fn format(
+    bundle: &FluentBundle<FluentResource>,
+    id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let (message, attribute) = match id.split_once('.') {
+        Some((message, attribute)) => (message, Some(attribute)),
+        None => (id, None),
+    };
+    let message = bundle.get_message(message)?;
+    let pattern = match attribute {
+        Some(attribute) => message.get_attribute(attribute)?.value(),
+        None => message.value()?,
+    };
+    let mut errors = vec![];
+    let text = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        warn!("Errors formatting translation {}: {:?}", id, errors);
+    }
+    Some(text.into_owned())
+}
+
+/// The bundled translations.
+pub fn translations() -> &'static Translations {
+    &TRANSLATIONS
+}
+
+/// Formats message `id` for `locale` with the named `args`, falling back to English.
+///
+/// Returns `id` itself if not even the fallback has the message.
+pub fn tr(locale: &str, id: &str, args: &[(&str, FluentValue)]) -> String {
+    let args = args
+        .iter()
+        .map(|(name, value)| (*name, value.clone()))
+        .collect::<FluentArgs>();
+    translations()
+        .get(locale, id, Some(&args))
+        .unwrap_or_else(|| {
+            warn!("Missing translation {}", id);
+            id.to_owned()
+        })
+}
+
+/// Formats `num` as an ordinal number such as "3rd" in `locale`.
+pub fn ordinal(locale: &str, num: i64) -> String {
+    tr(
+        locale,
+        "ordinal",
+        &[
+            ("number", num.into()),
+            ("category", ordinal_category(num).into()),
+        ],
+    )
+}
+
+/// The English ordinal plural category of `num`, one for "1st", two for "2nd", few for "3rd" and
+/// other for "4th", which the `ordinal` message picks the suffix by.
+fn ordinal_category(num: i64) -> &'static str {
+    match (num % 10, num % 100) {
+        (_, 11..=13) => "other",
+        (1, _) => "one",
+        (2, _) => "two",
+        (3, _) => "few",
+        _ => "other",
+    }
+}
+
+/// Formats a date such as "March 5" or "March 5, 1990" in `locale`, leaving out the day or year if
+/// they are unknown. Returns `None` for months outside 1 to 12.
+pub fn format_date(
+    locale: &str,
+    year: Option<i64>,
+    month: i64,
+    day: Option<i64>,
+) -> Option<String> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let month = tr(locale, "month", &[("month", month.into())]);
+    Some(match (day, year) {
+        (None, None) => month,
+        (Some(day), None) => tr(
+            locale,
+            "date-month-day",
+            &[("month", month.into()), ("day", day.into())],
+        ),
+        (None, Some(year)) => tr(
+            locale,
+            "date-month-year",
+            &[("month", month.into()), ("year", year.into())],
+        ),
+        (Some(day), Some(year)) => tr(
+            locale,
+            "date-full",
+            &[
+                ("month", month.into()),
+                ("day", day.into()),
+                ("year", year.into()),
+            ],
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_by_language_then_to_english() {
+        let translations = Translations::parse(&[
+            ("en-US", "hello = Hello, { $name }!\nbye = Bye!"),
+            ("es-ES", "hello = ¡Hola, { $name }!"),
+        ])
+        .unwrap();
+        let args = std::iter::once(("name", "Ainz")).collect::<FluentArgs>();
+        let hello = |locale| translations.get(locale, "hello", Some(&args));
+        assert_eq!(hello("es-ES").as_deref(), Some("¡Hola, Ainz!"));
+        assert_eq!(hello("es-419").as_deref(), Some("¡Hola, Ainz!"));
+        assert_eq!(hello("ko").as_deref(), Some("Hello, Ainz!"));
+        assert_eq!(
+            translations.get("es-ES", "bye", None).as_deref(),
+            Some("Bye!")
+        );
+        assert_eq!(translations.get("es-ES", "missing", None), None);
+    }
+
+    #[test]
+    fn ordinals() {
+        let ordinals = [1, 2, 3, 4, 11, 12, 13, 21, 22, 111]
+            .iter()
+            .map(|n| ordinal("en-US", *n))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ordinals,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "111th"]
+        );
+    }
+
+    #[test]
+    fn dates_leave_out_unknown_parts() {
+        assert_eq!(
+            format_date("en-US", None, 3, None).as_deref(),
+            Some("March")
+        );
+        assert_eq!(
+            format_date("en-US", None, 3, Some(5)).as_deref(),
+            Some("March 5")
+        );
+        assert_eq!(
+            format_date("en-US", Some(1990), 3, None).as_deref(),
+            Some("March, 1990")
+        );
+        assert_eq!(
+            format_date("en-US", Some(1990), 12, Some(5)).as_deref(),
+            Some("December 5, 1990")
+        );
+        assert_eq!(format_date("en-US", None, 13, Some(5)), None);
+    }
+
+    /// The ids of the replies in `source`, leaving out the `cmd-` messages, which English keeps in
+    /// the source code instead.
+    fn reply_ids(source: &str) -> Vec<String> {
+        let resource = FluentResource::try_new(source.to_string()).unwrap();
+        resource
+            .entries()
+            .filter_map(|entry| match entry {
+                fluent_syntax::ast::Entry::Message(message) => Some(message.id.name.to_owned()),
+                _ => None,
+            })
+            .filter(|id| !id.starts_with("cmd-"))
+            .collect()
+    }
+
+    #[test]
+    fn bundled_translations_cover_every_reply() {
+        let english = reply_ids(SOURCES[0].1);
+        for (locale, source) in &SOURCES[1..] {
+            let translated = reply_ids(source);
+            let missing = english
+                .iter()
+                .filter(|id| !translated.contains(id))
+                .collect::<Vec<_>>();
+            assert!(missing.is_empty(), "{} lacks {:?}", locale, missing);
+            let unknown = translated
+                .iter()
+                .filter(|id| !english.contains(id))
+                .collect::<Vec<_>>();
+            assert!(
+                unknown.is_empty(),
+                "{} translates {:?}, which English lacks",
+                locale,
+                unknown
+            );
+        }
+    }
+
+    #[test]
+    fn localizes_commands_and_parameters() {
+        let mut commands = vec![crate::commands::prefix::prefix()];
+        translations().localize_commands(&mut commands);
+        let prefix = &commands[0];
+        assert_eq!(
+            prefix.description.as_deref(),
+            Some("Shows or changes the command prefix for this server.")
+        );
+        assert_eq!(prefix.name_localizations["ja"], "プレフィックス");
+        let set = &prefix.subcommands[0];
+        assert_eq!(set.name_localizations["es-ES"], "establecer");
+        assert!(set.parameters[0]
+            .description_localizations
+            .contains_key("es-ES"));
+        for command in &commands {
+            for name in command.name_localizations.values() {
+                assert!(name.chars().count() <= 32 && !name.contains(char::is_uppercase));
+            }
+        }
+    }
+}
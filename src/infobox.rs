@@ -0,0 +1,371 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use lazy_static::lazy_static;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+
+lazy_static! {
+    static ref INFOBOX: Selector = Selector::parse("aside.portable-infobox").unwrap();
+    static ref DATA: Selector = Selector::parse(".pi-data").unwrap();
+    static ref LABEL: Selector = Selector::parse(".pi-data-label").unwrap();
+    static ref VALUE: Selector = Selector::parse(".pi-data-value").unwrap();
+    static ref LINK: Selector = Selector::parse("a[href]").unwrap();
+}
+
+/// The fields of a VTuber wiki article's portable infobox that are worth showing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Infobox {
+    pub original_name: Option<String>,
+    pub affiliation: Option<String>,
+    pub debut_date: Option<String>,
+    pub birthday: Option<String>,
+    pub height: Option<String>,
+    pub fan_name: Option<String>,
+    /// Name and URL of each linked channel.
+    pub channels: Vec<(String, Url)>,
+}
+
+impl Infobox {
+    /// Parses the first portable infobox in an article's HTML, resolving relative links against
+    /// `base`. Returns `None` if the article has no infobox.
+    pub fn parse(html: &str, base: &Url) -> Option<Infobox> {
+        let document = Html::parse_fragment(html);
+        let aside = document.select(&INFOBOX).next()?;
+        let mut infobox = Infobox::default();
+        for data in aside.select(&DATA) {
+            let value = match data.select(&VALUE).next() {
+                Some(value) => value,
+                None => continue,
+            };
+            // Editors don't always use the same source names, so fall back to the label.
+            let label = data.select(&LABEL).next().map(text).unwrap_or_default();
+            let key = match data.value().attr("data-source") {
+                Some(source) if is_known(source) => source.to_owned(),
+                _ => label.to_lowercase().replace(' ', "_"),
+            };
+            let slot = match key.as_str() {
+                "original_name" | "jp_name" | "japanese_name" => &mut infobox.original_name,
+                "affiliation" => &mut infobox.affiliation,
+                "debut_date" | "debut" => &mut infobox.debut_date,
+                "birthday" => &mut infobox.birthday,
+                "height" => &mut infobox.height,
+                "fan_name" => &mut infobox.fan_name,
+                "channel" | "channels" => {
+                    infobox.channels.extend(links(value, base));
+                    continue;
+                }
+                _ => continue,
+            };
+            let value = text(value);
+            if slot.is_none() && !value.is_empty() {
+                *slot = Some(value);
+            }
+        }
+        Some(infobox)
+    }
+
+    /// The birthday, if it is given as a date.
+    pub fn birthday_date(&self) -> Option<WikiDate> {
+        WikiDate::parse(self.birthday.as_deref()?)
+    }
+
+    /// The debut, if it is given as a date.
+    pub fn debut(&self) -> Option<WikiDate> {
+        WikiDate::parse(self.debut_date.as_deref()?)
+    }
+
+    /// Name and value of every field that is present, in display order.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        for (name, value) in [
+            ("Original Name", &self.original_name),
+            ("Affiliation", &self.affiliation),
+            ("Debut Date", &self.debut_date),
+            ("Birthday", &self.birthday),
+            ("Height", &self.height),
+            ("Fan Name", &self.fan_name),
+        ] {
+            if let Some(value) = value {
+                fields.push((name, value.clone()));
+            }
+        }
+        if !self.channels.is_empty() {
+            let channels = self
+                .channels
+                .iter()
+                .map(|(name, url)| format!("[{}]({})", name, url))
+                .collect::<Vec<_>>()
+                .join("\n");
+            fields.push(("Channels", channels));
+        }
+        fields
+    }
+}
+
+/// A day of the year as written on the wiki, with the year if it is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WikiDate {
+    pub year: Option<i32>,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl WikiDate {
+    /// Parses dates such as "June 20", "September 13, 2020" or "13 September 2020", ignoring
+    /// anything after them.
+    pub fn parse(text: &str) -> Option<WikiDate> {
+        let words = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .take(3)
+            .collect::<Vec<_>>();
+        let (month, day) = match (
+            words.first().and_then(|word| month(word)),
+            words.get(1).and_then(|word| month(word)),
+        ) {
+            (Some(month), _) => (month, day(words.get(1)?)?),
+            (None, Some(month)) => (month, day(words.first()?)?),
+            (None, None) => return None,
+        };
+        let year = words
+            .get(2)
+            .filter(|word| word.len() == 4)
+            .and_then(|word| word.parse().ok());
+        // Rejects days that don't exist in any year, such as February 30th.
+        chrono::NaiveDate::from_ymd_opt(2000, month, day)?;
+        Some(WikiDate { year, month, day })
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+fn month(word: &str) -> Option<u32> {
+    let word = word.trim_end_matches('.').to_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| *month == word || (word.len() >= 3 && month.starts_with(&word)))
+        .map(|index| index as u32 + 1)
+}
+
+/// A day of the month, allowing an ordinal suffix such as "20th".
+fn day(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '.');
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn is_known(source: &str) -> bool {
+    matches!(
+        source,
+        "original_name"
+            | "jp_name"
+            | "japanese_name"
+            | "affiliation"
+            | "debut_date"
+            | "debut"
+            | "birthday"
+            | "height"
+            | "fan_name"
+            | "channel"
+            | "channels"
+    )
+}
+
+/// The text of `element` without footnote markers, one line per `<br>` and with whitespace
+/// collapsed.
+fn text(element: ElementRef) -> String {
+    let mut raw = String::new();
+    for node in element.descendants() {
+        let in_footnote = node
+            .ancestors()
+            .any(|ancestor| matches!(ancestor.value(), Node::Element(e) if e.name() == "sup"));
+        if in_footnote {
+            continue;
+        }
+        match node.value() {
+            Node::Text(text) => raw.push_str(text),
+            Node::Element(e) if e.name() == "br" => raw.push('\n'),
+            _ => {}
+        }
+    }
+    raw.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn links(element: ElementRef, base: &Url) -> Vec<(String, Url)> {
+    element
+        .select(&LINK)
+        .filter_map(|link| {
+            let url = base.join(link.value().attr("href")?).ok()?;
+            let name = text(link);
+            Some((
+                if name.is_empty() {
+                    url.to_string()
+                } else {
+                    name
+                },
+                url,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://virtualyoutuber.fandom.com/wiki/").unwrap()
+    }
+
+    #[test]
+    fn parses_every_field() {
+        let html = include_str!("../tests/fixtures/wiki/gawr_gura.html");
+
+        let infobox = Infobox::parse(html, &base()).unwrap();
+
+        assert_eq!(infobox.original_name.as_deref(), Some("がうる・ぐら"));
+        assert_eq!(infobox.affiliation.as_deref(), Some("hololive (English)"));
+        assert_eq!(infobox.debut_date.as_deref(), Some("September 13, 2020"));
+        assert_eq!(infobox.birthday.as_deref(), Some("June 20"));
+        assert_eq!(infobox.height.as_deref(), Some("141 cm (4'7\")"));
+        assert_eq!(infobox.fan_name.as_deref(), Some("chumbuds"));
+        assert_eq!(
+            infobox.channels,
+            vec![
+                (
+                    "YouTube".to_owned(),
+                    Url::parse("https://www.youtube.com/channel/UCoSrY_IQQVpmIRZ9Xf-y93g").unwrap()
+                ),
+                (
+                    "Twitter".to_owned(),
+                    Url::parse("https://twitter.com/gawrgura").unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_labels_and_resolves_wiki_links() {
+        let html = include_str!("../tests/fixtures/wiki/kizuna_ai.html");
+
+        let infobox = Infobox::parse(html, &base()).unwrap();
+
+        assert_eq!(infobox.original_name.as_deref(), Some("キズナアイ"));
+        assert_eq!(
+            infobox.affiliation.as_deref(),
+            Some("Kizuna AI Inc.\nUpd8 (formerly)")
+        );
+        assert_eq!(infobox.debut_date.as_deref(), Some("November 29, 2016"));
+        assert_eq!(infobox.height.as_deref(), Some("156 cm"));
+        assert_eq!(infobox.fan_name, None);
+        assert_eq!(
+            infobox.channels,
+            vec![(
+                "A.I.Channel".to_owned(),
+                Url::parse("https://virtualyoutuber.fandom.com/wiki/A.I.Channel").unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn lists_present_fields_in_order() {
+        let html = include_str!("../tests/fixtures/wiki/kizuna_ai.html");
+
+        let fields = Infobox::parse(html, &base()).unwrap().fields();
+
+        let names = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Original Name",
+                "Affiliation",
+                "Debut Date",
+                "Birthday",
+                "Height",
+                "Channels"
+            ]
+        );
+        assert_eq!(
+            fields[5].1,
+            "[A.I.Channel](https://virtualyoutuber.fandom.com/wiki/A.I.Channel)"
+        );
+    }
+
+    #[test]
+    fn parses_dates() {
+        let date = |year, month, day| Some(WikiDate { year, month, day });
+
+        assert_eq!(WikiDate::parse("June 20"), date(None, 6, 20));
+        assert_eq!(
+            WikiDate::parse("September 13, 2020"),
+            date(Some(2020), 9, 13)
+        );
+        assert_eq!(
+            WikiDate::parse("13 September 2020 (YouTube)"),
+            date(Some(2020), 9, 13)
+        );
+        assert_eq!(WikiDate::parse("Dec. 1st"), date(None, 12, 1));
+        assert_eq!(WikiDate::parse("February 29"), date(None, 2, 29));
+        assert_eq!(WikiDate::parse("February 30"), None);
+        assert_eq!(WikiDate::parse("Unknown"), None);
+    }
+
+    #[test]
+    fn dates_from_the_infobox() {
+        let html = include_str!("../tests/fixtures/wiki/gawr_gura.html");
+
+        let infobox = Infobox::parse(html, &base()).unwrap();
+
+        assert_eq!(
+            infobox.birthday_date(),
+            Some(WikiDate {
+                year: None,
+                month: 6,
+                day: 20
+            })
+        );
+        assert_eq!(
+            infobox.debut(),
+            Some(WikiDate {
+                year: Some(2020),
+                month: 9,
+                day: 13
+            })
+        );
+    }
+
+    #[test]
+    fn articles_without_an_infobox() {
+        assert_eq!(Infobox::parse("<p>Just text.</p>", &base()), None);
+    }
+}
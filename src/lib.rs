@@ -0,0 +1,45 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+pub mod airing;
+pub mod alerts;
+pub mod cache;
+pub mod commands;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod http;
+pub mod i18n;
+pub mod infobox;
+pub mod markdown;
+pub mod mediawiki;
+pub mod pagination;
+pub mod reply;
+pub mod shutdown;
+pub mod util;
+pub mod vtuber;
+pub mod watcher;
+
+use poise::serenity_prelude as serenity;
+
+pub use util::Data;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Context<'a> = poise::Context<'a, Data, Error>;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const BOT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
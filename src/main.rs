@@ -14,6 +14,7 @@
  *    limitations under the License.
  */
 
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,21 +22,29 @@ use std::time::Duration;
 use chrono::Utc;
 use dotenv::dotenv;
 use poise::{serenity_prelude as serenity, Event};
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 use tracing::log::trace;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload};
 
-use util::Data;
+use lupusregina::alerts::{self, AlertSink};
+use lupusregina::cache::{self, ResponseCache};
+use lupusregina::config::unknown_env_overrides;
+use lupusregina::db::restart::{self, RestartNotice};
+use lupusregina::db::{guild_settings, Database};
+use lupusregina::error::{self, BotError};
+use lupusregina::http::HttpClient;
+use lupusregina::shutdown::{self, Flush, Mode, Shutdown};
+use lupusregina::util::{get_configuration, ConfigHandle};
+use lupusregina::{airing, commands, i18n, vtuber, watcher, Context, Data, Error};
 
-use crate::util::get_configuration;
-
-pub mod commands;
-pub mod util;
-
-type Error = Box<dyn std::error::Error + Send + Sync>;
-type Context<'a> = poise::Context<'a, Data, Error>;
-
-#[poise::command(prefix_command, hide_in_help, owners_only)]
+#[poise::command(
+    prefix_command,
+    hide_in_help,
+    category = "owner",
+    check = "commands::owner::is_owner"
+)]
 async fn register(ctx: Context<'_>) -> Result<(), Error> {
     poise::builtins::register_application_commands_buttons(ctx).await?;
     Ok(())
@@ -48,10 +57,41 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     match error {
         poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {:?}", error),
         poise::FrameworkError::Command { error, ctx } => {
-            error!("Error in command `{}`: {:?}", ctx.command().name, error,);
-            let _ = ctx.send(|m| m.content("Error processing command or no results were returned by the remote server.").ephemeral(true)).await;
+            ctx.data().shutdown.finished(ctx.id());
+            error::report(ctx, BotError::from(error)).await;
+        }
+        poise::FrameworkError::ArgumentParse { error, input, ctx } => {
+            ctx.data().shutdown.finished(ctx.id());
+            let reason = match input {
+                Some(input) => format!("`{}`: {}", input, error),
+                None => error.to_string(),
+            };
+            error::report(ctx, BotError::InvalidInput(reason)).await;
+        }
+        poise::FrameworkError::MissingUserPermissions {
+            missing_permissions: Some(permissions),
+            ctx,
+        } => {
+            let error = BotError::MissingPermission {
+                permissions,
+                bot: false,
+            };
+            error::report(ctx, error).await;
+        }
+        poise::FrameworkError::MissingBotPermissions {
+            missing_permissions,
+            ctx,
+        } => {
+            let error = BotError::MissingPermission {
+                permissions: missing_permissions,
+                bot: true,
+            };
+            error::report(ctx, error).await;
         }
         error => {
+            if let Some(ctx) = error.ctx() {
+                ctx.data().shutdown.finished(ctx.id());
+            }
             if let Err(e) = poise::builtins::on_error(error).await {
                 error!("Error while handling error: {}", e)
             }
@@ -59,55 +99,114 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     }
 }
 
-const VERSION: &str = env!("CARGO_PKG_VERSION");
-const BOT_NAME: &str = env!("CARGO_PKG_NAME");
-const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
+/// Keeps the framework's cooldown handlers in line with the currently loaded configuration.
+///
+/// Subcommands share the cooldown configured for their top-level command.
+async fn apply_cooldowns(ctx: Context<'_>) -> Result<bool, Error> {
+    let command = ctx.command();
+    let top_level = command
+        .qualified_name
+        .split(' ')
+        .next()
+        .unwrap_or(&command.name);
+    let cooldown = ctx.data().config.read().await.cooldown(top_level);
+    let mut applied = ctx.data().applied_cooldowns.lock().unwrap();
+    if applied.get(&command.qualified_name) != Some(&cooldown) {
+        *command.cooldowns.lock().unwrap() = poise::Cooldowns::new(cooldown.clone());
+        applied.insert(command.qualified_name.clone(), cooldown);
+    }
+    Ok(true)
+}
+
+async fn command_check(ctx: Context<'_>) -> Result<bool, Error> {
+    if ctx.data().shutdown.is_stopping() {
+        let locale = commands::settings::reply_locale(ctx).await;
+        let message = i18n::tr(&locale, "shutdown-refused", &[]);
+        ctx.send(|m| m.content(message).ephemeral(true)).await?;
+        return Ok(false);
+    }
+    apply_cooldowns(ctx).await?;
+    commands::settings::category_enabled(ctx).await
+}
 
 #[tokio::main]
+#[allow(clippy::result_large_err)]
 async fn main() -> Result<(), poise::serenity_prelude::Error> {
     dotenv().expect("Failed to load .env file!");
 
-    tracing_subscriber::fmt::init();
+    let config = get_configuration().unwrap_or_else(|e| panic!("Failed to load config: {}", e));
+    let (log_filter, log_filter_handle) = reload::Layer::new(config.logging.filter());
+    tracing_subscriber::registry()
+        .with(log_filter)
+        .with(fmt::layer())
+        .init();
+    for name in unknown_env_overrides() {
+        warn!("Ignoring {}, it doesn't name a configuration key", name);
+    }
+    let config = ConfigHandle::new(config, log_filter_handle);
+
+    let mut commands = vec![
+        register(),
+        commands::help::help(),
+        commands::general::ping(),
+        commands::general::about(),
+        commands::general::guildinfo(),
+        commands::general::userinfo(),
+        commands::owner::info(),
+        commands::alerts::alerts(),
+        commands::owner::flushcache(),
+        commands::owner::nickname(),
+        commands::owner::presence(),
+        commands::owner::reload(),
+        commands::owner::rename(),
+        commands::owner::setavatar(),
+        commands::owner::shutdown(),
+        commands::owner::restart(),
+        commands::prefix::prefix(),
+        commands::settings::settings(),
+        commands::weeb::anime(),
+        commands::weeb::manga(),
+        commands::weeb::character(),
+        commands::weeb::staff(),
+        commands::weeb::studio(),
+        commands::airing::airing(),
+        commands::anilist::anilist(),
+        commands::season::season(),
+        commands::vtuber::vtuber(),
+        commands::wiki::wiki(),
+        commands::wiki::wikis(),
+        commands::fun::bloodborne(),
+        commands::fun::darksouls(),
+        commands::fun::darksouls3(),
+        commands::fun::eightball(),
+        commands::fun::ddate(),
+    ];
+    i18n::translations().localize_commands(&mut commands);
 
     let options = poise::FrameworkOptions {
-        commands: vec![
-            register(),
-            commands::general::ping(),
-            commands::general::about(),
-            commands::general::guildinfo(),
-            commands::general::userinfo(),
-            commands::owner::info(),
-            commands::owner::nickname(),
-            commands::owner::presence(),
-            commands::owner::reload(),
-            commands::owner::rename(),
-            commands::owner::setavatar(),
-            commands::weeb::anime(),
-            commands::weeb::manga(),
-            commands::weeb::vtuber(),
-            commands::fun::bloodborne(),
-            commands::fun::darksouls(),
-            commands::fun::darksouls3(),
-            commands::fun::eightball(),
-            commands::fun::ddate(),
-        ],
+        commands,
         prefix_options: poise::PrefixFrameworkOptions {
-            prefix: Some("~".into()),
+            dynamic_prefix: Some(|ctx| Box::pin(commands::prefix::dynamic_prefix(ctx))),
+            stripped_dynamic_prefix: Some(commands::prefix::strip_additional_prefix),
+            mention_as_prefix: true,
             edit_tracker: Some(poise::EditTracker::for_timespan(Duration::from_secs(3600))),
             ..Default::default()
         },
-        /// The global error handler for all error cases that may occur
+        // The global error handler for all error cases that may occur
         on_error: |error| Box::pin(on_error(error)),
-        /// This code is run before every command
+        command_check: Some(|ctx| Box::pin(command_check(ctx))),
+        // This code is run before every command
         pre_command: |ctx| {
             Box::pin(async move {
                 trace!("Executing command {}...", ctx.command().qualified_name);
+                ctx.data().shutdown.started(ctx.id());
             })
         },
-        /// This code is run after a command if it was successful (returned Ok)
+        // This code is run after a command if it was successful (returned Ok)
         post_command: |ctx| {
             Box::pin(async move {
                 trace!("Executed command {}!", ctx.command().qualified_name);
+                ctx.data().shutdown.finished(ctx.id());
             })
         },
         listener: |_ctx, event, _framework, _data| {
@@ -125,14 +224,58 @@ async fn main() -> Result<(), poise::serenity_prelude::Error> {
         ..Default::default()
     };
 
+    let shutdown = Arc::new(Shutdown::default());
+    let data_shutdown = shutdown.clone();
     poise::Framework::builder()
         .token(env::var("BOT_TOKEN").expect("Missing `BOT_TOKEN` env var."))
-        .user_data_setup(move |_ctx, _ready, framework| {
+        .user_data_setup(move |ctx, _ready, framework| {
             Box::pin(async move {
+                let db = Database::open_default()?;
+                let guild_settings = db.run(guild_settings::load_all).await?;
+                if let Err(e) = watcher::spawn_config_watcher(config.clone()) {
+                    error!("Unable to watch the configuration for changes: {}", e);
+                }
+                let restart_notice = take_restart_notice(&db).await;
+                let cache = Arc::new(load_cache(
+                    config.read().await.cache.persist,
+                    restart_notice.is_some(),
+                ));
+                cache::spawn_cache_saver(cache.clone(), config.clone());
+                let http = HttpClient::new(config.clone(), cache.clone());
+                airing::spawn_airing_notifier(
+                    ctx.http.clone(),
+                    http.clone(),
+                    db.clone(),
+                    config.clone(),
+                );
+                vtuber::spawn_vtuber_announcer(ctx.http.clone(), db.clone(), config.clone());
+                let alerts = Arc::new(AlertSink::default());
+                alerts::spawn_alert_sender(ctx.http.clone(), db.clone(), alerts.clone());
+                shutdown::spawn_shutdown_handler(
+                    data_shutdown.clone(),
+                    framework.shard_manager().clone(),
+                    Flush {
+                        http: ctx.http.clone(),
+                        db: db.clone(),
+                        cache: cache.clone(),
+                        alerts: alerts.clone(),
+                        config: config.clone(),
+                    },
+                );
+                if let Some(notice) = restart_notice {
+                    report_restart(&ctx.http, notice).await;
+                }
                 Ok(Data {
-                    config: Mutex::new(get_configuration()?),
+                    config,
                     uptime: Arc::new(Utc::now()),
                     shard_manager: framework.shard_manager().clone(),
+                    applied_cooldowns: std::sync::Mutex::new(HashMap::new()),
+                    db,
+                    guild_settings: RwLock::new(guild_settings),
+                    title_autocomplete: Default::default(),
+                    http,
+                    alerts,
+                    shutdown: data_shutdown,
                 })
             })
         })
@@ -141,5 +284,58 @@ async fn main() -> Result<(), poise::serenity_prelude::Error> {
             serenity::GatewayIntents::non_privileged() | serenity::GatewayIntents::MESSAGE_CONTENT,
         )
         .run()
-        .await
+        .await?;
+
+    if shutdown.mode() == Some(Mode::Restart) {
+        info!("Restarting");
+        let e = shutdown::restart_process();
+        error!("Failed to restart: {}", e);
+        // Otherwise the next start would report a restart that didn't happen.
+        match Database::open_default() {
+            Ok(db) => {
+                take_restart_notice(&db).await;
+            }
+            Err(e) => warn!("Unable to clear the restart notice: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Removes and returns the notice `/restart` left, if the bot was restarted.
+async fn take_restart_notice(db: &Database) -> Option<RestartNotice> {
+    db.run(restart::take).await.unwrap_or_else(|e| {
+        warn!("Unable to load the restart notice: {}", e);
+        None
+    })
+}
+
+/// Loads the saved response cache if it is persisted, or once after a restart.
+fn load_cache(persist: bool, restarted: bool) -> ResponseCache {
+    if !persist && !restarted {
+        return ResponseCache::default();
+    }
+    let loaded = cache::cache_path().and_then(|path| {
+        let cache = ResponseCache::load(&path)?;
+        if !persist {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(cache)
+    });
+    loaded.unwrap_or_else(|e| {
+        warn!("Unable to load the response cache: {}", e);
+        ResponseCache::default()
+    })
+}
+
+/// Tells the channel `/restart` was used in that the bot is back.
+async fn report_restart(http: &serenity::Http, notice: RestartNotice) {
+    let seconds = (Utc::now().timestamp() - notice.requested_at).max(0);
+    let message = i18n::tr(
+        &notice.locale,
+        "restart-done",
+        &[("seconds", seconds.into())],
+    );
+    if let Err(e) = notice.channel_id.say(http, message).await {
+        warn!("Unable to report the restart: {}", e);
+    }
 }
@@ -0,0 +1,447 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Converts the HTML served by AniList and wikis to Discord markdown.
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node};
+
+/// Characters Discord treats as markdown anywhere in a line.
+const ESCAPED: &[char] = &['\\', '*', '_', '~', '`', '|', '[', ']'];
+/// Characters Discord only treats as markdown at the start of a line.
+const ESCAPED_AT_LINE_START: &[char] = &['>', '#', '-'];
+
+/// Converts `html` to Discord markdown.
+///
+/// Emphasis, strikeout, code and quotes are kept, links become `[text](url)` with relative ones
+/// resolved against `base`, and AniList's `~!spoiler!~` as well as `<spoiler>` become
+/// `||spoiler||`. Text is unescaped from HTML and escaped for markdown, everything else is
+/// dropped.
+pub fn from_html(html: &str, base: Option<&Url>) -> String {
+    let html = spoiler_elements(html);
+    let fragment = Html::parse_fragment(&html);
+    let mut writer = Writer::new(base, false);
+    writer.children(fragment.root_element());
+    tidy(&writer.out)
+}
+
+/// Turns each `~!` with a `!~` after it into a `<spoiler>` element. AniList spoilers can span
+/// several elements, so this is done before parsing. Unpaired markers such as in "Yay~!" are text.
+fn spoiler_elements(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find("~!") {
+        let close = match rest[open + 2..].find("!~") {
+            Some(close) => open + 2 + close,
+            None => break,
+        };
+        out.push_str(&rest[..open]);
+        out.push_str("<spoiler>");
+        out.push_str(&rest[open + 2..close]);
+        out.push_str("</spoiler>");
+        rest = &rest[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+struct Writer<'a> {
+    out: String,
+    base: Option<&'a Url>,
+    /// Whether this renders the inside of an inline element, whose start isn't a line start.
+    inline: bool,
+    /// Indentation of list items, one level per enclosing list.
+    depth: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(base: Option<&'a Url>, inline: bool) -> Self {
+        Writer {
+            out: String::new(),
+            base,
+            inline,
+            depth: 0,
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.ends_with('\n') || (self.out.is_empty() && !self.inline)
+    }
+
+    /// Appends already formatted text, folding whitespace into the whitespace before it.
+    fn push(&mut self, text: &str) {
+        if self.at_line_start() || self.out.ends_with(' ') {
+            self.out.push_str(text.trim_start_matches(' '));
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    fn newline(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+    }
+
+    /// Starts a new line, or a new paragraph if `paragraph` is set.
+    fn block(&mut self, paragraph: bool) {
+        if self.out.trim().is_empty() {
+            return;
+        }
+        if !self.out.ends_with('\n') {
+            self.newline();
+        }
+        if paragraph && !self.out.ends_with("\n\n") {
+            self.newline();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let mut collapsed = String::with_capacity(text.len());
+        for (i, word) in text.split_ascii_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        if text.starts_with(|c: char| c.is_ascii_whitespace()) {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(|c: char| c.is_ascii_whitespace()) && !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+        let mut escaped = escape(&collapsed);
+        if self.at_line_start() && escaped.trim_start().starts_with(ESCAPED_AT_LINE_START) {
+            escaped = format!("\\{}", escaped.trim_start());
+        }
+        self.push(&escaped);
+    }
+
+    fn children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renders the children of `element` on their own, for wrapping them in markup.
+    fn inner(&self, element: ElementRef, inline: bool) -> String {
+        let mut writer = Writer::new(self.base, inline);
+        writer.depth = self.depth;
+        writer.children(element);
+        writer.out
+    }
+
+    /// Wraps the children of `element` in `marker`, keeping surrounding whitespace outside of it
+    /// since Discord ignores markers next to whitespace.
+    fn wrap(&mut self, element: ElementRef, marker: &str) {
+        let inner = self.inner(element, true);
+        let trimmed = inner.trim();
+        if trimmed.is_empty() {
+            self.push(&inner);
+            return;
+        }
+        let before = if inner.starts_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        let after = if inner.ends_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        self.push(&format!(
+            "{}{}{}{}{}",
+            before, marker, trimmed, marker, after
+        ));
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let value = element.value();
+        let has_class = |class: &str| value.classes().any(|c| c == class);
+        match value.name() {
+            "script" | "style" | "noscript" | "img" | "figure" | "template" => {}
+            "sup" if has_class("reference") => {}
+            "span" if has_class("mw-editsection") => {}
+            "span" if has_class("markdown_spoiler") => self.wrap(element, "||"),
+            "spoiler" => self.wrap(element, "||"),
+            "b" | "strong" => self.wrap(element, "**"),
+            "i" | "em" | "cite" => self.wrap(element, "*"),
+            "u" | "ins" => self.wrap(element, "__"),
+            "s" | "strike" | "del" => self.wrap(element, "~~"),
+            "code" | "kbd" | "tt" => self.code(element),
+            "a" => self.link(element),
+            "br" => self.newline(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block(true);
+                self.wrap(element, "**");
+                self.block(true);
+            }
+            "ul" | "ol" => {
+                self.block(self.depth == 0);
+                self.depth += 1;
+                let mut number = 0;
+                for item in element.children().filter_map(ElementRef::wrap) {
+                    if item.value().name() != "li" {
+                        self.element(item);
+                        continue;
+                    }
+                    number += 1;
+                    self.block(false);
+                    self.out.push_str(&"  ".repeat(self.depth - 1));
+                    if value.name() == "ol" {
+                        self.out.push_str(&format!("{}. ", number));
+                    } else {
+                        self.out.push_str("- ");
+                    }
+                    self.children(item);
+                }
+                self.depth -= 1;
+                self.block(self.depth == 0);
+            }
+            "blockquote" => {
+                self.block(true);
+                let inner = tidy(&self.inner(element, false));
+                for line in inner.lines() {
+                    self.out.push_str("> ");
+                    self.out.push_str(line);
+                    self.out.push('\n');
+                }
+                self.block(true);
+            }
+            "pre" => {
+                self.block(true);
+                let code = element.text().collect::<String>();
+                self.out.push_str("```\n");
+                self.out.push_str(code.trim_matches('\n'));
+                self.out.push_str("\n```");
+                self.block(true);
+            }
+            "p" | "table" | "dl" | "hr" => {
+                self.block(true);
+                self.children(element);
+                self.block(true);
+            }
+            "div" | "li" | "tr" | "dt" | "dd" | "center" | "section" | "figcaption" => {
+                self.block(false);
+                self.children(element);
+                self.block(false);
+            }
+            "td" | "th" => {
+                self.push(" ");
+                self.children(element);
+                self.push(" ");
+            }
+            _ => self.children(element),
+        }
+    }
+
+    fn code(&mut self, element: ElementRef) {
+        let code = element.text().collect::<String>();
+        let code = code.trim();
+        if code.is_empty() {
+            return;
+        }
+        // Backticks inside code can only be shown by fencing it with more of them.
+        if code.contains('`') {
+            self.push(&format!("`` {} ``", code));
+        } else {
+            self.push(&format!("`{}`", code));
+        }
+    }
+
+    fn link(&mut self, element: ElementRef) {
+        let url = element
+            .value()
+            .attr("href")
+            .filter(|href| !href.starts_with('#'))
+            .and_then(|href| match self.base {
+                Some(base) => base.join(href).ok(),
+                None => Url::parse(href).ok(),
+            })
+            .filter(|url| matches!(url.scheme(), "http" | "https"));
+        let url = match url {
+            Some(url) => url,
+            None => return self.children(element),
+        };
+        let inner = self.inner(element, true);
+        let text = inner.trim();
+        if text.is_empty() {
+            return;
+        }
+        let url = url.as_str().replace('(', "%28").replace(')', "%29");
+        let before = if inner.starts_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        let after = if inner.ends_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        self.push(&format!("{}[{}]({}){}", before, text, url, after));
+    }
+}
+
+/// Escapes the characters Discord would read as markdown.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if ESCAPED.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Drops trailing whitespace from each line and blank lines beyond one in a row.
+fn tidy(text: &str) -> String {
+    let mut tidied = String::with_capacity(text.len());
+    let mut blank = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank += 1;
+            if blank > 1 {
+                continue;
+            }
+        } else {
+            blank = 0;
+        }
+        tidied.push_str(line);
+        tidied.push('\n');
+    }
+    tidied.trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        from_html(html, None)
+    }
+
+    #[test]
+    fn keeps_emphasis() {
+        assert_eq!(
+            convert("<b>Note:</b> <i>still</i> <u>going</u> <s>ended</s>"),
+            "**Note:** *still* __going__ ~~ended~~"
+        );
+        assert_eq!(convert("a<b> bold </b>b"), "a **bold** b");
+        assert_eq!(convert("<strong><em>both</em></strong>"), "***both***");
+    }
+
+    #[test]
+    fn keeps_line_breaks_and_paragraphs() {
+        assert_eq!(
+            convert("Enter a world.<br><br>\n<i>(Source: Bandai)</i>"),
+            "Enter a world.\n\n*(Source: Bandai)*"
+        );
+        assert_eq!(convert("<p>One\n  two</p><p>Three</p>"), "One two\n\nThree");
+    }
+
+    #[test]
+    fn keeps_links() {
+        assert_eq!(
+            convert(r#"See <a href="https://anilist.co/anime/1">Cowboy Bebop</a>."#),
+            "See [Cowboy Bebop](https://anilist.co/anime/1)."
+        );
+        let base = Url::parse("https://virtualyoutuber.fandom.com/wiki/Gawr_Gura").unwrap();
+        assert_eq!(
+            from_html(
+                r#"<a href="/wiki/Hololive_(English)">hololive</a>"#,
+                Some(&base)
+            ),
+            "[hololive](https://virtualyoutuber.fandom.com/wiki/Hololive_%28English%29)"
+        );
+        // Links that can't be followed from Discord keep only their text.
+        assert_eq!(convert(r#"<a href="/wiki/Relative">text</a>"#), "text");
+        assert_eq!(convert(r##"<a href="#cite">text</a>"##), "text");
+        assert_eq!(convert(r#"<a href="javascript:alert(1)">text</a>"#), "text");
+    }
+
+    #[test]
+    fn converts_spoilers() {
+        assert_eq!(convert("He ~!dies!~."), "He ||dies||.");
+        assert_eq!(convert("~!<b>Big</b> twist!~"), "||**Big** twist||");
+        assert_eq!(convert("<spoiler>hidden</spoiler>"), "||hidden||");
+        assert_eq!(convert("Yay~! It works!"), r"Yay\~! It works!");
+        assert_eq!(convert("Wow!~ ~!a!~ b~!"), r"Wow!\~ ||a|| b\~!");
+        assert_eq!(
+            convert("<span class='markdown_spoiler'><span>hidden</span></span>"),
+            "||hidden||"
+        );
+    }
+
+    #[test]
+    fn escapes_markdown() {
+        assert_eq!(
+            convert("2*3 = 6, snake_case, ~tilde~, `tick`, a|b, [x]"),
+            r"2\*3 = 6, snake\_case, \~tilde\~, \`tick\`, a\|b, \[x\]"
+        );
+        assert_eq!(
+            convert("# not a heading<br>> not a quote"),
+            "\\# not a heading\n\\> not a quote"
+        );
+        assert_eq!(convert("1 > 0 - fine"), "1 > 0 - fine");
+    }
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(
+            convert("It&rsquo;s &quot;fine&quot; &amp; &hellip; &#9733; &#x2665; &eacute;"),
+            "It’s \"fine\" & … ★ ♥ é"
+        );
+        assert_eq!(convert("&lt;b&gt;"), "<b>");
+    }
+
+    #[test]
+    fn converts_blocks() {
+        assert_eq!(
+            convert("<h2>History</h2><ul><li>One</li><li>Two<ol><li>Nested</li></ol></li></ul>"),
+            "**History**\n\n- One\n- Two\n  1. Nested"
+        );
+        assert_eq!(
+            convert("<blockquote><p>Quoted</p><p>Twice</p></blockquote>After"),
+            "> Quoted\n>\n> Twice\n\nAfter"
+        );
+        assert_eq!(
+            convert("Run <code>cargo_test</code><pre>fn main() {}\n</pre>"),
+            "Run `cargo_test`\n\n```\nfn main() {}\n```"
+        );
+    }
+
+    #[test]
+    fn drops_wiki_clutter() {
+        assert_eq!(
+            convert(
+                r##"<p>Gura<sup class="reference"><a href="#cite_note-1">[1]</a></sup> is a shark.<script>x()</script></p>"##
+            ),
+            "Gura is a shark."
+        );
+    }
+}
@@ -0,0 +1,311 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Looks up articles on MediaWiki sites such as Fandom wikis through their `api.php`.
+
+use std::collections::HashMap;
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::error::BotError;
+use crate::http::{HttpClient, HttpError};
+use crate::markdown;
+use crate::reply::Embed;
+use crate::Error;
+
+/// Most articles returned for a search.
+pub const SEARCH_LIMIT: usize = 10;
+
+/// Where an article's description is cut from its HTML.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Extraction {
+    /// The description starts after the first occurrence of this, which must be present. By
+    /// default it starts after the portable infobox, if there is one.
+    pub start: Option<String>,
+    /// The description ends before the first occurrence of this after the start, by default the
+    /// first heading. The rest of the article is used if it isn't found.
+    pub end: Option<String>,
+}
+
+impl Extraction {
+    /// Cuts the description out of an article's HTML.
+    pub fn apply<'a>(&self, html: &'a str) -> Result<&'a str, Error> {
+        let start = match &self.start {
+            Some(marker) => {
+                html.find(marker.as_str())
+                    .ok_or("Unable to find start of description")?
+                    + marker.len()
+            }
+            None => html.find("</aside>").map_or(0, |i| i + "</aside>".len()),
+        };
+        let rest = &html[start..];
+        // The table of contents starts with a heading too, so this also leaves it out.
+        let end = rest
+            .find(self.end.as_deref().unwrap_or("<h2"))
+            .unwrap_or(rest.len());
+        Ok(&rest[..end])
+    }
+}
+
+#[derive(Deserialize, Clone)]
+struct OpenSearchResults(
+    #[allow(dead_code)] String,
+    Vec<String>,
+    #[allow(dead_code)] Vec<String>,
+    Vec<String>,
+);
+
+#[derive(Deserialize, Clone)]
+struct ParseDetails {
+    parse: Parse,
+}
+
+#[derive(Deserialize, Clone)]
+struct Parse {
+    text: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone)]
+struct ArticleImage {
+    image: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone)]
+struct PageImages {
+    query: PageImagesQuery,
+}
+
+#[derive(Deserialize, Clone)]
+struct PageImagesQuery {
+    pages: HashMap<String, PageImagesPage>,
+}
+
+#[derive(Deserialize, Clone)]
+struct PageImagesPage {
+    thumbnail: Option<PageImagesThumbnail>,
+}
+
+#[derive(Deserialize, Clone)]
+struct PageImagesThumbnail {
+    source: String,
+}
+
+#[derive(Deserialize, Clone)]
+struct SiteInfo {
+    query: SiteInfoQuery,
+}
+
+#[derive(Deserialize, Clone)]
+struct SiteInfoQuery {
+    general: SiteInfoGeneral,
+}
+
+#[derive(Deserialize, Clone)]
+struct SiteInfoGeneral {
+    sitename: String,
+}
+
+/// Titles and URLs of the articles matching `query`, best match first.
+pub async fn search(
+    http: &HttpClient,
+    api: &Url,
+    query: &str,
+) -> Result<Vec<(String, String)>, Error> {
+    let limit = SEARCH_LIMIT.to_string();
+    let search: OpenSearchResults = http
+        .get_json(
+            api,
+            &[
+                ("action", "opensearch"),
+                ("limit", &limit),
+                ("search", query),
+                ("redirects", "resolve"),
+                ("format", "json"),
+            ],
+        )
+        .await
+        .map_err(|e| match e {
+            HttpError::Decode(_) => BotError::NotFound(query.to_owned()).into(),
+            e => Error::from(e),
+        })?;
+    let mut matches: Vec<(String, String)> = Vec::new();
+    for (title, url) in search.1.into_iter().zip(search.3) {
+        // Resolved redirects can lead several results to the same article.
+        if !matches.iter().any(|(seen, _)| *seen == title) {
+            matches.push((title, url));
+        }
+    }
+    Ok(matches)
+}
+
+/// The rendered HTML of the article `title`.
+pub async fn article_html(http: &HttpClient, api: &Url, title: &str) -> Result<String, Error> {
+    let details: ParseDetails = http
+        .get_json(
+            api,
+            &[
+                ("action", "parse"),
+                ("page", title),
+                ("format", "json"),
+                ("prop", "text"),
+            ],
+        )
+        .await
+        .map_err(|e| match e {
+            HttpError::Decode(_) => Box::from(format!("Unable to get article {}", title)),
+            e => Error::from(e),
+        })?;
+    Ok(details
+        .parse
+        .text
+        .get("*")
+        .ok_or("Failed to get text")?
+        .clone())
+}
+
+/// The URL of the article's main image, if the wiki knows one.
+///
+/// Fandom wikis are asked through `imageserving`, other wikis through the PageImages extension.
+pub async fn article_image(
+    http: &HttpClient,
+    api: &Url,
+    title: &str,
+) -> Result<Option<String>, Error> {
+    let image = http
+        .get_json::<ArticleImage>(
+            api,
+            &[
+                ("action", "imageserving"),
+                ("wisTitle", title),
+                ("format", "json"),
+            ],
+        )
+        .await;
+    match image {
+        Ok(mut image) => {
+            if let Some(image) = image.image.remove("imageserving") {
+                return Ok(Some(image));
+            }
+        }
+        // Wikis without the action answer with an error object.
+        Err(HttpError::Decode(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    let images = http
+        .get_json::<PageImages>(
+            api,
+            &[
+                ("action", "query"),
+                ("prop", "pageimages"),
+                ("titles", title),
+                ("pithumbsize", "400"),
+                ("format", "json"),
+            ],
+        )
+        .await;
+    match images {
+        Ok(images) => Ok(images
+            .query
+            .pages
+            .into_iter()
+            .find_map(|(_, page)| page.thumbnail)
+            .map(|thumbnail| thumbnail.source)),
+        Err(HttpError::Decode(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The name of the wiki, which also checks that `api` is a MediaWiki API.
+pub async fn site_name(http: &HttpClient, api: &Url) -> Result<String, Error> {
+    let info: SiteInfo = http
+        .get_json(
+            api,
+            &[
+                ("action", "query"),
+                ("meta", "siteinfo"),
+                ("format", "json"),
+            ],
+        )
+        .await
+        .map_err(|e| match e {
+            HttpError::Decode(_) => Box::from(format!("{} is not a MediaWiki API", api)),
+            e => Error::from(e),
+        })?;
+    Ok(info.query.general.sitename)
+}
+
+/// Renders a description cut out by [`Extraction::apply`] as Discord markdown, resolving links
+/// against the article's `url`.
+pub fn description(html: &str, url: &str) -> String {
+    markdown::from_html(html, Url::parse(url).ok().as_ref())
+}
+
+/// Builds an embed from the article `title`'s description and image.
+pub async fn article_embed(
+    http: &HttpClient,
+    api: &Url,
+    title: &str,
+    url: &str,
+    extraction: &Extraction,
+) -> Result<Embed, Error> {
+    let html = article_html(http, api, title).await?;
+    article_embed_from_html(http, api, title, url, &html, extraction).await
+}
+
+/// Like [`article_embed`], for an article whose HTML was already fetched.
+pub async fn article_embed_from_html(
+    http: &HttpClient,
+    api: &Url,
+    title: &str,
+    url: &str,
+    html: &str,
+    extraction: &Extraction,
+) -> Result<Embed, Error> {
+    let description = description(extraction.apply(html)?, url);
+    let image = article_image(http, api, title).await?;
+    let e = Embed::new().title(title).url(url).description(description);
+    Ok(match image {
+        Some(image) => e.thumbnail(image),
+        None => e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extraction_defaults_to_after_the_infobox_until_a_heading() {
+        let html = "<aside>Infobox</aside><p>Lead</p><h2>History</h2><p>Later</p>";
+        assert_eq!(Extraction::default().apply(html).unwrap(), "<p>Lead</p>");
+        assert_eq!(
+            Extraction::default().apply("<p>Lead</p>").unwrap(),
+            "<p>Lead</p>"
+        );
+    }
+
+    #[test]
+    fn extraction_uses_custom_markers() {
+        let extraction = Extraction {
+            start: Some("<main>".to_owned()),
+            end: Some("</main>".to_owned()),
+        };
+        let html = "<nav>Menu</nav><main><p>Lead</p></main><footer>";
+        assert_eq!(extraction.apply(html).unwrap(), "<p>Lead</p>");
+        assert!(extraction.apply("<p>Lead</p>").is_err());
+    }
+}
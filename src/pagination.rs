@@ -0,0 +1,346 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    ButtonStyle, CollectComponentInteraction, CreateComponents, CreateEmbed,
+    InteractionResponseType,
+};
+use poise::ReplyHandle;
+
+use crate::commands::settings::reply_locale;
+use crate::i18n::tr;
+use crate::{Context, Error};
+
+/// How long the buttons keep working after the last click.
+const TIMEOUT: Duration = Duration::from_secs(120);
+/// Most options a select menu can hold.
+const MAX_OPTIONS: usize = 25;
+
+/// Sends the first of `pages` with Previous/Next buttons to flip through the rest and a Select
+/// button to settle on the current one.
+///
+/// Only the invoking user can use the buttons. They are removed once a page is selected, after
+/// [`TIMEOUT`] or when the bot shuts down, leaving the current page in place. Returns the index
+/// of that page.
+pub async fn paginate(context: Context<'_>, pages: Vec<CreateEmbed>) -> Result<usize, Error> {
+    flip(context, pages, true, false).await
+}
+
+/// Like [`paginate`], but without a Select button, for pages that are only read.
+pub async fn browse(context: Context<'_>, pages: Vec<CreateEmbed>) -> Result<(), Error> {
+    flip(context, pages, false, false).await?;
+    Ok(())
+}
+
+/// Like [`browse`], but only shows the pages to the invoking user, where Discord supports it.
+pub async fn browse_ephemeral(context: Context<'_>, pages: Vec<CreateEmbed>) -> Result<(), Error> {
+    flip(context, pages, false, true).await?;
+    Ok(())
+}
+
+async fn flip(
+    context: Context<'_>,
+    pages: Vec<CreateEmbed>,
+    selectable: bool,
+    ephemeral: bool,
+) -> Result<usize, Error> {
+    let first = pages.first().ok_or("Nothing to show.")?.clone();
+    if pages.len() == 1 {
+        context
+            .send(|m| m.embed(|e| set_embed(e, first)).ephemeral(ephemeral))
+            .await?;
+        return Ok(0);
+    }
+
+    let locale = reply_locale(context).await;
+    let select = selectable.then(|| tr(&locale, "pagination-select", &[]));
+    let id = context.id().to_string();
+    let mut index = 0;
+    let reply = context
+        .send(|m| {
+            m.embed(|e| set_embed(e, first))
+                .components(|c| buttons(c, &id, select.as_deref(), index, pages.len()))
+                .ephemeral(ephemeral)
+        })
+        .await?;
+
+    loop {
+        let filter_id = id.clone();
+        let collector = CollectComponentInteraction::new(context.discord())
+            .channel_id(context.channel_id())
+            .filter(move |i| Action::of(&filter_id, &i.data.custom_id).is_some())
+            .timeout(TIMEOUT);
+        // Shutting down waits for running commands, so stop waiting for clicks at once.
+        let interaction = tokio::select! {
+            interaction = collector => interaction,
+            _ = context.data().shutdown.requested() => None,
+        };
+        let interaction = match interaction {
+            Some(interaction) => interaction,
+            None => break,
+        };
+
+        if interaction.user.id != context.author().id {
+            let message = tr(
+                interaction.locale.as_str(),
+                "pagination-not-yours",
+                &[("user", context.author().name.as_str().into())],
+            );
+            interaction
+                .create_interaction_response(context.discord(), |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| d.content(message).ephemeral(true))
+                })
+                .await?;
+            continue;
+        }
+
+        let action = Action::of(&id, &interaction.data.custom_id);
+        let selected = action == Some(Action::Select);
+        if let Some(action) = action {
+            index = action.step(index, pages.len());
+        }
+        let page = pages[index].clone();
+        interaction
+            .create_interaction_response(context.discord(), |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.set_embed(page).components(|c| {
+                            if selected {
+                                c
+                            } else {
+                                buttons(c, &id, select.as_deref(), index, pages.len())
+                            }
+                        })
+                    })
+            })
+            .await?;
+        if selected {
+            return Ok(index);
+        }
+    }
+
+    // Prefix command edits replace every embed, so the current page has to be sent again.
+    let page = pages[index].clone();
+    reply
+        .edit(context, |m| {
+            m.embed(|e| set_embed(e, page)).components(|c| c)
+        })
+        .await?;
+    Ok(index)
+}
+
+/// Sends `prompt` with a select menu of `options` for the invoking user to pick one of.
+///
+/// Returns the index of the picked option along with the prompt, whose menu has been removed, or
+/// `None` if nothing was picked within [`TIMEOUT`] or before the bot shut down. At most 25 options
+/// are offered.
+pub async fn choose<'a>(
+    context: Context<'a>,
+    prompt: &str,
+    options: &[String],
+) -> Result<Option<(usize, ReplyHandle<'a>)>, Error> {
+    let locale = reply_locale(context).await;
+    let id = format!("{}choose", context.id());
+    let menu_id = id.clone();
+    let reply = context
+        .send(|m| {
+            m.content(prompt).components(|c| {
+                c.create_action_row(|row| {
+                    row.create_select_menu(|menu| {
+                        menu.custom_id(menu_id).options(|o| {
+                            for (i, option) in options.iter().enumerate().take(MAX_OPTIONS) {
+                                o.create_option(|opt| {
+                                    opt.label(option.chars().take(100).collect::<String>())
+                                        .value(i)
+                                });
+                            }
+                            o
+                        })
+                    })
+                })
+            })
+        })
+        .await?;
+
+    loop {
+        let filter_id = id.clone();
+        let collector = CollectComponentInteraction::new(context.discord())
+            .channel_id(context.channel_id())
+            .filter(move |i| i.data.custom_id == filter_id)
+            .timeout(TIMEOUT);
+        // Shutting down waits for running commands, so stop waiting for clicks at once.
+        let interaction = tokio::select! {
+            interaction = collector => interaction,
+            _ = context.data().shutdown.requested() => None,
+        };
+        let interaction = match interaction {
+            Some(interaction) => interaction,
+            None => break,
+        };
+
+        if interaction.user.id != context.author().id {
+            let message = tr(
+                interaction.locale.as_str(),
+                "choose-not-yours",
+                &[("user", context.author().name.as_str().into())],
+            );
+            interaction
+                .create_interaction_response(context.discord(), |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| d.content(message).ephemeral(true))
+                })
+                .await?;
+            continue;
+        }
+
+        let index = interaction
+            .data
+            .values
+            .first()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|index| *index < options.len())
+            .ok_or("Invalid selection.")?;
+        // The caller may take a while to show the pick, so only acknowledge it for now.
+        interaction
+            .create_interaction_response(context.discord(), |r| {
+                r.kind(InteractionResponseType::DeferredUpdateMessage)
+            })
+            .await?;
+        let picked = tr(
+            &locale,
+            "choose-picked",
+            &[("option", options[index].as_str().into())],
+        );
+        reply
+            .edit(context, |m| m.content(picked).components(|c| c))
+            .await?;
+        return Ok(Some((index, reply)));
+    }
+
+    let message = tr(&locale, "choose-nothing-picked", &[]);
+    reply
+        .edit(context, |m| m.content(message).components(|c| c))
+        .await?;
+    Ok(None)
+}
+
+/// What the buttons below pages do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Previous,
+    Next,
+    Select,
+}
+
+impl Action {
+    const ALL: [Action; 3] = [Action::Previous, Action::Next, Action::Select];
+
+    /// The custom ID of this action's button below the pages sent with `id`.
+    fn custom_id(self, id: &str) -> String {
+        let action = match self {
+            Action::Previous => "previous",
+            Action::Next => "next",
+            Action::Select => "select",
+        };
+        format!("{}{}", id, action)
+    }
+
+    /// The action of the button with `custom_id`, if it is one below the pages sent with `id`.
+    fn of(id: &str, custom_id: &str) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|action| action.custom_id(id) == custom_id)
+    }
+
+    /// The page shown after this action on page `index` of `total`, wrapping around at the ends.
+    fn step(self, index: usize, total: usize) -> usize {
+        match self {
+            Action::Previous => index.checked_sub(1).unwrap_or(total - 1),
+            Action::Next => (index + 1) % total,
+            Action::Select => index,
+        }
+    }
+}
+
+fn set_embed(target: &mut CreateEmbed, embed: CreateEmbed) -> &mut CreateEmbed {
+    *target = embed;
+    target
+}
+
+fn buttons<'a>(
+    components: &'a mut CreateComponents,
+    id: &str,
+    select: Option<&str>,
+    index: usize,
+    total: usize,
+) -> &'a mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(Action::Previous.custom_id(id))
+                .emoji('◀')
+                .style(ButtonStyle::Secondary)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{}position", id))
+                .label(format!("{}/{}", index + 1, total))
+                .style(ButtonStyle::Secondary)
+                .disabled(true)
+        })
+        .create_button(|b| {
+            b.custom_id(Action::Next.custom_id(id))
+                .emoji('▶')
+                .style(ButtonStyle::Secondary)
+        });
+        if let Some(select) = select {
+            row.create_button(|b| {
+                b.custom_id(Action::Select.custom_id(id))
+                    .label(select)
+                    .style(ButtonStyle::Primary)
+            });
+        }
+        row
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_buttons_of_these_pages_match() {
+        assert_eq!(Action::of("12", "12previous"), Some(Action::Previous));
+        assert_eq!(Action::of("12", "12next"), Some(Action::Next));
+        assert_eq!(Action::of("12", "12select"), Some(Action::Select));
+        // The page counter is disabled, and other pages' IDs may start with the same digits.
+        assert_eq!(Action::of("12", "12position"), None);
+        assert_eq!(Action::of("12", "123next"), None);
+        assert_eq!(Action::of("12", "12choose"), None);
+        assert_eq!(Action::of("12", "next"), None);
+    }
+
+    #[test]
+    fn stepping_wraps_around() {
+        assert_eq!(Action::Next.step(0, 3), 1);
+        assert_eq!(Action::Next.step(2, 3), 0);
+        assert_eq!(Action::Previous.step(2, 3), 1);
+        assert_eq!(Action::Previous.step(0, 3), 2);
+        assert_eq!(Action::Select.step(1, 3), 1);
+    }
+}
@@ -0,0 +1,644 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{Colour, CreateEmbed};
+use poise::ReplyHandle;
+
+use crate::{Context, Error};
+
+/// Most characters Discord accepts in an embed title, author name or field name.
+pub const TITLE_LIMIT: usize = 256;
+/// Most characters Discord accepts in an embed description.
+pub const DESCRIPTION_LIMIT: usize = 4096;
+/// Most characters Discord accepts in an embed field value.
+pub const FIELD_VALUE_LIMIT: usize = 1024;
+/// Most fields Discord accepts in an embed.
+pub const FIELD_LIMIT: usize = 25;
+/// Most characters Discord accepts in an embed footer.
+pub const FOOTER_LIMIT: usize = 2048;
+/// Most characters Discord accepts across an embed's title, description, fields, author and footer.
+pub const TOTAL_LIMIT: usize = 6000;
+
+/// What a command responds with, built without talking to Discord so it can be checked in tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reply {
+    pub content: Option<String>,
+    pub embed: Option<Embed>,
+    pub ephemeral: bool,
+}
+
+impl Reply {
+    /// A plain text reply.
+    pub fn text(content: impl Into<String>) -> Self {
+        Reply {
+            content: Some(content.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A reply consisting of a single embed.
+    pub fn embed(embed: Embed) -> Self {
+        Reply {
+            embed: Some(embed),
+            ..Default::default()
+        }
+    }
+
+    /// Only shows the reply to the invoking user, where Discord supports it.
+    pub fn ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+
+    /// Sends the reply in response to the command being run in `context`, cutting the embed down
+    /// to Discord's limits.
+    pub async fn send(self, context: Context<'_>) -> Result<ReplyHandle<'_>, Error> {
+        let handle = context
+            .send(|m| {
+                if let Some(content) = self.content {
+                    m.content(content);
+                }
+                if let Some(embed) = self.embed {
+                    m.embed(|e| {
+                        *e = embed.truncated().into();
+                        e
+                    });
+                }
+                m.ephemeral(self.ephemeral)
+            })
+            .await?;
+        Ok(handle)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Embed {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub colour: Option<Colour>,
+    pub author: Option<Author>,
+    pub fields: Vec<Field>,
+    pub image: Option<String>,
+    pub thumbnail: Option<String>,
+    pub footer: Option<String>,
+    pub footer_icon: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Author {
+    pub name: String,
+    pub icon_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+impl Embed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn colour(mut self, colour: impl Into<Colour>) -> Self {
+        self.colour = Some(colour.into());
+        self
+    }
+
+    pub fn author(mut self, name: impl Into<String>, icon_url: Option<String>) -> Self {
+        self.author = Some(Author {
+            name: name.into(),
+            icon_url,
+        });
+        self
+    }
+
+    pub fn field(mut self, name: impl Into<String>, value: impl ToString, inline: bool) -> Self {
+        self.fields.push(Field {
+            name: name.into(),
+            value: value.to_string(),
+            inline,
+        });
+        self
+    }
+
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image = Some(url.into());
+        self
+    }
+
+    pub fn thumbnail(mut self, url: impl Into<String>) -> Self {
+        self.thumbnail = Some(url.into());
+        self
+    }
+
+    pub fn footer(mut self, text: impl Into<String>) -> Self {
+        self.footer = Some(text.into());
+        self
+    }
+
+    pub fn footer_icon(mut self, url: impl Into<String>) -> Self {
+        self.footer_icon = Some(url.into());
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// The value of the first field called `name`.
+    pub fn field_value(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value.as_str())
+    }
+
+    /// Cuts the embed down to Discord's limits.
+    ///
+    /// A description that is too long ends at a sentence or word boundary with a "Read more"
+    /// link to the embed's URL, if it has one. Fields that don't fit are left out.
+    pub fn truncated(mut self) -> Embed {
+        self.shorten_headings();
+        let limit = DESCRIPTION_LIMIT.min(TOTAL_LIMIT - self.headings_len());
+        if let Some(description) = &self.description {
+            self.description = Some(truncate(description, limit, self.url.as_deref()));
+        }
+        self.pages().swap_remove(0)
+    }
+
+    /// Splits the embed into pages that each fit Discord's limits, for
+    /// [`crate::pagination::paginate`].
+    ///
+    /// A description that is too long is cut like in [`Embed::truncated`] if the embed has a
+    /// URL to read the rest at, and spread over several pages otherwise. Field values are cut
+    /// to fit, and fields that don't fit on a page move to the next one. Every page keeps the
+    /// title, author, images and footer.
+    pub fn pages(mut self) -> Vec<Embed> {
+        self.shorten_headings();
+        for field in &mut self.fields {
+            field.value = truncate(&field.value, FIELD_VALUE_LIMIT, self.url.as_deref());
+        }
+        let limit = DESCRIPTION_LIMIT.min(TOTAL_LIMIT - self.headings_len());
+        let descriptions = match self.description.take() {
+            Some(description) if description.chars().count() > limit => match &self.url {
+                Some(url) => vec![truncate(&description, limit, Some(url))],
+                None => split(&description, limit),
+            },
+            Some(description) => vec![description],
+            None => Vec::new(),
+        };
+        let fields = std::mem::take(&mut self.fields);
+        let mut pages = descriptions
+            .into_iter()
+            .map(|description| self.clone().description(description))
+            .collect::<Vec<_>>();
+        if pages.is_empty() {
+            pages.push(self.clone());
+        }
+
+        let mut index = 0;
+        for field in fields {
+            let length = field.name.chars().count() + field.value.chars().count();
+            loop {
+                if index == pages.len() {
+                    pages.push(self.clone());
+                }
+                let page = &mut pages[index];
+                if page.fields.len() < FIELD_LIMIT && page.len() + length <= TOTAL_LIMIT {
+                    page.fields.push(field);
+                    break;
+                }
+                index += 1;
+            }
+        }
+        pages
+    }
+
+    /// The number of characters Discord counts towards [`TOTAL_LIMIT`].
+    pub fn len(&self) -> usize {
+        self.headings_len()
+            + self
+                .description
+                .as_ref()
+                .map_or(0, |description| description.chars().count())
+            + self
+                .fields
+                .iter()
+                .map(|field| field.name.chars().count() + field.value.chars().count())
+                .sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The length of the parts every page repeats.
+    fn headings_len(&self) -> usize {
+        let length = |text: &Option<String>| text.as_ref().map_or(0, |t| t.chars().count());
+        length(&self.title)
+            + length(&self.footer)
+            + self
+                .author
+                .as_ref()
+                .map_or(0, |author| author.name.chars().count())
+    }
+
+    fn shorten_headings(&mut self) {
+        let shorten = |text: &mut String, limit| *text = truncate(text, limit, None);
+        if let Some(title) = &mut self.title {
+            shorten(title, TITLE_LIMIT);
+        }
+        if let Some(author) = &mut self.author {
+            shorten(&mut author.name, TITLE_LIMIT);
+        }
+        if let Some(footer) = &mut self.footer {
+            shorten(footer, FOOTER_LIMIT);
+        }
+        for field in &mut self.fields {
+            shorten(&mut field.name, TITLE_LIMIT);
+        }
+    }
+}
+
+/// Cuts markdown `text` down to `limit` characters, preferring to end at a paragraph, sentence
+/// or word. Cut text ends with an ellipsis and a "Read more" link to `more`, if given.
+pub fn truncate(text: &str, limit: usize, more: Option<&str>) -> String {
+    if text.chars().count() <= limit {
+        return text.to_owned();
+    }
+    let suffix = more
+        .map(|url| {
+            let url = url.replace('(', "%28").replace(')', "%29");
+            format!("… [Read more]({})", url)
+        })
+        .filter(|suffix| suffix.chars().count() * 2 <= limit)
+        .unwrap_or_else(|| "…".to_owned());
+    let mut budget = limit.saturating_sub(suffix.chars().count());
+    let mut head = loop {
+        if budget == 0 {
+            break String::new();
+        }
+        let (head, _) = cut(text, budget);
+        let closing = unclosed(head).concat();
+        if head.chars().count() + closing.chars().count() + suffix.chars().count() <= limit {
+            break format!("{}{}", head, closing);
+        }
+        budget = budget.saturating_sub(closing.chars().count());
+    };
+    head.push_str(&suffix);
+    head
+}
+
+/// Splits markdown `text` into chunks of at most `limit` characters, at paragraphs, sentences or
+/// words where possible. Markup open at the end of a chunk is closed and opened again in the
+/// next one.
+pub fn split(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut reopened = String::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        // Leaves room for closing whatever markup the cut leaves open.
+        let budget = limit.saturating_sub(reopened.chars().count() + MARKERS.concat().len());
+        let (head, tail) = if rest.chars().count() + reopened.chars().count() <= limit {
+            (rest, "")
+        } else {
+            cut(rest, budget.max(1))
+        };
+        let mut chunk = std::mem::take(&mut reopened);
+        chunk.push_str(head);
+        let open = unclosed(&chunk);
+        for marker in &open {
+            chunk.push_str(marker);
+        }
+        reopened = open.iter().rev().copied().collect();
+        chunks.push(chunk);
+        rest = tail.trim_start();
+    }
+    chunks
+}
+
+/// Markdown markers that have to be balanced, innermost first when closing.
+const MARKERS: [&str; 5] = ["*", "**", "__", "~~", "||"];
+
+/// Splits `text` after at most `budget` characters, at the last paragraph, sentence or word
+/// boundary in the second half of that if there is one. Links are only cut in half if `text` starts
+/// with one longer than `budget`, the head always has at least one character of a non-empty `text`.
+fn cut(text: &str, budget: usize) -> (&str, &str) {
+    let end = text
+        .char_indices()
+        .nth(budget)
+        .map_or(text.len(), |(index, _)| index);
+    let window = &text[..end];
+    let half = end / 2;
+    let mut boundary = window
+        .rfind("\n\n")
+        .filter(|index| *index >= half)
+        .or_else(|| {
+            [". ", "! ", "? ", ".\n", "!\n", "?\n"]
+                .iter()
+                .filter_map(|end| window.rfind(end).map(|index| index + 1))
+                .filter(|index| *index >= half)
+                .max()
+        })
+        .or_else(|| {
+            window
+                .rfind(char::is_whitespace)
+                .filter(|index| *index >= half)
+        })
+        .unwrap_or(end);
+    // A link that would be cut off is left out entirely.
+    let open = text[..boundary]
+        .rmatch_indices('[')
+        .map(|(index, _)| index)
+        .find(|index| !text[..*index].ends_with('\\'));
+    if let Some(open) = open {
+        if !text[open..boundary].contains(')') && !text[..open].trim_end().is_empty() {
+            boundary = open;
+        }
+    }
+    if text[..boundary].trim_end().is_empty() {
+        boundary = text.chars().next().map_or(0, char::len_utf8);
+    }
+    let (head, tail) = text.split_at(boundary);
+    (head.trim_end(), tail)
+}
+
+/// The markers left open in `text`, in the order they have to be closed.
+fn unclosed(text: &str) -> Vec<&'static str> {
+    let mut counts = [0usize; MARKERS.len()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' => {
+                let mut run = 1;
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                    run += 1;
+                }
+                counts[0] += run % 2;
+                counts[1] += run / 2;
+            }
+            '_' | '~' | '|' if chars.peek() == Some(&c) => {
+                chars.next();
+                let index = MARKERS
+                    .iter()
+                    .position(|marker| marker.starts_with(c))
+                    .unwrap_or_default();
+                counts[index] += 1;
+            }
+            _ => {}
+        }
+    }
+    MARKERS
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| count % 2 == 1)
+        .map(|(marker, _)| *marker)
+        .collect()
+}
+
+impl From<Embed> for CreateEmbed {
+    fn from(embed: Embed) -> Self {
+        let mut e = CreateEmbed::default();
+        if let Some(title) = embed.title {
+            e.title(title);
+        }
+        if let Some(description) = embed.description {
+            e.description(description);
+        }
+        if let Some(url) = embed.url {
+            e.url(url);
+        }
+        if let Some(colour) = embed.colour {
+            e.colour(colour);
+        }
+        if let Some(author) = embed.author {
+            e.author(|a| {
+                a.name(author.name);
+                if let Some(icon_url) = author.icon_url {
+                    a.icon_url(icon_url);
+                }
+                a
+            });
+        }
+        for field in embed.fields {
+            e.field(field.name, field.value, field.inline);
+        }
+        if let Some(image) = embed.image {
+            e.image(image);
+        }
+        if let Some(thumbnail) = embed.thumbnail {
+            e.thumbnail(thumbnail);
+        }
+        if let Some(footer) = embed.footer {
+            let icon_url = embed.footer_icon;
+            e.footer(|f| {
+                f.text(footer);
+                if let Some(icon_url) = icon_url {
+                    f.icon_url(icon_url);
+                }
+                f
+            });
+        }
+        if let Some(timestamp) = embed.timestamp {
+            e.timestamp(timestamp);
+        }
+        e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(count: usize) -> String {
+        (0..count)
+            .map(|i| format!("Sentence number {} is here.", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn chars(text: &Option<String>) -> usize {
+        text.as_ref().map_or(0, |text| text.chars().count())
+    }
+
+    #[test]
+    fn short_text_is_left_alone() {
+        assert_eq!(truncate("Short.", 10, Some("https://a.b")), "Short.");
+    }
+
+    #[test]
+    fn truncates_at_sentence_with_read_more() {
+        let text = sentences(20);
+        let cut = truncate(&text, 100, Some("https://anilist.co/anime/1"));
+        assert!(cut.chars().count() <= 100);
+        assert!(cut.starts_with("Sentence number 0 is here."));
+        assert!(cut.ends_with("is here.… [Read more](https://anilist.co/anime/1)"));
+    }
+
+    #[test]
+    fn truncates_at_word_without_sentences() {
+        let cut = truncate(&"word ".repeat(50), 32, None);
+        assert_eq!(cut, "word word word word word word…");
+    }
+
+    #[test]
+    fn truncation_closes_markup_and_keeps_links_whole() {
+        let cut = truncate(&format!("**{}**", "bold words ".repeat(20)), 50, None);
+        assert!(cut.ends_with("**…"), "{}", cut);
+        assert_eq!(cut.matches("**").count(), 2);
+
+        let text = format!(
+            "{} [a link](https://example.org/{})",
+            "text ".repeat(8),
+            "x".repeat(40)
+        );
+        let cut = truncate(&text, 70, None);
+        assert!(!cut.contains('['), "{}", cut);
+    }
+
+    #[test]
+    fn cut_always_makes_progress() {
+        let link = "[a link with a long title](https://example.com/a/long/path) and more";
+        let (head, tail) = cut(link, 10);
+        assert_eq!(head, "[a link");
+        assert_eq!(
+            tail,
+            " with a long title](https://example.com/a/long/path) and more"
+        );
+        assert_eq!(cut("word", 0), ("w", "ord"));
+        assert_eq!(
+            cut("see [a long link](https://a.b)", 10),
+            ("see", "[a long link](https://a.b)")
+        );
+    }
+
+    #[test]
+    fn split_cuts_a_leading_link_longer_than_the_limit() {
+        let text = "[a link with a long title](https://example.com/a/long/path) and more";
+        let chunks = split(text, 20);
+        assert!(
+            chunks.iter().all(|chunk| chunk.chars().count() <= 20),
+            "{:?}",
+            chunks
+        );
+        assert_eq!(chunks.concat().replace(' ', ""), text.replace(' ', ""));
+    }
+
+    #[test]
+    fn split_reopens_markup() {
+        let chunks = split(&format!("||{}||", sentences(10)), 120);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 120);
+            assert!(
+                chunk.starts_with("||") && chunk.ends_with("||"),
+                "{}",
+                chunk
+            );
+        }
+    }
+
+    #[test]
+    fn long_description_with_url_gets_read_more() {
+        let pages = Embed::new()
+            .title("Title")
+            .url("https://anilist.co/anime/1")
+            .description(sentences(300))
+            .field("Episodes", 26, true)
+            .pages();
+        assert_eq!(pages.len(), 1);
+        assert!(chars(&pages[0].description) <= DESCRIPTION_LIMIT);
+        assert!(pages[0]
+            .description
+            .as_ref()
+            .unwrap()
+            .ends_with("[Read more](https://anilist.co/anime/1)"));
+        assert_eq!(pages[0].field_value("Episodes"), Some("26"));
+    }
+
+    #[test]
+    fn long_description_without_url_is_paginated() {
+        let pages = Embed::new()
+            .title("Title")
+            .description(sentences(300))
+            .pages();
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert_eq!(page.title.as_deref(), Some("Title"));
+            assert!(chars(&page.description) <= DESCRIPTION_LIMIT);
+            assert!(page.len() <= TOTAL_LIMIT);
+        }
+        let joined = pages
+            .iter()
+            .map(|page| page.description.clone().unwrap())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(joined, sentences(300));
+    }
+
+    #[test]
+    fn fields_spill_onto_more_pages() {
+        let mut embed = Embed::new().title("Many fields");
+        for i in 0..30 {
+            embed = embed.field(format!("Field {}", i), "value", true);
+        }
+        let pages = embed.clone().pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].fields.len(), FIELD_LIMIT);
+        assert_eq!(pages[1].field_value("Field 29"), Some("value"));
+        assert_eq!(embed.truncated().fields.len(), FIELD_LIMIT);
+
+        let mut embed = Embed::new().description(sentences(100));
+        for i in 0..6 {
+            embed = embed.field(format!("Field {}", i), "x".repeat(2000), false);
+        }
+        let pages = embed.pages();
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert!(page.len() <= TOTAL_LIMIT);
+            assert!(page
+                .fields
+                .iter()
+                .all(|field| field.value.chars().count() <= FIELD_VALUE_LIMIT));
+        }
+    }
+}
@@ -0,0 +1,270 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Stopping the bot without cutting off running commands, on a signal or `/shutdown` and
+//! `/restart`.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use poise::serenity_prelude::{Http, ShardManager};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::alerts::{self, AlertSink};
+use crate::cache::{self, ResponseCache};
+use crate::db::Database;
+use crate::util::ConfigHandle;
+
+/// How long running commands get to finish before the shards are shut down anyway.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What happens once the bot has stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// The process exits.
+    Stop,
+    /// The process starts itself again.
+    Restart,
+}
+
+/// Tracks the running commands and whether the bot was asked to stop.
+#[derive(Default)]
+pub struct Shutdown {
+    state: Mutex<State>,
+    changed: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    mode: Option<Mode>,
+    /// IDs of the commands that are running.
+    running: HashSet<u64>,
+}
+
+impl Shutdown {
+    /// Asks the bot to stop. Only the first request counts.
+    pub fn request(&self, mode: Mode) {
+        let mut state = self.state.lock().unwrap();
+        if state.mode.is_none() {
+            state.mode = Some(mode);
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// How the bot was asked to stop, if it was.
+    pub fn mode(&self) -> Option<Mode> {
+        self.state.lock().unwrap().mode
+    }
+
+    /// Whether new commands should be refused.
+    pub fn is_stopping(&self) -> bool {
+        self.mode().is_some()
+    }
+
+    /// Records that the command with invocation ID `id` started.
+    pub fn started(&self, id: u64) {
+        self.state.lock().unwrap().running.insert(id);
+    }
+
+    /// Records that the command with invocation ID `id` finished, successfully or not.
+    pub fn finished(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.running.remove(&id) && state.running.is_empty() {
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// How many commands are running.
+    pub fn running(&self) -> usize {
+        self.state.lock().unwrap().running.len()
+    }
+
+    /// Waits until the bot is asked to stop.
+    pub async fn requested(&self) -> Mode {
+        loop {
+            let changed = self.changed.notified();
+            if let Some(mode) = self.mode() {
+                return mode;
+            }
+            changed.await;
+        }
+    }
+
+    /// Waits until no commands are running.
+    pub async fn drained(&self) {
+        loop {
+            let changed = self.changed.notified();
+            if self.running() == 0 {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// What has to be saved or sent before the process exits.
+pub struct Flush {
+    pub http: Arc<Http>,
+    pub db: Database,
+    pub cache: Arc<ResponseCache>,
+    pub alerts: Arc<AlertSink>,
+    pub config: ConfigHandle,
+}
+
+/// Stops the bot on SIGINT, SIGTERM or a request through `shutdown`: refuses new commands, gives
+/// the running ones [`DRAIN_TIMEOUT`] to finish, saves the response cache if it is persisted or
+/// the bot restarts, sends the queued owner alerts and shuts the shards down, which ends the
+/// framework's `run`.
+pub fn spawn_shutdown_handler(
+    shutdown: Arc<Shutdown>,
+    shard_manager: Arc<tokio::sync::Mutex<ShardManager>>,
+    flush: Flush,
+) {
+    tokio::spawn(async move {
+        let mode = tokio::select! {
+            mode = shutdown.requested() => mode,
+            signal = signal() => {
+                info!("Received {}", signal);
+                shutdown.request(Mode::Stop);
+                // A `/restart` may have raced the signal.
+                shutdown.mode().unwrap_or(Mode::Stop)
+            }
+        };
+        info!(
+            "{} once {} running commands finish",
+            if mode == Mode::Restart {
+                "Restarting"
+            } else {
+                "Shutting down"
+            },
+            shutdown.running()
+        );
+        if tokio::time::timeout(DRAIN_TIMEOUT, shutdown.drained())
+            .await
+            .is_err()
+        {
+            warn!(
+                "Stopping with {} commands still running after {}s",
+                shutdown.running(),
+                DRAIN_TIMEOUT.as_secs()
+            );
+        }
+        save(&flush, mode).await;
+        shard_manager.lock().await.shutdown_all().await;
+    });
+}
+
+async fn save(flush: &Flush, mode: Mode) {
+    // A restart keeps the cache even if it isn't persisted, the new process loads it once.
+    if mode == Mode::Restart || flush.config.read().await.cache.persist {
+        let cache = flush.cache.clone();
+        let saved = tokio::task::spawn_blocking(move || cache.save(&cache::cache_path()?)).await;
+        match saved {
+            Ok(Ok(())) => info!("Saved the response cache"),
+            Ok(Err(e)) => warn!("Failed to save the response cache: {}", e),
+            Err(e) => warn!("Failed to save the response cache: {}", e),
+        }
+    }
+    if let Err(e) = alerts::flush(&flush.http, &flush.db, &flush.alerts).await {
+        warn!("Failed to send owner alerts: {}", e);
+    }
+}
+
+#[cfg(unix)]
+async fn signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            warn!("Unable to listen for SIGTERM: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            return "SIGINT";
+        }
+    };
+    tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+    }
+}
+
+#[cfg(not(unix))]
+async fn signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl-C"
+}
+
+/// Replaces the process with a new instance of the bot, with the same arguments.
+///
+/// Only returns if starting the new instance failed.
+pub fn restart_process() -> std::io::Error {
+    let executable = match std::env::current_exe() {
+        Ok(executable) => executable,
+        Err(e) => return e,
+    };
+    let mut command = std::process::Command::new(executable);
+    command.args(std::env::args_os().skip(1));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.exec()
+    }
+    #[cfg(not(unix))]
+    match command.spawn() {
+        Ok(_) => std::process::exit(0),
+        Err(e) => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_running_commands() {
+        let shutdown = Arc::new(Shutdown::default());
+        shutdown.started(1);
+        shutdown.started(2);
+        let waiting = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.drained().await }
+        });
+        shutdown.finished(1);
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        shutdown.finished(2);
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn first_request_wins() {
+        let shutdown = Arc::new(Shutdown::default());
+        assert!(!shutdown.is_stopping());
+        let waiting = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.requested().await }
+        });
+        shutdown.request(Mode::Restart);
+        shutdown.request(Mode::Stop);
+        assert_eq!(waiting.await.unwrap(), Mode::Restart);
+        assert_eq!(shutdown.mode(), Some(Mode::Restart));
+    }
+}
@@ -14,7 +14,9 @@
  *    limitations under the License.
  */
 
+use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::anyhow;
@@ -22,128 +24,132 @@ use anyhow::Result;
 use chrono::DateTime;
 use chrono::Utc;
 use directories::ProjectDirs;
-use html2text::render::text_renderer::{TaggedLine, TextDecorator};
 use ini::Ini;
-use poise::serenity_prelude::ShardManager;
-use tokio::sync::Mutex;
+use poise::serenity_prelude::{GuildId, ShardManager};
+use poise::CooldownConfig;
+use tokio::sync::{Mutex, RwLock, RwLockReadGuard};
+use tracing::info;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::{reload, Registry};
+
+use crate::alerts::AlertSink;
+use crate::commands::weeb::TitleAutocomplete;
+use crate::config::{apply_env_overrides, Config};
+use crate::db::guild_settings::{self, GuildSettings};
+use crate::db::Database;
+use crate::http::HttpClient;
+use crate::shutdown::Shutdown;
+
+pub type LogFilterHandle = reload::Handle<Targets, Registry>;
+
+/// Shared handle to the loaded configuration.
+#[derive(Clone)]
+pub struct ConfigHandle {
+    config: Arc<RwLock<Config>>,
+    log_filter: LogFilterHandle,
+}
+
+impl ConfigHandle {
+    pub fn new(config: Config, log_filter: LogFilterHandle) -> Self {
+        ConfigHandle {
+            config: Arc::new(RwLock::new(config)),
+            log_filter,
+        }
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, Config> {
+        self.config.read().await
+    }
+
+    /// Loads `settings.ini` again and swaps it in, returning a description of each changed key.
+    ///
+    /// If the file fails to load or validate the current configuration is kept.
+    pub async fn reload(&self) -> Result<Vec<String>> {
+        let new = get_configuration()?;
+        self.log_filter.reload(new.logging.filter())?;
+        let mut config = self.config.write().await;
+        let changes = config.diff(&new);
+        *config = new;
+        if changes.is_empty() {
+            info!("Reloaded configuration, nothing changed");
+        }
+        for change in &changes {
+            info!("Reloaded configuration: {}", change);
+        }
+        Ok(changes)
+    }
+}
 
 pub struct Data {
-    pub(crate) config: Mutex<Ini>,
-    pub(crate) uptime: Arc<DateTime<Utc>>,
-    pub(crate) shard_manager: Arc<Mutex<ShardManager>>,
+    pub config: ConfigHandle,
+    pub uptime: Arc<DateTime<Utc>>,
+    pub shard_manager: Arc<Mutex<ShardManager>>,
+    /// Cooldown settings last applied to each command, so config reloads can be picked up.
+    pub applied_cooldowns: std::sync::Mutex<HashMap<String, CooldownConfig>>,
+    pub db: Database,
+    /// Cached copy of every guild's stored settings.
+    pub guild_settings: RwLock<HashMap<GuildId, GuildSettings>>,
+    pub title_autocomplete: TitleAutocomplete,
+    pub http: HttpClient,
+    /// Command errors waiting to be sent to the owners.
+    pub alerts: Arc<AlertSink>,
+    /// Running commands and whether the bot is stopping.
+    pub shutdown: Arc<Shutdown>,
+}
+
+impl Data {
+    /// Settings for a guild, or the defaults if it never changed any.
+    pub async fn guild_settings(&self, guild_id: GuildId) -> GuildSettings {
+        self.guild_settings
+            .read()
+            .await
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Applies `update` to a guild's settings and persists the result.
+    pub async fn update_guild_settings<F>(
+        &self,
+        guild_id: GuildId,
+        update: F,
+    ) -> Result<GuildSettings>
+    where
+        F: FnOnce(&mut GuildSettings),
+    {
+        let mut cache = self.guild_settings.write().await;
+        let mut settings = cache.get(&guild_id).cloned().unwrap_or_default();
+        update(&mut settings);
+        let stored = settings.clone();
+        self.db
+            .run(move |connection| guild_settings::save(connection, guild_id, &stored))
+            .await?;
+        cache.insert(guild_id, settings.clone());
+        Ok(settings)
+    }
 }
 
 pub fn get_project_dirs() -> Option<ProjectDirs> {
     ProjectDirs::from("moe.esoteric", "flat", "Lupusreginaβ")
 }
 
-pub fn get_configuration() -> Result<Ini> {
+pub fn get_configuration_path() -> Result<PathBuf> {
     let project_dirs =
         get_project_dirs().ok_or_else(|| anyhow!("Failed to get project directories!"))?;
-    let config_path = project_dirs.config_dir().join("settings.ini");
+    Ok(project_dirs.config_dir().join("settings.ini"))
+}
+
+pub fn get_configuration() -> Result<Config> {
+    let config_path = get_configuration_path()?;
     if !config_path.exists() {
         fs::create_dir_all(
-            &config_path
+            config_path
                 .parent()
                 .ok_or_else(|| anyhow!("Failed to get parent of path!"))?,
         )?;
         fs::File::create(&config_path)?;
     }
-    Ini::load_from_file(config_path).map_err(|e| e.into())
-}
-
-#[derive(Clone)]
-pub struct DiscordMarkdownDecorator {
-    #[allow(dead_code)]
-    links: Vec<String>,
-}
-
-impl DiscordMarkdownDecorator {
-    /// Create a new `DiscordMarkdownDecorator`.
-    #[cfg_attr(feature = "clippy", allow(new_without_default_derive))]
-    pub fn new() -> DiscordMarkdownDecorator {
-        DiscordMarkdownDecorator { links: Vec::new() }
-    }
-}
-
-impl Default for DiscordMarkdownDecorator {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl TextDecorator for DiscordMarkdownDecorator {
-    type Annotation = ();
-
-    fn decorate_link_start(&mut self, _url: &str) -> (String, Self::Annotation) {
-        ("".to_string(), ())
-    }
-
-    fn decorate_link_end(&mut self) -> String {
-        "".to_string()
-    }
-
-    fn decorate_em_start(&mut self) -> (String, Self::Annotation) {
-        ("*".to_string(), ())
-    }
-
-    fn decorate_em_end(&mut self) -> String {
-        "*".to_string()
-    }
-
-    fn decorate_strong_start(&mut self) -> (String, Self::Annotation) {
-        ("**".to_string(), ())
-    }
-
-    fn decorate_strong_end(&mut self) -> String {
-        "**".to_string()
-    }
-
-    fn decorate_strikeout_start(&mut self) -> (String, Self::Annotation) {
-        ("--".to_string(), ())
-    }
-
-    fn decorate_strikeout_end(&mut self) -> String {
-        "--".to_string()
-    }
-
-    fn decorate_code_start(&mut self) -> (String, Self::Annotation) {
-        ("```".to_string(), ())
-    }
-
-    fn decorate_code_end(&mut self) -> String {
-        "```".to_string()
-    }
-
-    fn decorate_preformat_first(&mut self) -> Self::Annotation {}
-
-    fn decorate_preformat_cont(&mut self) -> Self::Annotation {}
-
-    fn decorate_image(&mut self, title: &str) -> (String, Self::Annotation) {
-        (title.to_string(), ())
-    }
-
-    fn header_prefix(&mut self, _level: usize) -> String {
-        String::from("**")
-    }
-
-    fn quote_prefix(&mut self) -> String {
-        String::from("`")
-    }
-
-    fn unordered_item_prefix(&mut self) -> String {
-        String::from("* ")
-    }
-
-    fn ordered_item_prefix(&mut self, i: i64) -> String {
-        format!("{}. ", i)
-    }
-
-    fn make_subblock_decorator(&self) -> Self {
-        DiscordMarkdownDecorator::new()
-    }
-
-    fn finalise(self) -> Vec<TaggedLine<()>> {
-        Vec::new()
-    }
+    let mut ini = Ini::load_from_file(config_path)?;
+    apply_env_overrides(&mut ini);
+    Config::from_ini(&ini).map_err(|e| e.into())
 }
@@ -0,0 +1,285 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use poise::serenity_prelude::{Colour, Http};
+use tracing::{debug, warn};
+
+use crate::db::vtuber::{self, Follow};
+use crate::db::{guild_settings, Database};
+use crate::i18n::{ordinal, tr, FALLBACK_LOCALE};
+use crate::infobox::WikiDate;
+use crate::reply::Embed;
+use crate::util::ConfigHandle;
+use crate::Error;
+
+/// How often the date is checked. Announcements go out at the first check of each UTC day.
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Birthday,
+    /// A debut anniversary, with the number of years if the debut year is known.
+    Anniversary(Option<i32>),
+}
+
+/// A birthday or debut anniversary of a followed VTuber.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub date: NaiveDate,
+    pub kind: EventKind,
+    pub follow: Follow,
+}
+
+impl Event {
+    /// Describes the event in `locale`, such as "Gawr Gura's 3rd debut anniversary".
+    pub fn describe(&self, locale: &str) -> String {
+        let name = self.follow.title.as_str();
+        match self.kind {
+            EventKind::Birthday => tr(locale, "vtuber-event-birthday", &[("name", name.into())]),
+            EventKind::Anniversary(Some(years)) => tr(
+                locale,
+                "vtuber-event-anniversary",
+                &[
+                    ("name", name.into()),
+                    ("years", years.into()),
+                    ("ordinal", ordinal(locale, years.into()).into()),
+                ],
+            ),
+            EventKind::Anniversary(None) => tr(
+                locale,
+                "vtuber-event-debut-anniversary",
+                &[("name", name.into())],
+            ),
+        }
+    }
+}
+
+/// Posts the day's birthdays and debut anniversaries of followed VTubers in each guild's
+/// announcement channel until the process exits.
+///
+/// The last announced day is stored in the database, so a restart doesn't repeat them.
+pub fn spawn_vtuber_announcer(http: Arc<Http>, db: Database, config: ConfigHandle) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = check(&http, &db, &config).await {
+                warn!(
+                    "Failed to announce VTuber birthdays and anniversaries: {}",
+                    e
+                );
+            }
+        }
+    });
+}
+
+async fn check(http: &Http, db: &Database, config: &ConfigHandle) -> Result<(), Error> {
+    let today = Utc::now().date_naive();
+    if db.run(vtuber::last_announced).await? >= Some(today) {
+        return Ok(());
+    }
+    let follows = db.run(vtuber::all).await?;
+    let settings = db.run(guild_settings::load_all).await?;
+    let colour = config.read().await.colours.vtuber;
+    for event in events_between(&follows, today, today) {
+        let guild_id = event.follow.guild_id;
+        let locale = settings
+            .get(&guild_id)
+            .and_then(|settings| settings.locale.as_deref())
+            .unwrap_or(FALLBACK_LOCALE);
+        let channel = match db
+            .run(move |connection| vtuber::channel(connection, guild_id))
+            .await?
+        {
+            Some(channel) => channel,
+            None => continue,
+        };
+        debug!(
+            "Announcing {} in {}",
+            event.describe(FALLBACK_LOCALE),
+            channel
+        );
+        let embed = event_embed(&event, colour, locale);
+        if let Err(e) = channel
+            .send_message(http, |m| m.set_embed(embed.truncated().into()))
+            .await
+        {
+            warn!(
+                "Failed to announce {} in channel {}: {}",
+                event.describe(FALLBACK_LOCALE),
+                channel,
+                e
+            );
+        }
+    }
+    db.run(move |connection| vtuber::set_last_announced(connection, today))
+        .await?;
+    Ok(())
+}
+
+fn event_embed(event: &Event, colour: Colour, locale: &str) -> Embed {
+    let name = event.follow.title.as_str();
+    let today = || {
+        tr(
+            locale,
+            "vtuber-announce-today",
+            &[("event", event.describe(locale).into())],
+        )
+    };
+    let (title, description) = match event.kind {
+        EventKind::Birthday => ("vtuber-announce-birthday", today()),
+        EventKind::Anniversary(Some(years)) => (
+            "vtuber-announce-anniversary",
+            tr(
+                locale,
+                "vtuber-announce-years",
+                &[("name", name.into()), ("years", years.into())],
+            ),
+        ),
+        EventKind::Anniversary(None) => ("vtuber-announce-anniversary", today()),
+    };
+    Embed::new()
+        .colour(colour)
+        .url(&event.follow.url)
+        .title(tr(locale, title, &[("name", name.into())]))
+        .description(description)
+        .footer(tr(locale, "vtuber-wiki-footer", &[]))
+}
+
+/// Every event from `from` to `to`, both inclusive, in date order.
+pub fn events_between(follows: &[Follow], from: NaiveDate, to: NaiveDate) -> Vec<Event> {
+    let mut events = Vec::new();
+    for year in from.year()..=to.year() {
+        for follow in follows {
+            let birthday = follow
+                .birthday
+                .and_then(|date| occurrence(date, year))
+                .map(|date| (date, EventKind::Birthday));
+            let anniversary = follow.debut.and_then(|debut| {
+                let years = debut.year.map(|debut_year| year - debut_year);
+                if years.is_some_and(|years| years < 1) {
+                    return None;
+                }
+                Some((occurrence(debut, year)?, EventKind::Anniversary(years)))
+            });
+            for (date, kind) in birthday.into_iter().chain(anniversary) {
+                if from <= date && date <= to {
+                    events.push(Event {
+                        date,
+                        kind,
+                        follow: follow.clone(),
+                    });
+                }
+            }
+        }
+    }
+    events.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then(a.follow.title.cmp(&b.follow.title))
+    });
+    events
+}
+
+/// The day `date` falls on in `year`, moving February 29th to the 28th outside leap years.
+fn occurrence(date: WikiDate, year: i32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, date.month, date.day).or_else(|| {
+        if (date.month, date.day) == (2, 29) {
+            NaiveDate::from_ymd_opt(year, 2, 28)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::GuildId;
+
+    use super::*;
+
+    fn follow(title: &str, birthday: Option<(u32, u32)>, debut: Option<(i32, u32, u32)>) -> Follow {
+        Follow {
+            guild_id: GuildId(1),
+            title: title.to_owned(),
+            url: format!("https://virtualyoutuber.fandom.com/wiki/{}", title),
+            birthday: birthday.map(|(month, day)| WikiDate {
+                year: None,
+                month,
+                day,
+            }),
+            debut: debut.map(|(year, month, day)| WikiDate {
+                year: Some(year),
+                month,
+                day,
+            }),
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn lists_events_in_order_across_new_year() {
+        let follows = [
+            follow("Gawr Gura", Some((6, 20)), Some((2020, 9, 13))),
+            follow("Kizuna AI", Some((6, 30)), Some((2016, 11, 29))),
+            follow("Shirakami Fubuki", Some((10, 5)), Some((2018, 6, 1))),
+        ];
+
+        let events = events_between(&follows, date(2022, 9, 1), date(2023, 6, 25))
+            .into_iter()
+            .map(|event| (event.date, event.describe("en-US")))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            [
+                (
+                    date(2022, 9, 13),
+                    "Gawr Gura's 2nd debut anniversary".to_owned()
+                ),
+                (date(2022, 10, 5), "Shirakami Fubuki's birthday".to_owned()),
+                (
+                    date(2022, 11, 29),
+                    "Kizuna AI's 6th debut anniversary".to_owned()
+                ),
+                (
+                    date(2023, 6, 1),
+                    "Shirakami Fubuki's 5th debut anniversary".to_owned()
+                ),
+                (date(2023, 6, 20), "Gawr Gura's birthday".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_the_debut_itself_and_moves_leap_days() {
+        let follows = [follow("Leap", Some((2, 29)), Some((2023, 1, 1)))];
+
+        let events = events_between(&follows, date(2023, 1, 1), date(2023, 12, 31));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].date, date(2023, 2, 28));
+        assert_eq!(events[0].kind, EventKind::Birthday);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::util::{get_configuration_path, ConfigHandle};
+
+/// How long to wait for an editor to finish writing before reloading.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Reloads the configuration whenever `settings.ini` changes on disk or the process receives
+/// SIGHUP.
+pub fn spawn_config_watcher(config: ConfigHandle) -> Result<()> {
+    let files = watched_files(&get_configuration_path()?);
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    // Watch the directories rather than the files, editors often replace the file on save.
+    let file_sender = sender.clone();
+    let watched = files.clone();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+            Ok(event)
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event.paths.iter().any(|p| watched.contains(&normalize(p))) =>
+            {
+                let _ = file_sender.send("file change");
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Error watching configuration: {}", e),
+        })?;
+    let mut directories = vec![];
+    for file in &files {
+        let directory = file
+            .parent()
+            .ok_or_else(|| anyhow!("Failed to get parent of path!"))?;
+        if !directories.contains(&directory) {
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+            directories.push(directory);
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = signal(SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                if sender.send("SIGHUP").is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        // Keep the watcher alive for as long as the task runs.
+        let _watcher = watcher;
+        while let Some(trigger) = receiver.recv().await {
+            tokio::time::sleep(DEBOUNCE).await;
+            while receiver.try_recv().is_ok() {}
+            info!("Reloading configuration after {}", trigger);
+            if let Err(e) = config.reload().await {
+                error!("Keeping the current configuration, reload failed: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// `path` with its directory made absolute and free of symlinks, so it compares equal to the
+/// paths in events however it was written. The file itself may not exist.
+fn normalize(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(directory), Some(name)) => {
+            let directory = if directory.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                directory
+            };
+            fs::canonicalize(directory)
+                .map(|directory| directory.join(name))
+                .unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The configuration file and, if it is a symlink, the file it points to.
+fn watched_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![normalize(path)];
+    if let Ok(target) = fs::canonicalize(path) {
+        if !files.contains(&target) {
+            files.push(target);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_relative_and_indirect_paths() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(directory.path()).unwrap();
+        fs::create_dir(root.join("config")).unwrap();
+        let indirect = directory.path().join("config/../config/settings.ini");
+        assert_eq!(normalize(&indirect), root.join("config/settings.ini"));
+        assert_eq!(
+            normalize(Path::new("settings.ini")),
+            std::env::current_dir().unwrap().join("settings.ini")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn watches_the_target_of_a_symlink() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(directory.path()).unwrap();
+        fs::create_dir(root.join("dotfiles")).unwrap();
+        fs::write(root.join("dotfiles/settings.ini"), "").unwrap();
+        std::os::unix::fs::symlink(
+            root.join("dotfiles/settings.ini"),
+            root.join("settings.ini"),
+        )
+        .unwrap();
+        assert_eq!(
+            watched_files(&root.join("settings.ini")),
+            vec![
+                root.join("settings.ini"),
+                root.join("dotfiles/settings.ini")
+            ]
+        );
+        assert_eq!(
+            watched_files(&root.join("dotfiles/settings.ini")),
+            vec![root.join("dotfiles/settings.ini")]
+        );
+    }
+}
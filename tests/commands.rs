@@ -0,0 +1,207 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Checks the replies built by commands for fake users, members and guilds.
+
+mod support;
+
+use chrono::NaiveDate;
+use lupusregina::commands::fun::{ddate_reply, eightball_reply, EIGHTBALL_ANSWERS};
+use lupusregina::commands::general::{guildinfo_reply, userinfo_reply};
+use lupusregina::reply::Reply;
+use poise::serenity_prelude::Colour;
+use serde_json::json;
+
+const GUILD_ID: u64 = 81384788765712384;
+const USER_ID: u64 = 80351110224678912;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn eightball_answers_with_the_chosen_fortune() {
+    let user = support::user(USER_ID, "Nelly");
+
+    let reply = eightball_reply("en-US", &user, None, "Will it rain?".to_owned(), 0);
+
+    let embed = reply.embed.unwrap();
+    assert_eq!(embed.description.as_deref(), Some("Will it rain?"));
+    assert_eq!(embed.field_value("🎱Eightball🎱"), Some("It is certain."));
+    assert_eq!(embed.author.unwrap().name, "Nelly");
+    assert!(reply.content.is_none());
+    assert!(!reply.ephemeral);
+}
+
+#[test]
+fn eightball_colour_follows_the_answer() {
+    let user = support::user(USER_ID, "Nelly");
+    let colour = |answer| {
+        eightball_reply("en-US", &user, None, "?".to_owned(), answer)
+            .embed
+            .unwrap()
+            .colour
+    };
+
+    assert_eq!(colour(9), Some(Colour::new(0x28_A7_45)));
+    assert_eq!(colour(10), Some(Colour::new(0xFF_C1_07)));
+    assert_eq!(colour(14), Some(Colour::new(0xFF_C1_07)));
+    assert_eq!(colour(EIGHTBALL_ANSWERS - 1), Some(Colour::new(0xDC_35_45)));
+}
+
+#[test]
+fn eightball_prefers_the_nickname() {
+    let user = support::user(USER_ID, "Nelly");
+    let member = support::member(GUILD_ID, &user, Some("Nel"), None);
+
+    let reply = eightball_reply("en-US", &user, Some(&member), "?".to_owned(), 3);
+
+    assert_eq!(reply.embed.unwrap().author.unwrap().name, "Nel");
+}
+
+#[test]
+fn ddate_formats_the_discordian_date() {
+    assert_eq!(
+        ddate_reply("en-US", date(2020, 1, 1)),
+        Reply::text("Today is Sweetmorn, the 1st day of Chaos in the YOLD 3186")
+    );
+    assert_eq!(
+        ddate_reply("en-US", date(2020, 1, 11)),
+        Reply::text("Today is Sweetmorn, the 11th day of Chaos in the YOLD 3186")
+    );
+    assert_eq!(
+        ddate_reply("en-US", date(2021, 1, 5)),
+        Reply::text("Today is Mungday, the 5th day of Chaos in the YOLD 3187")
+    );
+    assert_eq!(
+        ddate_reply("en-US", date(2021, 2, 19)),
+        Reply::text("Today is Chaosflux, the 50th day of Chaos in the YOLD 3187")
+    );
+    assert_eq!(
+        ddate_reply("en-US", date(2021, 12, 31)),
+        Reply::text("Today is Setting Orange, the 73rd day of The Aftermath in the YOLD 3187")
+    );
+}
+
+#[test]
+fn ddate_knows_st_tibs_day() {
+    assert_eq!(
+        ddate_reply("en-US", date(2020, 2, 29)),
+        Reply::text("Today is St. Tib's Day in the YOLD 3186")
+    );
+}
+
+#[test]
+fn guildinfo_describes_the_guild() {
+    let guild = support::guild(
+        GUILD_ID,
+        "Nazarick",
+        41,
+        json!({
+            "icon": "abcdef",
+            "premium_tier": 2,
+            "premium_subscription_count": 9,
+            "features": ["COMMUNITY"],
+        }),
+    );
+
+    let reply = guildinfo_reply("en-US", &guild);
+
+    assert!(reply.ephemeral);
+    let embed = reply.embed.unwrap();
+    let author = embed.author.clone().unwrap();
+    assert_eq!(author.name, "Nazarick");
+    assert_eq!(
+        author.icon_url.as_deref(),
+        Some("https://cdn.discordapp.com/icons/81384788765712384/abcdef.webp")
+    );
+    assert_eq!(embed.field_value("Guild ID"), Some("81384788765712384"));
+    assert_eq!(embed.field_value("Members"), Some("41"));
+    assert_eq!(embed.field_value("Features"), Some("[\"COMMUNITY\"]"));
+    assert_eq!(embed.field_value("Nitro Boost Level"), Some("Tier2"));
+    assert_eq!(embed.field_value("Nitro Boosts"), Some("9"));
+    assert!(embed.image.is_none());
+    assert_eq!(
+        embed.footer.as_deref(),
+        Some("Guild created at 2015-08-13T13:54:05.698Z")
+    );
+}
+
+#[test]
+fn guildinfo_shows_the_splash() {
+    let guild = support::guild(GUILD_ID, "Nazarick", 1, json!({ "splash": "splash" }));
+
+    let embed = guildinfo_reply("en-US", &guild).embed.unwrap();
+
+    assert!(embed.author.unwrap().icon_url.is_none());
+    assert!(embed
+        .image
+        .unwrap()
+        .contains("/splashes/81384788765712384/splash"));
+}
+
+#[test]
+fn userinfo_describes_the_member() {
+    let user = support::user(USER_ID, "Nelly");
+    let member = support::member(
+        GUILD_ID,
+        &user,
+        Some("Nel"),
+        Some("2021-06-01T12:00:00+00:00"),
+    );
+
+    let reply = userinfo_reply("en-US", &user, &member);
+
+    assert!(reply.ephemeral);
+    let embed = reply.embed.unwrap();
+    assert_eq!(embed.author.clone().unwrap().name, "Nelly");
+    assert_eq!(embed.field_value("Discriminator"), Some("#0042"));
+    assert_eq!(embed.field_value("User ID"), Some("80351110224678912"));
+    assert_eq!(embed.field_value("Nickname"), Some("Nel"));
+    assert_eq!(
+        embed.field_value("User Created"),
+        Some("2015-08-10T17:26:37.529Z")
+    );
+    assert!(embed
+        .field_value("Joined Server")
+        .unwrap()
+        .starts_with("2021-06-01"));
+}
+
+#[test]
+fn userinfo_without_nickname_or_join_date() {
+    let user = support::user(USER_ID, "Nelly");
+    let member = support::member(GUILD_ID, &user, None, None);
+
+    let embed = userinfo_reply("en-US", &user, &member).embed.unwrap();
+
+    assert_eq!(embed.field_value("Nickname"), Some("None"));
+    assert_eq!(embed.field_value("Joined Server"), Some("Unavailable"));
+}
+
+#[test]
+fn userinfo_in_the_reply_locale() {
+    let user = support::user(USER_ID, "Nelly");
+    let member = support::member(GUILD_ID, &user, None, None);
+
+    let embed = userinfo_reply("es-ES", &user, &member).embed.unwrap();
+
+    assert_eq!(embed.field_value("Apodo"), Some("Ninguno"));
+    assert_eq!(
+        embed.field_value("Se unió al servidor"),
+        Some("No disponible")
+    );
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Fake Discord users, members and guilds for running command logic without a connection.
+
+#![allow(dead_code)]
+
+use poise::serenity_prelude::{Guild, Member, User};
+use serde_json::{json, Value};
+
+/// A user with the given ID and name, and no avatar.
+pub fn user(id: u64, name: &str) -> User {
+    serde_json::from_value(json!({
+        "id": id.to_string(),
+        "username": name,
+        "discriminator": "0042",
+        "avatar": null,
+    }))
+    .expect("Invalid fake user")
+}
+
+/// `user` as a member of guild `guild_id`, joined at the RFC 3339 timestamp `joined_at`.
+pub fn member(guild_id: u64, user: &User, nick: Option<&str>, joined_at: Option<&str>) -> Member {
+    serde_json::from_value(json!({
+        "guild_id": guild_id.to_string(),
+        "user": user,
+        "nick": nick,
+        "joined_at": joined_at,
+        "roles": [],
+        "deaf": false,
+        "mute": false,
+    }))
+    .expect("Invalid fake member")
+}
+
+/// A guild with the given ID, name and member count, changed by `overrides` before being built.
+pub fn guild(id: u64, name: &str, member_count: u64, overrides: Value) -> Guild {
+    let mut guild = json!({
+        "id": id.to_string(),
+        "name": name,
+        "icon": null,
+        "splash": null,
+        "owner_id": "1",
+        "afk_timeout": 300,
+        "channels": [],
+        "default_message_notifications": 0,
+        "emojis": [],
+        "explicit_content_filter": 0,
+        "features": [],
+        "joined_at": "2020-01-01T00:00:00.000000+00:00",
+        "large": false,
+        "member_count": member_count,
+        "members": [],
+        "mfa_level": 0,
+        "presences": [],
+        "roles": [],
+        "verification_level": 0,
+        "voice_states": [],
+        "preferred_locale": "en-US",
+        "nsfw_level": 0,
+        "system_channel_flags": 0,
+        "stickers": [],
+    });
+    if let (Some(guild), Value::Object(overrides)) = (guild.as_object_mut(), overrides) {
+        guild.extend(overrides);
+    }
+    serde_json::from_value(guild).expect("Invalid fake guild")
+}
@@ -0,0 +1,389 @@
+/*
+ * Copyright 2020 Kenneth Swenson
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! Runs the AniList and wiki lookups against a local mock server serving recorded responses.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use lupusregina::cache::ResponseCache;
+use lupusregina::commands::vtuber::vtuber_embed;
+use lupusregina::commands::weeb::{anime_pages, manga_pages};
+use lupusregina::config::Config;
+use lupusregina::error::BotError;
+use lupusregina::http::{HttpClient, HttpError};
+use lupusregina::mediawiki::{self, Extraction};
+use lupusregina::util::ConfigHandle;
+use lupusregina::Error;
+use poise::serenity_prelude::{Colour, CreateEmbed};
+use reqwest::Url;
+use serde_json::Value;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::reload;
+
+use wiremock::matchers::{body_string_contains, method, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SITE: &str = "https://anilist.co/";
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {:?}: {}", path, e))
+}
+
+fn json(name: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(fixture(name), "application/json")
+}
+
+/// A client pointed at `server` with a short timeout and no retries.
+fn client(server: &MockServer) -> (HttpClient, Url) {
+    let mut config = Config::default();
+    let uri = Url::parse(&server.uri()).unwrap();
+    config.api.anilist = uri.clone();
+    config.api.vtuber_wiki = uri.join("api.php").unwrap();
+    config.http.timeout = Duration::from_millis(500);
+    config.http.retries = 0;
+    let (_, log_filter) = reload::Layer::new(Targets::new());
+    let config = ConfigHandle::new(config, log_filter);
+    (
+        HttpClient::new(config, Arc::new(ResponseCache::default())),
+        uri,
+    )
+}
+
+fn site() -> Url {
+    Url::parse(SITE).unwrap()
+}
+
+fn get<'a>(embed: &'a CreateEmbed, key: &str) -> &'a str {
+    embed.0[key]
+        .as_str()
+        .unwrap_or_else(|| panic!("Embed has no {}", key))
+}
+
+fn field<'a>(embed: &'a CreateEmbed, name: &str) -> &'a str {
+    embed.0["fields"]
+        .as_array()
+        .expect("Embed has no fields")
+        .iter()
+        .find(|f| f["name"] == name)
+        .and_then(|f| f["value"].as_str())
+        .unwrap_or_else(|| panic!("Embed has no {} field", name))
+}
+
+fn is_timeout(error: &Error) -> bool {
+    matches!(
+        error.downcast_ref::<HttpError>(),
+        Some(HttpError::Timeout { .. })
+    )
+}
+
+async fn anilist_mock(server: &MockServer, search: &str, response: ResponseTemplate) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(search))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+async fn wiki_mock(server: &MockServer, action: &str, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(query_param("action", action))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn anime_builds_a_page_per_match() {
+    let server = MockServer::start().await;
+    anilist_mock(&server, "Cowboy Bebop", json("anilist/anime.json")).await;
+    let (http, endpoint) = client(&server);
+
+    let pages = anime_pages(
+        &http,
+        &endpoint,
+        &site(),
+        Colour::BLUE,
+        "en-US",
+        "Cowboy Bebop".to_owned(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(pages.len(), 2);
+    let bebop = &pages[0];
+    assert_eq!(get(bebop, "url"), "https://anilist.co/anime/1");
+    assert!(get(bebop, "title").contains("Cowboy Bebop"));
+    assert!(get(bebop, "description").ends_with("solar system.\n\n*(Source: Bandai)*"));
+    assert_eq!(field(bebop, "Episodes"), "26");
+    assert_eq!(bebop.0["color"], Value::from(Colour::BLUE.0));
+    assert_eq!(get(&pages[1], "url"), "https://anilist.co/anime/5");
+}
+
+#[tokio::test]
+async fn manga_builds_an_embed() {
+    let server = MockServer::start().await;
+    anilist_mock(&server, "Berserk", json("anilist/manga.json")).await;
+    let (http, endpoint) = client(&server);
+
+    let pages = manga_pages(
+        &http,
+        &endpoint,
+        &site(),
+        Colour::BLUE,
+        "en-US",
+        "Berserk".to_owned(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(pages.len(), 1);
+    assert_eq!(get(&pages[0], "url"), "https://anilist.co/manga/30002");
+    assert!(get(&pages[0], "title").contains("Berserk"));
+}
+
+#[tokio::test]
+async fn anime_without_results_is_an_error() {
+    let server = MockServer::start().await;
+    anilist_mock(&server, "nothing", json("anilist/empty.json")).await;
+    let (http, endpoint) = client(&server);
+
+    let error = anime_pages(
+        &http,
+        &endpoint,
+        &site(),
+        Colour::BLUE,
+        "en-US",
+        "nothing".to_owned(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<BotError>(),
+        Some(BotError::NotFound(query)) if query == "nothing"
+    ));
+}
+
+#[tokio::test]
+async fn manga_with_malformed_json_is_an_error() {
+    let server = MockServer::start().await;
+    anilist_mock(
+        &server,
+        "Berserk",
+        ResponseTemplate::new(200).set_body_raw("{\"data\": {\"Page\": ", "application/json"),
+    )
+    .await;
+    let (http, endpoint) = client(&server);
+
+    let error = manga_pages(
+        &http,
+        &endpoint,
+        &site(),
+        Colour::BLUE,
+        "en-US",
+        "Berserk".to_owned(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<HttpError>(),
+        Some(HttpError::Decode(_))
+    ));
+}
+
+#[tokio::test]
+async fn anime_times_out() {
+    let server = MockServer::start().await;
+    anilist_mock(
+        &server,
+        "Cowboy Bebop",
+        json("anilist/anime.json").set_delay(Duration::from_secs(2)),
+    )
+    .await;
+    let (http, endpoint) = client(&server);
+
+    let error = anime_pages(
+        &http,
+        &endpoint,
+        &site(),
+        Colour::BLUE,
+        "en-US",
+        "Cowboy Bebop".to_owned(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(is_timeout(&error), "{:?}", error);
+}
+
+/// Searches for `name` and builds the embed for the best match, as the command does.
+async fn vtuber(http: &HttpClient, uri: &Url, name: &str) -> Result<CreateEmbed, Error> {
+    let api = uri.join("api.php").unwrap();
+    let (title, url) = mediawiki::search(http, &api, name)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or("No matches")?;
+    vtuber_embed(http, &api, &title, &url)
+        .await
+        .map(CreateEmbed::from)
+}
+
+#[tokio::test]
+async fn vtuber_builds_an_embed_from_the_introduction() {
+    let server = MockServer::start().await;
+    wiki_mock(&server, "opensearch", json("wiki/opensearch.json")).await;
+    wiki_mock(&server, "parse", json("wiki/parse.json")).await;
+    wiki_mock(&server, "imageserving", json("wiki/imageserving.json")).await;
+    let (http, uri) = client(&server);
+
+    let embed = vtuber(&http, &uri, "gura").await.unwrap();
+
+    assert_eq!(get(&embed, "title"), "Gawr Gura");
+    assert_eq!(
+        get(&embed, "url"),
+        "https://virtualyoutuber.fandom.com/wiki/Gawr_Gura"
+    );
+    let description = get(&embed, "description");
+    assert!(description.contains("English-speaking female Virtual YouTuber"));
+    assert!(!description.contains("Contents"));
+    assert!(!description.contains("shark girl"));
+    assert_eq!(field(&embed, "Affiliation"), "hololive (English)");
+    assert_eq!(field(&embed, "Debut Date"), "September 13, 2020");
+    assert_eq!(
+        field(&embed, "Channels"),
+        "[YouTube](https://www.youtube.com/channel/UCoSrY_IQQVpmIRZ9Xf-y93g)\n[Twitter](https://twitter.com/gawrgura)"
+    );
+    assert!(embed.0["thumbnail"]["url"]
+        .as_str()
+        .unwrap()
+        .contains("Gawr_Gura_Portrait.png"));
+}
+
+#[tokio::test]
+async fn vtuber_search_without_results_is_empty() {
+    let server = MockServer::start().await;
+    wiki_mock(&server, "opensearch", json("wiki/opensearch_empty.json")).await;
+    let (http, uri) = client(&server);
+
+    let matches = mediawiki::search(&http, &uri.join("api.php").unwrap(), "nobody in particular")
+        .await
+        .unwrap();
+
+    assert!(matches.is_empty());
+}
+
+#[tokio::test]
+async fn vtuber_search_lists_every_article_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(query_param("action", "opensearch"))
+        .and(query_param("limit", "10"))
+        .respond_with(json("wiki/opensearch_many.json"))
+        .mount(&server)
+        .await;
+    let (http, uri) = client(&server);
+
+    let matches = mediawiki::search(&http, &uri.join("api.php").unwrap(), "kiryu")
+        .await
+        .unwrap();
+
+    let titles = matches
+        .iter()
+        .map(|(title, _)| title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        titles,
+        ["Kiryu Coco", "Kiryuin Aoi", "Kiryu Kazuma (VTuber)"]
+    );
+    assert_eq!(
+        matches[2].1,
+        "https://virtualyoutuber.fandom.com/wiki/Kiryu_Kazuma_(VTuber)"
+    );
+}
+
+#[tokio::test]
+async fn vtuber_with_malformed_json_is_an_error() {
+    let server = MockServer::start().await;
+    wiki_mock(&server, "opensearch", json("wiki/opensearch.json")).await;
+    wiki_mock(
+        &server,
+        "parse",
+        ResponseTemplate::new(200).set_body_raw("<html>Service Unavailable", "text/html"),
+    )
+    .await;
+    let (http, uri) = client(&server);
+
+    let error = vtuber(&http, &uri, "gura").await.unwrap_err();
+
+    assert_eq!(error.to_string(), "Unable to get article Gawr Gura");
+}
+
+#[tokio::test]
+async fn vtuber_times_out() {
+    let server = MockServer::start().await;
+    wiki_mock(
+        &server,
+        "opensearch",
+        json("wiki/opensearch.json").set_delay(Duration::from_secs(2)),
+    )
+    .await;
+    let (http, uri) = client(&server);
+
+    let error = vtuber(&http, &uri, "gura").await.unwrap_err();
+
+    assert!(is_timeout(&error), "{:?}", error);
+}
+
+#[tokio::test]
+async fn wiki_uses_its_extraction_and_page_images() {
+    let server = MockServer::start().await;
+    wiki_mock(&server, "parse", json("wiki/parse_plain.json")).await;
+    wiki_mock(&server, "imageserving", json("wiki/error.json")).await;
+    wiki_mock(&server, "query", json("wiki/pageimages.json")).await;
+    let (http, uri) = client(&server);
+    let extraction = Extraction {
+        start: Some("<div class=\"lead\">".to_owned()),
+        end: Some("<div class=\"trivia\">".to_owned()),
+    };
+
+    let embed = mediawiki::article_embed(
+        &http,
+        &uri.join("api.php").unwrap(),
+        "Albedo",
+        "https://overlord.fandom.com/wiki/Albedo",
+        &extraction,
+    )
+    .await
+    .map(CreateEmbed::from)
+    .unwrap();
+
+    let description = get(&embed, "description");
+    assert!(description.contains("Overseer of the Floor Guardians"));
+    assert!(!description.contains("Navigation"));
+    assert!(!description.contains("Trivia"));
+    assert_eq!(
+        embed.0["thumbnail"]["url"].as_str(),
+        Some("https://static.example.org/images/Albedo.png")
+    );
+}